@@ -0,0 +1,41 @@
+// Stable public surface of the crate. Everything else (`kyber_*`,
+// `dilithium_*` modules, FSM step functions, packing helpers) is exposed
+// for the hardware models and may change between versions.
+
+// Kyber512 KEM
+pub use crate::kyber_kem::{kem_decaps, kem_encaps, kem_keypair, KyberDecapsKey, KyberEncapsKey};
+pub use crate::kyber_params::{CIPHERTEXTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES};
+
+/// Dilithium2 signatures.
+pub mod dilithium {
+    pub use crate::dilithium::{keygen, sign, verify, PublicKey, SecretKey, Signature};
+    pub use crate::dilithium_params::{PK_BYTES, SK_BYTES};
+}
+
+#[cfg(test)]
+mod tests {
+    // Only `crate::api` paths here: if a re-export goes missing this stops compiling.
+    use crate::api::{self, dilithium};
+
+    #[test]
+    fn kem_roundtrip_through_api() {
+        let (pk, sk): ([u8; api::PUBLICKEYBYTES], [u8; api::SECRETKEYBYTES]) =
+            api::kem_keypair(&[1u8; api::SYMBYTES]);
+        let (ct, ss): ([u8; api::CIPHERTEXTBYTES], [u8; api::SYMBYTES]) =
+            api::kem_encaps(&[2u8; api::SYMBYTES], &pk);
+        assert_eq!(api::kem_decaps(&ct, &sk), ss);
+    }
+
+    #[test]
+    fn signature_roundtrip_through_api() {
+        let (pk, sk): (dilithium::PublicKey, dilithium::SecretKey) = dilithium::keygen([3u8; 32]);
+        let pk_bytes: [u8; dilithium::PK_BYTES] = pk.to_bytes();
+        let sk_bytes: [u8; dilithium::SK_BYTES] = sk.to_bytes();
+        let pk = dilithium::PublicKey::from_bytes(&pk_bytes).unwrap();
+        let sk = dilithium::SecretKey::from_bytes(&sk_bytes).unwrap();
+
+        let sig: dilithium::Signature = dilithium::sign(&sk, b"api");
+        assert!(dilithium::verify(&pk, b"api", &sig));
+        assert!(!dilithium::verify(&pk, b"apj", &sig));
+    }
+}
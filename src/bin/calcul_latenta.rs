@@ -1,9 +1,14 @@
+use proiect::dilithium_ntt::NttState as DiliNtt;
+use proiect::kyber_ntt::NttState as KyberNtt;
+use proiect::ntt_control::{expected_ntt_cycles, FinalScaling};
+
 fn main() {
-    // cycle counts measured (include done-cycle)
-    let kyber_ntt: f64 = 1794.0;
-    let kyber_intt: f64 = 2306.0;
-    let dili_ntt: f64 = 2050.0;
-    let dili_intt: f64 = 2562.0;
+    // cycle counts from the FSM schedule (include done-cycle); the unit tests
+    // check these against the measured counts (1794 / 2306 / 2050 / 2562)
+    let kyber_ntt = expected_ntt_cycles::<KyberNtt>(256, false, FinalScaling::PerCoeff) as f64;
+    let kyber_intt = expected_ntt_cycles::<KyberNtt>(256, true, FinalScaling::PerCoeff) as f64;
+    let dili_ntt = expected_ntt_cycles::<DiliNtt>(256, false, FinalScaling::PerCoeff) as f64;
+    let dili_intt = expected_ntt_cycles::<DiliNtt>(256, true, FinalScaling::PerCoeff) as f64;
 
     // scenario frequencies in MHz
     let freqs_mhz = [100.0, 200.0, 250.0];
@@ -1,4 +1,4 @@
-use proiect::dilithium::{keygen, sign, verify};
+use proiect::api::dilithium::{keygen, sign, verify};
 
 fn main() {
     println!("--- Start Dilithium2 Demo ---");
@@ -7,6 +7,10 @@ fn main() {
     let dili_ntt: f64 = 2050.0;
     let dili_intt: f64 = 2562.0;
 
+    // NTT(a), NTT(b), pointwise, INTT chained on shared BRAM (cycle model)
+    let (_, pipe) = proiect::dilithium_ntt::poly_mul_pipeline(&[0; 256], &[0; 256]);
+    let dili_polymul: f64 = pipe.total() as f64;
+
     let freqs_mhz = [100.0, 200.0, 250.0];
 
     println!("| Core | Cycles | F (MHz) | Latency (us) | Throughput (ops/s) |");
@@ -19,6 +23,7 @@ fn main() {
             ("Kyber INTT", kyber_intt),
             ("Dilithium NTT", dili_ntt),
             ("Dilithium INTT", dili_intt),
+            ("Dilithium poly mul", dili_polymul),
         ] {
             let lat_us = c / f;
             let thr = f_hz / c;
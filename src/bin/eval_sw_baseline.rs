@@ -2,6 +2,7 @@ use std::time::{Duration, Instant};
 
 use proiect::dilithium;
 use proiect::dilithium_mldsa;
+use proiect::dilithium_ntt;
 
 use pqcrypto_kyber::kyber512 as pqk;
 use pqcrypto_traits::kem::{PublicKey as _, SecretKey as _, SharedSecret as _, Ciphertext as _};
@@ -95,4 +96,22 @@ fn main() {
         let _ = dilithium_mldsa::verify_44(&vk, msg, &sig);
     }
     println!("ref verify avg: {:?}", avg(t8.elapsed(), iters));
+
+    // --- NTT: FSM emulation vs direct loops (same output) ---
+    println!("\n=== CPU: Dilithium NTT, FSM emulation vs direct ===");
+    let a: [i32; 256] = core::array::from_fn(|i| (i as i32 * 7919) % 8_380_417);
+
+    let t9 = Instant::now();
+    for _ in 0..iters {
+        let mut x = a;
+        dilithium_ntt::ntt(&mut x);
+    }
+    println!("fsm ntt avg:    {:?}", avg(t9.elapsed(), iters));
+
+    let t10 = Instant::now();
+    for _ in 0..iters {
+        let mut x = a;
+        dilithium_ntt::ntt_direct(&mut x);
+    }
+    println!("direct ntt avg: {:?}", avg(t10.elapsed(), iters));
 }
@@ -3,17 +3,9 @@
 use std::fs;
 
 use proiect::kyber_kem;
+use proiect::util::hex;
 use proiect::kyber_params::{CIPHERTEXTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES};
 
-fn unhex(s: &str) -> Vec<u8> {
-    let s = s.trim();
-    assert!(s.len() % 2 == 0);
-    (0..s.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
-        .collect()
-}
-
 fn main() {
     let txt = fs::read_to_string("kat_kyber512.txt").expect("missing kat_kyber512.txt");
     let mut ok = 0usize;
@@ -68,17 +60,17 @@ fn main() {
         }
 
         if let Some(v) = line.strip_prefix("seed_kp = ") {
-            seed_kp = Some(unhex(v));
+            seed_kp = Some(hex::decode(v).expect("invalid hex"));
         } else if let Some(v) = line.strip_prefix("seed_m  = ") {
-            seed_m = Some(unhex(v));
+            seed_m = Some(hex::decode(v).expect("invalid hex"));
         } else if let Some(v) = line.strip_prefix("pk = ") {
-            pk = Some(unhex(v));
+            pk = Some(hex::decode(v).expect("invalid hex"));
         } else if let Some(v) = line.strip_prefix("sk = ") {
-            sk = Some(unhex(v));
+            sk = Some(hex::decode(v).expect("invalid hex"));
         } else if let Some(v) = line.strip_prefix("ct = ") {
-            ct = Some(unhex(v));
+            ct = Some(hex::decode(v).expect("invalid hex"));
         } else if let Some(v) = line.strip_prefix("ss = ") {
-            ss = Some(unhex(v));
+            ss = Some(hex::decode(v).expect("invalid hex"));
         }
     }
 
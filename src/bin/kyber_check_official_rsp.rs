@@ -1,100 +1,215 @@
 // Checks official Kyber512 KAT file (PQCkemKAT_1632.rsp) against YOUR implementation,
 // by reproducing the NIST KAT RNG (AES-CTR DRBG) and feeding the same randomness.
+//
+// The file is read line by line through a BufReader and each test case is
+// checked as soon as its block ends, so only one case is held in memory.
 
-use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
 use proiect::nist_drbg::NistDrbg;
+use proiect::util::hex;
 use proiect::kyber_kem::{crypto_kem_enc_deterministic, crypto_kem_keypair_deterministic, crypto_kem_dec};
 use proiect::kyber_params::{CIPHERTEXTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES};
 
-fn unhex(s: &str) -> Vec<u8> {
-    let s = s.trim();
-    assert!(s.len() % 2 == 0);
-    (0..s.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
-        .collect()
+/// One `count = ...` block of the .rsp file.
+struct KemCase {
+    count: u32,
+    seed: [u8; 48],
+    pk: [u8; PUBLICKEYBYTES],
+    sk: [u8; SECRETKEYBYTES],
+    ct: [u8; CIPHERTEXTBYTES],
+    ss: [u8; SYMBYTES],
 }
 
-fn main() {
-    let path = "kat/PQCkemKAT_1632.rsp";
-    let txt = fs::read_to_string(path).expect("missing kat/PQCkemKAT_1632.rsp");
-
-    let mut count: Option<u32> = None;
-    let mut seed: Option<[u8; 48]> = None;
-    let mut pk: Option<[u8; PUBLICKEYBYTES]> = None;
-    let mut sk: Option<[u8; SECRETKEYBYTES]> = None;
-    let mut ct: Option<[u8; CIPHERTEXTBYTES]> = None;
-    let mut ss: Option<[u8; SYMBYTES]> = None;
-
-    let mut ok = 0usize;
-
-    for line in txt.lines() {
-        let line = line.trim();
-
-        if line.is_empty() {
-            if let (Some(_c), Some(seed48), Some(pk_exp), Some(sk_exp), Some(ct_exp), Some(ss_exp)) =
-                (count, seed, pk, sk, ct, ss)
-            {
-                // 1) init DRBG from official seed
-                let mut drbg = NistDrbg::new(&seed48);
-
-                // 2) reproduce Kyber ref call order:
-                //    - keypair: randombytes(d,32), randombytes(z,32)
-                //    - encaps:  randombytes(seed_m_raw,32)
-                let mut d = [0u8; SYMBYTES];
-                let mut z = [0u8; SYMBYTES];
-                let mut seed_m_raw = [0u8; SYMBYTES];
-
-                drbg.randombytes(&mut d);
-                drbg.randombytes(&mut z);
-                drbg.randombytes(&mut seed_m_raw);
-
-                // 3) run YOUR Kyber with same randomness
-                let (pk_got, sk_got) = crypto_kem_keypair_deterministic(&d, &z);
-                assert_eq!(pk_got, pk_exp, "pk mismatch at count={:?}", count);
-                assert_eq!(sk_got, sk_exp, "sk mismatch at count={:?}", count);
-
-                let (ct_got, ss_got) = crypto_kem_enc_deterministic(&pk_got, &seed_m_raw);
-                assert_eq!(ct_got, ct_exp, "ct mismatch at count={:?}", count);
-                assert_eq!(ss_got, ss_exp, "ss(encaps) mismatch at count={:?}", count);
-
-                let ss2 = crypto_kem_dec(&sk_got, &ct_got);
-                assert_eq!(ss2, ss_exp, "ss(decaps) mismatch at count={:?}", count);
-
-                ok += 1;
-            }
+/// Fields seen so far in the current block.
+#[derive(Default)]
+struct Partial {
+    count: Option<u32>,
+    seed: Option<[u8; 48]>,
+    pk: Option<[u8; PUBLICKEYBYTES]>,
+    sk: Option<[u8; SECRETKEYBYTES]>,
+    ct: Option<[u8; CIPHERTEXTBYTES]>,
+    ss: Option<[u8; SYMBYTES]>,
+}
 
-            // reset
-            count = None;
-            seed = None;
-            pk = None;
-            sk = None;
-            ct = None;
-            ss = None;
-            continue;
-        }
+impl Partial {
+    fn take_case(&mut self) -> Option<KemCase> {
+        let p = std::mem::take(self);
+        Some(KemCase { count: p.count?, seed: p.seed?, pk: p.pk?, sk: p.sk?, ct: p.ct?, ss: p.ss? })
+    }
 
+    fn add_line(&mut self, line: &str) {
         if let Some(v) = line.strip_prefix("count = ") {
-            count = Some(v.parse().unwrap());
+            self.count = Some(v.parse().unwrap());
         } else if let Some(v) = line.strip_prefix("seed = ") {
-            let b = unhex(v);
-            let seed48_arr: [u8; 48] = b.try_into().expect("seed must be 48 bytes");
-            seed = Some(seed48_arr);
+            let b = hex::decode(v).expect("invalid hex");
+            self.seed = Some(b.try_into().expect("seed must be 48 bytes"));
         } else if let Some(v) = line.strip_prefix("pk = ") {
-            let b = unhex(v);
-            pk = Some(b.try_into().unwrap());
+            let b = hex::decode(v).expect("invalid hex");
+            self.pk = Some(b.try_into().unwrap());
         } else if let Some(v) = line.strip_prefix("sk = ") {
-            let b = unhex(v);
-            sk = Some(b.try_into().unwrap());
+            let b = hex::decode(v).expect("invalid hex");
+            self.sk = Some(b.try_into().unwrap());
         } else if let Some(v) = line.strip_prefix("ct = ") {
-            let b = unhex(v);
-            ct = Some(b.try_into().unwrap());
+            let b = hex::decode(v).expect("invalid hex");
+            self.ct = Some(b.try_into().unwrap());
         } else if let Some(v) = line.strip_prefix("ss = ") {
-            let b = unhex(v);
-            ss = Some(b.try_into().unwrap());
+            let b = hex::decode(v).expect("invalid hex");
+            self.ss = Some(b.try_into().unwrap());
         }
     }
+}
+
+/// Yields the complete cases of an .rsp stream in file order. A block ends
+/// at a blank line or at end of input; blocks missing a field are skipped.
+struct RspCases<R: BufRead> {
+    lines: std::io::Lines<R>,
+    partial: Partial,
+}
+
+impl<R: BufRead> RspCases<R> {
+    fn new(reader: R) -> Self {
+        Self { lines: reader.lines(), partial: Partial::default() }
+    }
+}
+
+impl<R: BufRead> Iterator for RspCases<R> {
+    type Item = KemCase;
+
+    fn next(&mut self) -> Option<KemCase> {
+        loop {
+            match self.lines.next() {
+                Some(line) => {
+                    let line = line.expect("read error");
+                    let line = line.trim();
+                    if line.is_empty() {
+                        if let Some(case) = self.partial.take_case() {
+                            return Some(case);
+                        }
+                    } else {
+                        self.partial.add_line(line);
+                    }
+                }
+                None => return self.partial.take_case(),
+            }
+        }
+    }
+}
+
+fn check_case(case: &KemCase) {
+    let count = case.count;
+
+    // 1) init DRBG from official seed
+    let mut drbg = NistDrbg::new(&case.seed);
+
+    // 2) reproduce Kyber ref call order:
+    //    - keypair: randombytes(d,32), randombytes(z,32)
+    //    - encaps:  randombytes(seed_m_raw,32)
+    let mut d = [0u8; SYMBYTES];
+    let mut z = [0u8; SYMBYTES];
+    let mut seed_m_raw = [0u8; SYMBYTES];
+
+    drbg.randombytes(&mut d);
+    drbg.randombytes(&mut z);
+    drbg.randombytes(&mut seed_m_raw);
+
+    // 3) run YOUR Kyber with same randomness
+    let (pk_got, sk_got) = crypto_kem_keypair_deterministic(&d, &z);
+    assert_eq!(pk_got, case.pk, "pk mismatch at count={}", count);
+    assert_eq!(sk_got, case.sk, "sk mismatch at count={}", count);
+
+    let (ct_got, ss_got) = crypto_kem_enc_deterministic(&pk_got, &seed_m_raw);
+    assert_eq!(ct_got, case.ct, "ct mismatch at count={}", count);
+    assert_eq!(ss_got, case.ss, "ss(encaps) mismatch at count={}", count);
+
+    let ss2 = crypto_kem_dec(&sk_got, &ct_got);
+    assert_eq!(ss2, case.ss, "ss(decaps) mismatch at count={}", count);
+}
+
+/// Checks every case of the stream, panicking on the first mismatch.
+/// Returns the number of cases checked.
+fn check_rsp<R: BufRead>(reader: R) -> usize {
+    RspCases::new(reader).map(|case| check_case(&case)).count()
+}
+
+fn main() {
+    let path = "kat/PQCkemKAT_1632.rsp";
+    let file = File::open(path).expect("missing kat/PQCkemKAT_1632.rsp");
+
+    let ok = check_rsp(BufReader::new(file));
 
     println!("Official KAT check OK for {} testcases", ok);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // The checker before streaming: whole file in memory, a case is counted
+    // when a blank line closes a block with every field present.
+    fn full_read_count(txt: &str) -> usize {
+        let mut partial = Partial::default();
+        let mut ok = 0usize;
+        for line in txt.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                if let Some(case) = partial.take_case() {
+                    check_case(&case);
+                    ok += 1;
+                }
+                continue;
+            }
+            partial.add_line(line);
+        }
+        ok
+    }
+
+    // Cases in the .rsp layout, generated by this implementation from the
+    // same DRBG call order the checker replays.
+    fn synthetic_rsp(cases: u8) -> String {
+        let mut out = String::from("# Kyber512\n\n");
+        for count in 0..cases {
+            let seed: [u8; 48] = core::array::from_fn(|i| (i as u8).wrapping_mul(7) ^ count);
+            let mut drbg = NistDrbg::new(&seed);
+            let (mut d, mut z, mut m) = ([0u8; SYMBYTES], [0u8; SYMBYTES], [0u8; SYMBYTES]);
+            drbg.randombytes(&mut d);
+            drbg.randombytes(&mut z);
+            drbg.randombytes(&mut m);
+            let (pk, sk) = crypto_kem_keypair_deterministic(&d, &z);
+            let (ct, ss) = crypto_kem_enc_deterministic(&pk, &m);
+            out += &format!(
+                "count = {}\nseed = {}\npk = {}\nsk = {}\nct = {}\nss = {}\n\n",
+                count,
+                hex::encode(&seed).to_uppercase(),
+                hex::encode(&pk).to_uppercase(),
+                hex::encode(&sk).to_uppercase(),
+                hex::encode(&ct).to_uppercase(),
+                hex::encode(&ss).to_uppercase(),
+            );
+        }
+        out
+    }
+
+    #[test]
+    fn streaming_matches_full_read() {
+        let txt = synthetic_rsp(4);
+
+        let path = std::env::temp_dir().join(format!("kyber_rsp_stream_{}.rsp", std::process::id()));
+        File::create(&path).unwrap().write_all(txt.as_bytes()).unwrap();
+        let streamed = check_rsp(BufReader::new(File::open(&path).unwrap()));
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(streamed, 4);
+        assert_eq!(streamed, full_read_count(&txt));
+
+        // a block with a field missing is skipped by both
+        let broken = txt.replacen("ss = ", "xx = ", 1);
+        assert_eq!(check_rsp(broken.as_bytes()), 3);
+        assert_eq!(full_read_count(&broken), 3);
+
+        // and a last case without a trailing blank line is still checked
+        assert_eq!(check_rsp(txt.trim_end().as_bytes()), 4);
+    }
+}
@@ -5,14 +5,9 @@ use std::fs::File;
 use std::io::Write;
 
 use proiect::kyber_kem;
+use proiect::util::hex;
 use proiect::kyber_params::{CIPHERTEXTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES};
 
-fn hex(bytes: &[u8]) -> String {
-    let mut s = String::with_capacity(bytes.len() * 2);
-    for b in bytes { s.push_str(&format!("{:02x}", b)); }
-    s
-}
-
 fn main() {
     let mut f = File::create("kat_kyber512.txt").expect("create output");
 
@@ -32,12 +27,12 @@ fn main() {
         assert_eq!(ss1, ss2, "internal KEM mismatch at i={}", i);
 
         writeln!(f, "count = {}", i).unwrap();
-        writeln!(f, "seed_kp = {}", hex(&seed_kp)).unwrap();
-        writeln!(f, "seed_m  = {}", hex(&seed_m)).unwrap();
-        writeln!(f, "pk = {}", hex(&pk)).unwrap();
-        writeln!(f, "sk = {}", hex(&sk)).unwrap();
-        writeln!(f, "ct = {}", hex(&ct)).unwrap();
-        writeln!(f, "ss = {}", hex(&ss1)).unwrap();
+        writeln!(f, "seed_kp = {}", hex::encode(&seed_kp)).unwrap();
+        writeln!(f, "seed_m  = {}", hex::encode(&seed_m)).unwrap();
+        writeln!(f, "pk = {}", hex::encode(&pk)).unwrap();
+        writeln!(f, "sk = {}", hex::encode(&sk)).unwrap();
+        writeln!(f, "ct = {}", hex::encode(&ct)).unwrap();
+        writeln!(f, "ss = {}", hex::encode(&ss1)).unwrap();
         writeln!(f).unwrap();
 
         // sanity sizes
@@ -2,6 +2,7 @@ use rhdl::prelude::*;
 
 // KYBER 
 use proiect::kyber_ntt::{ntt_step as kyber_step, NttIn as KyberIn, NttState as KyberState, Coeff as KyberCoeff};
+use proiect::kyber_ntt_single_port::run_ntt_single_port;
 
 // DILITHIUM 
 use proiect::dilithium_ntt::{ntt_step as dili_step, NttIn as DiliIn, NttState as DiliState, Coeff as DiliCoeff};
@@ -121,6 +122,11 @@ fn main() {
     let k_fwd = kyber_cycles(false);
     let k_inv = kyber_cycles(true);
 
+    // Kyber, single-port BRAM (one access per cycle)
+    let mut mem: [KyberCoeff; 256] = [signed::<U16>(0); 256];
+    let k_sp_fwd = run_ntt_single_port(&mut mem, false);
+    let k_sp_inv = run_ntt_single_port(&mut mem, true);
+
     // Dilithium
     let d_fwd = dilithium_cycles(false);
     let d_inv = dilithium_cycles(true);
@@ -128,6 +134,23 @@ fn main() {
     println!("Cicluri numarate");
     println!("Kyber     NTT  cycles = {k_fwd}");
     println!("Kyber     INTT cycles = {k_inv}");
+    println!("Kyber     NTT  cycles (single-port BRAM) = {k_sp_fwd}");
+    println!("Kyber     INTT cycles (single-port BRAM) = {k_sp_inv}");
     println!("Dilithium NTT  cycles = {d_fwd}");
     println!("Dilithium INTT cycles = {d_inv}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // eval_perf_table and calcul_latenta publish these; a latency change in
+    // either NTT FSM has to update the table too.
+    #[test]
+    fn ntt_cycle_counts_pinned() {
+        assert_eq!(kyber_cycles(false), 1794);
+        assert_eq!(kyber_cycles(true), 2306);
+        assert_eq!(dilithium_cycles(false), 2050);
+        assert_eq!(dilithium_cycles(true), 2562);
+    }
+}
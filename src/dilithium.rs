@@ -1,16 +1,32 @@
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
 use crate::shake::shake256;
-use crate::dilithium_params::{Dilithium2, N, Q, K, L, D, GAMMA1, GAMMA2, BETA, OMEGA, PK_BYTES, POLYW1_PACKEDBYTES};
-use crate::dilithium_poly::{Poly, PolyVec};
+use crate::xof_stream::{ShakeAbsorber, ShakeStream};
+use crate::dilithium_params::{
+    Dilithium2, DilithiumParams, N, K, L, D, GAMMA1, GAMMA2, BETA, OMEGA, PK_BYTES, SK_BYTES,
+    CTILDE_BYTES,
+    POLYETA_PACKEDBYTES, POLYT1_PACKEDBYTES, POLYW1_PACKEDBYTES,
+};
+use crate::dilithium_poly::{Poly, PolyMat, PolyVec};
+use crate::dilithium_reduce::center_mod_q;
 use crate::dilithium_rounding::{power2round, high_bits, low_bits, make_hint, use_hint, norm_bound};
-use crate::dilithium_sample::{expand_a, expand_s, expand_mask, challenge, mat_vec_mul_ntt};
+use crate::dilithium_sample::{expand_a, expand_s, expand_mask, challenge_ntt, mat_vec_mul_ntt, mat_vec_mul_ntt_streamed};
 use crate::dilithium_pack::{
-    pack_poly_t1, polyvec_w1_bytes, poly_check_norm,
+    pack_poly_t1, pack_polyvec_eta, pack_polyvec_t0, polyvec_w1_bytes, polyvec_w1_bytes_into, polyvec_check_norm,
+    unpack_poly_t1, unpack_polyvec_eta, unpack_polyvec_t0,
 };
+use crate::util::key_header::{self, Algorithm, DILITHIUM2_LEVEL};
+use crate::util::rand::RandomBytes;
 
 #[derive(Clone, Debug)]
 pub struct PublicKey {
     pub rho: [u8; 32],
+    /// positive, [0, 2^10)
     pub t1: PolyVec<K>,
+    /// H(rho || t1), filled in by `keygen` and `from_bytes` so `verify` does
+    /// not re-serialize and re-hash the key. Set it to None (or recompute it)
+    /// after editing rho or t1 by hand.
+    pub tr: Option<[u8; 64]>,
 }
 
 #[derive(Clone, Debug)]
@@ -18,52 +34,218 @@ pub struct SecretKey {
     pub rho: [u8; 32],
     pub key: [u8; 32],
     pub tr: [u8; 64],
+    /// centered, [-ETA, ETA]
     pub s1: PolyVec<L>,
+    /// centered, [-ETA, ETA]
     pub s2: PolyVec<K>,
+    /// centered, (-2^(D-1), 2^(D-1)]
     pub t0: PolyVec<K>,
 }
 
-#[derive(Clone, Debug)]
+/// Equality compares c_tilde, every z coefficient and the h bit pattern
+/// (i.e. the hint positions), so two deterministic signatures can be
+/// checked with `assert_eq!`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Signature {
-    pub c_tilde: [u8; 32],
+    pub c_tilde: [u8; CTILDE_BYTES],
+    /// centered, |z| < GAMMA1 - BETA
     pub z: PolyVec<L>,
+    /// 0 or 1
     pub h: PolyVec<K>,
 }
 
+impl PublicKey {
+    /// rho || t1 (10 bits per coefficient)
+    pub fn to_bytes(&self) -> [u8; PK_BYTES] {
+        let mut out = [0u8; PK_BYTES];
+        out[..32].copy_from_slice(&self.rho);
+        for (i, chunk) in out[32..].chunks_exact_mut(POLYT1_PACKEDBYTES).enumerate() {
+            chunk.copy_from_slice(&pack_poly_t1(&self.t1.v[i]));
+        }
+        out
+    }
+
+    /// Inverse of `to_bytes`. None if the length is not PK_BYTES.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != PK_BYTES {
+            return None;
+        }
+        let mut rho = [0u8; 32];
+        rho.copy_from_slice(&bytes[..32]);
+        let mut t1 = PolyVec::<K>::default();
+        for (i, chunk) in bytes[32..].chunks_exact(POLYT1_PACKEDBYTES).enumerate() {
+            t1.v[i] = unpack_poly_t1(chunk.try_into().unwrap());
+        }
+        let mut pk = PublicKey { rho, t1, tr: None };
+        pk.tr = Some(pk.compute_tr());
+        Some(pk)
+    }
+
+    /// H(rho || t1) from the key material, ignoring the cached value.
+    pub fn compute_tr(&self) -> [u8; 64] {
+        compute_tr(&self.rho, &self.t1)
+    }
+
+    /// The cached tr, or `compute_tr()` if there is none.
+    pub fn tr(&self) -> [u8; 64] {
+        self.tr.unwrap_or_else(|| self.compute_tr())
+    }
+
+    /// `to_bytes` behind a `util::key_header` header, for key storage.
+    pub fn to_bytes_versioned(&self) -> Vec<u8> {
+        key_header::wrap(Algorithm::Dilithium, DILITHIUM2_LEVEL, &self.to_bytes())
+    }
+
+    /// Inverse of `to_bytes_versioned`. None on any header mismatch.
+    pub fn from_bytes_versioned(bytes: &[u8]) -> Option<Self> {
+        Self::from_bytes(key_header::unwrap(bytes, Algorithm::Dilithium, DILITHIUM2_LEVEL)?)
+    }
+}
+
+impl SecretKey {
+    /// rho || key || tr || s1 || s2 || t0
+    pub fn to_bytes(&self) -> [u8; SK_BYTES] {
+        let mut out = Vec::with_capacity(SK_BYTES);
+        out.extend_from_slice(&self.rho);
+        out.extend_from_slice(&self.key);
+        out.extend_from_slice(&self.tr);
+        out.extend_from_slice(&pack_polyvec_eta::<Dilithium2, L>(&self.s1));
+        out.extend_from_slice(&pack_polyvec_eta::<Dilithium2, K>(&self.s2));
+        out.extend_from_slice(&pack_polyvec_t0::<K>(&self.t0));
+        out.try_into().unwrap()
+    }
+
+    /// Inverse of `to_bytes`. None if the length is wrong or an s1/s2
+    /// coefficient is outside [-ETA, ETA].
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != SK_BYTES {
+            return None;
+        }
+        let mut rho = [0u8; 32];
+        let mut key = [0u8; 32];
+        let mut tr = [0u8; 64];
+        rho.copy_from_slice(&bytes[..32]);
+        key.copy_from_slice(&bytes[32..64]);
+        tr.copy_from_slice(&bytes[64..128]);
+
+        let (s1_bytes, rest) = bytes[128..].split_at(L * POLYETA_PACKEDBYTES);
+        let (s2_bytes, t0_bytes) = rest.split_at(K * POLYETA_PACKEDBYTES);
+        let s1 = unpack_polyvec_eta::<Dilithium2, L>(s1_bytes)?;
+        let s2 = unpack_polyvec_eta::<Dilithium2, K>(s2_bytes)?;
+        let t0 = unpack_polyvec_t0::<K>(t0_bytes)?;
+        Some(SecretKey { rho, key, tr, s1, s2, t0 })
+    }
+
+    /// `to_bytes` behind a `util::key_header` header, for key storage.
+    pub fn to_bytes_versioned(&self) -> Vec<u8> {
+        key_header::wrap(Algorithm::Dilithium, DILITHIUM2_LEVEL, &self.to_bytes())
+    }
+
+    /// Inverse of `to_bytes_versioned`. None on any header mismatch or when
+    /// `from_bytes` rejects the key.
+    pub fn from_bytes_versioned(bytes: &[u8]) -> Option<Self> {
+        Self::from_bytes(key_header::unwrap(bytes, Algorithm::Dilithium, DILITHIUM2_LEVEL)?)
+    }
+}
+
+/// tr = H(rho || t1) over the `PublicKey::to_bytes` encoding, absorbing rho
+/// and one packed t1 polynomial at a time instead of building the whole
+/// encoded key.
+pub fn compute_tr(rho: &[u8; 32], t1: &PolyVec<K>) -> [u8; 64] {
+    let mut h = ShakeAbsorber::shake256();
+    h.absorb(rho);
+    for p in t1.v.iter() {
+        h.absorb(&pack_poly_t1(p));
+    }
+    let mut tr = [0u8; 64];
+    h.finish().fill_bytes(&mut tr);
+    tr
+}
+
+/// Per-coefficient hint bitmap, `bitmap[i][j]` = h_i has a 1 at X^j.
+pub type HintBitmap = [[bool; N]; K];
+
+impl Signature {
+    /// `h` as a dense bitmap (the form the hardware hint unit produces).
+    pub fn hint_bitmap(&self) -> HintBitmap {
+        core::array::from_fn(|i| core::array::from_fn(|j| self.h.v[i].coeffs[j] != 0))
+    }
+
+    /// Builds a signature whose `h` comes from a bitmap.
+    pub fn from_bitmap(c_tilde: [u8; CTILDE_BYTES], z: PolyVec<L>, bitmap: &HintBitmap) -> Self {
+        let mut h = PolyVec::<K>::default();
+        for (p, row) in h.v.iter_mut().zip(bitmap.iter()) {
+            for (c, &bit) in p.coeffs.iter_mut().zip(row.iter()) {
+                *c = bit as i32;
+            }
+        }
+        Signature { c_tilde, z, h }
+    }
+}
+
 // Helper to center coefficients in [-Q/2, Q/2]
 fn normalize_poly(p: &mut Poly) {
     for c in p.coeffs.iter_mut() {
-        let mut v = *c % Q;
-        if v < 0 {
-            v += Q;
-        }
-        if v > Q / 2 {
-            v -= Q;
-        }
-        *c = v;
+        *c = center_mod_q(*c);
     }
 }
 
-fn hash_to_96(seed: &[u8; 32]) -> [u8; 96] {
-    let mut v = vec![0u8; 96];
-    shake256(seed, &mut v);
-    let mut out = [0u8; 96];
-    out.copy_from_slice(&v);
-    out
+/// How `keygen_with` turns the 32-byte seed into `(rho, rho_prime, key)`.
+pub trait SeedExpander {
+    fn expand(seed: &[u8; 32]) -> ([u8; 32], [u8; 64], [u8; 32]);
 }
 
-pub fn keygen(seed: [u8; 32]) -> (PublicKey, SecretKey) {
-    let z = hash_to_96(&seed);
+/// FIPS 204 seed expansion: `H(seed || K || L, 128)` split into
+/// `rho || rho_prime || key`. Used by `keygen`.
+pub struct Fips204;
+
+impl SeedExpander for Fips204 {
+    fn expand(seed: &[u8; 32]) -> ([u8; 32], [u8; 64], [u8; 32]) {
+        let mut inbuf = [0u8; 34];
+        inbuf[..32].copy_from_slice(seed);
+        inbuf[32] = K as u8;
+        inbuf[33] = L as u8;
+
+        let mut z = vec![0u8; 128];
+        shake256(&inbuf, &mut z);
+
+        let mut rho = [0u8; 32];
+        let mut rho_prime = [0u8; 64];
+        let mut key = [0u8; 32];
+        rho.copy_from_slice(&z[0..32]);
+        rho_prime.copy_from_slice(&z[32..96]);
+        key.copy_from_slice(&z[96..128]);
+        (rho, rho_prime, key)
+    }
+}
 
-    let mut rho = [0u8; 32];
-    let mut rho_prime = [0u8; 64];
-    rho.copy_from_slice(&z[0..32]);
-    rho_prime.copy_from_slice(&z[32..96]);
+/// The expansion keygen used before the FIPS 204 alignment, for keys made
+/// by older builds: `z = H(seed, 96)` split into `rho || rho_prime`, then
+/// `key = H(z, 32)`. No K/L domain bytes.
+pub struct Legacy;
+
+impl SeedExpander for Legacy {
+    fn expand(seed: &[u8; 32]) -> ([u8; 32], [u8; 64], [u8; 32]) {
+        let mut z = [0u8; 96];
+        shake256(seed, &mut z);
+
+        let mut rho = [0u8; 32];
+        let mut rho_prime = [0u8; 64];
+        let mut key = [0u8; 32];
+        rho.copy_from_slice(&z[0..32]);
+        rho_prime.copy_from_slice(&z[32..96]);
+        shake256(&z, &mut key);
+        (rho, rho_prime, key)
+    }
+}
+
+pub fn keygen(seed: [u8; 32]) -> (PublicKey, SecretKey) {
+    keygen_with::<Fips204>(seed)
+}
 
-    let mut key_vec = vec![0u8; 32];
-    shake256(&z, &mut key_vec);
-    let mut key = [0u8; 32];
-    key.copy_from_slice(&key_vec);
+/// `keygen` with the seed expansion chosen by `E`.
+pub fn keygen_with<E: SeedExpander>(seed: [u8; 32]) -> (PublicKey, SecretKey) {
+    let (rho, rho_prime, key) = E::expand(&seed);
 
     let a_hat = expand_a(&rho);
     let (s1, s2) = expand_s(&rho_prime);
@@ -74,11 +256,7 @@ pub fn keygen(seed: [u8; 32]) -> (PublicKey, SecretKey) {
 
     let mut t = PolyVec::<K>::default();
     for i in 0..K {
-        let mut acc = Poly::default();
-        for j in 0..L {
-            let tp = Poly::pointwise_mul(&a_hat.m[i][j], &s1_hat.v[j]);
-            acc.add_assign(&tp);
-        }
+        let mut acc = a_hat.row_times_vec(i, &s1_hat);
         acc.intt();
         normalize_poly(&mut acc);
         acc.add_assign(&s2.v[i]);
@@ -98,24 +276,260 @@ pub fn keygen(seed: [u8; 32]) -> (PublicKey, SecretKey) {
     }
 
     // tr = H(rho || t1)
-    let mut pk_bytes = Vec::with_capacity(PK_BYTES);
-    pk_bytes.extend_from_slice(&rho);
-    for i in 0..K {
-        pk_bytes.extend_from_slice(&pack_poly_t1(&t1.v[i]));
+    let mut pk = PublicKey { rho, t1, tr: None };
+    let tr = pk.compute_tr();
+    pk.tr = Some(tr);
+
+    (pk, SecretKey { rho, key, tr, s1, s2, t0 })
+}
+
+/// Secret-dependent intermediates of the signing rejection loop.
+/// Cleared before `sign` returns and again on drop.
+pub(crate) struct SignScratch {
+    pub(crate) rho_prime: [u8; 64],
+    pub(crate) s1_hat: PolyVec<L>,
+    pub(crate) s2_hat: PolyVec<K>,
+    pub(crate) t0_hat: PolyVec<K>,
+    pub(crate) y: PolyVec<L>,
+    pub(crate) w: PolyVec<K>,
+    pub(crate) w_minus_cs2: PolyVec<K>,
+    pub(crate) prod: Poly,
+}
+
+impl SignScratch {
+    pub(crate) fn new() -> Self {
+        Self {
+            rho_prime: [0u8; 64],
+            s1_hat: PolyVec::default(),
+            s2_hat: PolyVec::default(),
+            t0_hat: PolyVec::default(),
+            y: PolyVec::default(),
+            w: PolyVec::default(),
+            w_minus_cs2: PolyVec::default(),
+            prod: Poly::default(),
+        }
     }
-    
-    let mut tr_vec = vec![0u8; 64];
-    shake256(&pk_bytes, &mut tr_vec);
-    let mut tr = [0u8; 64];
-    tr.copy_from_slice(&tr_vec);
 
-    (
-        PublicKey { rho, t1 },
-        SecretKey { rho, key, tr, s1, s2, t0 },
-    )
+    pub(crate) fn clear(&mut self) {
+        self.rho_prime.zeroize();
+        self.s1_hat.zeroize();
+        self.s2_hat.zeroize();
+        self.t0_hat.zeroize();
+        self.y.zeroize();
+        self.w.zeroize();
+        self.w_minus_cs2.zeroize();
+        self.prod.zeroize();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_cleared(&self) -> bool {
+        fn zero<const DIM: usize>(pv: &PolyVec<DIM>) -> bool {
+            pv.v.iter().all(|p| p.coeffs.iter().all(|&c| c == 0))
+        }
+        self.rho_prime.iter().all(|&b| b == 0)
+            && zero(&self.s1_hat)
+            && zero(&self.s2_hat)
+            && zero(&self.t0_hat)
+            && zero(&self.y)
+            && zero(&self.w)
+            && zero(&self.w_minus_cs2)
+            && self.prod.coeffs.iter().all(|&c| c == 0)
+    }
+}
+
+impl Drop for SignScratch {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl ZeroizeOnDrop for SignScratch {}
+
+/// Why a signing attempt was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// ||z||_inf >= GAMMA1 - BETA
+    ZNorm,
+    /// ||LowBits(w - c*s2)||_inf >= GAMMA2 - BETA
+    LowBits,
+    /// ||c*t0||_inf >= GAMMA2
+    Ct0,
+    /// more than OMEGA hint bits set
+    Hints,
 }
 
+/// Per-attempt data from the rejection loop, passed to `sign_with_observer`.
+/// Checks after the failing one are not evaluated and report `None`.
+#[derive(Clone, Copy, Debug)]
+pub struct AttemptInfo {
+    /// 1-based attempt counter
+    pub attempt: u32,
+    /// mask nonce used for y
+    pub kappa: u16,
+    pub z_norm: i32,
+    pub r0_norm: Option<i32>,
+    pub ct0_norm: Option<i32>,
+    pub hint_count: Option<usize>,
+    /// None when the attempt produced the signature
+    pub rejected: Option<Rejection>,
+}
+
+/// Deterministic signing with the legacy Round 3 derivation
+/// rho' = H(key || mu), no rnd input. The result verifies like any other
+/// signature, but it is not FIPS 204's deterministic variant (rnd = 0^32
+/// over M', see `sign_ctx`).
 pub fn sign(sk: &SecretKey, msg: &[u8]) -> Signature {
+    let mut scratch = SignScratch::new();
+    sign_with_scratch(sk, msg, &mut scratch)
+}
+
+/// Same as `sign`, calling `observer` once per attempt of the rejection loop
+/// (the last call is the accepted attempt).
+pub fn sign_with_observer(
+    sk: &SecretKey,
+    msg: &[u8],
+    observer: &mut impl FnMut(AttemptInfo),
+) -> Signature {
+    let mut scratch = SignScratch::new();
+    let sig = sign_inner(sk, msg, None, &mut scratch, LoopConfig::default(), observer).expect(STUCK);
+    scratch.clear();
+    sig
+}
+
+/// Test hook: runs the rejection loop from `kappa_start` instead of 0, so a
+/// test can start on an attempt known to be rejected.
+#[cfg(test)]
+pub(crate) fn sign_from_kappa(
+    sk: &SecretKey,
+    msg: &[u8],
+    kappa_start: u16,
+    observer: &mut impl FnMut(AttemptInfo),
+) -> Signature {
+    let mut scratch = SignScratch::new();
+    let config = LoopConfig { kappa_start, ..LoopConfig::default() };
+    let sig = sign_inner(sk, msg, None, &mut scratch, config, observer).expect(STUCK);
+    scratch.clear();
+    sig
+}
+
+/// Same signature as `sign`, but never holds the K x L matrix A: its entries
+/// are resampled from rho inside every A*y product. Trades K*L SHAKE128
+/// expansions per attempt for the matrix memory (~57 KB at level 5).
+pub fn sign_low_mem(sk: &SecretKey, msg: &[u8]) -> Signature {
+    let mut scratch = SignScratch::new();
+    let config = LoopConfig { matrix: MatrixSource::Streamed, ..LoopConfig::default() };
+    let sig = sign_inner(sk, msg, None, &mut scratch, config, &mut |_| {}).expect(STUCK);
+    scratch.clear();
+    sig
+}
+
+/// `sign` that gives up after `max_attempts` rounds of the rejection loop
+/// and returns None instead of panicking. Deterministic, so a message that
+/// fails under a given bound always fails under it.
+pub fn sign_bounded(sk: &SecretKey, msg: &[u8], max_attempts: u32) -> Option<Signature> {
+    let mut scratch = SignScratch::new();
+    let config = LoopConfig { max_attempts, ..LoopConfig::default() };
+    let sig = sign_inner(sk, msg, None, &mut scratch, config, &mut |_| {});
+    scratch.clear();
+    sig
+}
+
+/// Hedged signing: rho' = H(key || rnd || mu) as in FIPS 204, so two
+/// signatures of the same message differ. `sign` keeps the legacy Round 3
+/// rho' = H(key || mu).
+pub fn sign_randomized(sk: &SecretKey, msg: &[u8], rnd: &[u8; 32]) -> Signature {
+    let mut scratch = SignScratch::new();
+    let sig = sign_inner(sk, msg, Some(rnd), &mut scratch, LoopConfig::default(), &mut |_| {}).expect(STUCK);
+    scratch.clear();
+    sig
+}
+
+/// `sign_randomized` with rnd drawn from `rng` (32 bytes).
+pub fn sign_rng<R: RandomBytes>(sk: &SecretKey, msg: &[u8], mut rng: R) -> Signature {
+    let mut rnd = [0u8; 32];
+    rng.randombytes(&mut rnd);
+    let sig = sign_randomized(sk, msg, &rnd);
+    rnd.zeroize();
+    sig
+}
+
+/// FIPS 204 pure signing with a context string, deterministic variant
+/// (rnd = 0^32, so rho' = H(key || 0^32 || mu)): signs
+/// M' = 0 || len(ctx) || ctx || msg, so a signature only verifies under
+/// `verify_ctx` with the same ctx. `sign`/`verify` hash msg with no prefix
+/// at all, which is not the same as an empty ctx here. None if ctx is
+/// longer than 255 bytes.
+pub fn sign_ctx(sk: &SecretKey, msg: &[u8], ctx: &[u8]) -> Option<Signature> {
+    let m_prime = ctx_message(msg, ctx)?;
+    Some(sign_randomized(sk, &m_prime, &[0u8; 32]))
+}
+
+/// Counterpart of `sign_ctx`. False for a ctx longer than 255 bytes.
+pub fn verify_ctx(pk: &PublicKey, msg: &[u8], sig: &Signature, ctx: &[u8]) -> bool {
+    match ctx_message(msg, ctx) {
+        Some(m_prime) => verify(pk, &m_prime, sig),
+        None => false,
+    }
+}
+
+/// M' = 0 || len(ctx) || ctx || msg; the leading 0 separates pure signing
+/// from the pre-hash variant.
+fn ctx_message(msg: &[u8], ctx: &[u8]) -> Option<Vec<u8>> {
+    let len = u8::try_from(ctx.len()).ok()?;
+    let mut m_prime = Vec::with_capacity(2 + ctx.len() + msg.len());
+    m_prime.extend_from_slice(&[0, len]);
+    m_prime.extend_from_slice(ctx);
+    m_prime.extend_from_slice(msg);
+    Some(m_prime)
+}
+
+pub(crate) fn sign_with_scratch(sk: &SecretKey, msg: &[u8], scratch: &mut SignScratch) -> Signature {
+    let sig = sign_inner(sk, msg, None, scratch, LoopConfig::default(), &mut |_| {}).expect(STUCK);
+    scratch.clear();
+    sig
+}
+
+fn poly_inf_norm(p: &Poly) -> i32 {
+    p.coeffs.iter().map(|&c| norm_bound(c)).max().unwrap_or(0)
+}
+
+/// Where the rejection loop gets A from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MatrixSource {
+    /// expand_a once up front
+    Expanded,
+    /// resample entries per product (sign_low_mem)
+    Streamed,
+}
+
+/// Attempt bound used by `sign` and the other panicking entry points.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 1000;
+const STUCK: &str = "Sign stuck in loop";
+
+/// How the rejection loop runs.
+#[derive(Clone, Copy)]
+struct LoopConfig {
+    matrix: MatrixSource,
+    /// first mask nonce
+    kappa_start: u16,
+    /// give up (None) after this many attempts
+    max_attempts: u32,
+}
+
+impl Default for LoopConfig {
+    fn default() -> Self {
+        Self { matrix: MatrixSource::Expanded, kappa_start: 0, max_attempts: DEFAULT_MAX_ATTEMPTS }
+    }
+}
+
+fn sign_inner(
+    sk: &SecretKey,
+    msg: &[u8],
+    rnd: Option<&[u8; 32]>,
+    scratch: &mut SignScratch,
+    config: LoopConfig,
+    observer: &mut impl FnMut(AttemptInfo),
+) -> Option<Signature> {
     let mut trm = Vec::with_capacity(64 + msg.len());
     trm.extend_from_slice(&sk.tr);
     trm.extend_from_slice(msg);
@@ -123,107 +537,124 @@ pub fn sign(sk: &SecretKey, msg: &[u8]) -> Signature {
     let mut mu = vec![0u8; 64];
     shake256(&trm, &mut mu);
 
-    let mut km = Vec::with_capacity(32 + 64);
+    let mut km = Vec::with_capacity(32 + 32 + 64);
     km.extend_from_slice(&sk.key);
+    if let Some(rnd) = rnd {
+        km.extend_from_slice(rnd);
+    }
     km.extend_from_slice(&mu);
     
-    let mut rho_prime = vec![0u8; 64];
-    shake256(&km, &mut rho_prime);
+    shake256(&km, &mut scratch.rho_prime);
+    km.zeroize();
 
-    let a_hat = expand_a(&sk.rho);
+    let a_hat = match config.matrix {
+        MatrixSource::Expanded => Some(expand_a(&sk.rho)),
+        MatrixSource::Streamed => None,
+    };
 
-    let mut s1_hat = sk.s1;
-    s1_hat.ntt();
-    let mut s2_hat = sk.s2;
-    s2_hat.ntt();
-    let mut t0_hat = sk.t0;
-    t0_hat.ntt();
+    scratch.s1_hat = sk.s1;
+    scratch.s1_hat.ntt();
+    scratch.s2_hat = sk.s2;
+    scratch.s2_hat.ntt();
+    scratch.t0_hat = sk.t0;
+    scratch.t0_hat.ntt();
+
+    // mu || w1_bytes, the w1 part is rewritten in place each attempt
+    let mut h_in = vec![0u8; 64 + K * POLYW1_PACKEDBYTES];
+    h_in[..64].copy_from_slice(&mu);
 
-    let mut kappa: u16 = 0;
-    let mut attempts = 0;
+    let mut kappa: u16 = config.kappa_start;
+    let mut attempts: u32 = 0;
 
     loop {
         attempts += 1;
-        if attempts > 1000 {
-             panic!("Sign stuck in loop");
+        if attempts > config.max_attempts {
+            return None;
         }
 
         // 1. Expand y (Standard Domain)
-        let y = expand_mask(&rho_prime, kappa);
+        scratch.y = expand_mask(&scratch.rho_prime, kappa);
         
         // 2. Compute w = A * y
         // Funcția mat_vec_mul_ntt din dilithium_sample.rs face intern:
         // y -> NTT -> Multiply -> INTT.
         // Deci rezultatul `w` este deja în Standard Domain.
-        let w = mat_vec_mul_ntt(&a_hat, &y); // Fără 'mut'
+        scratch.w = match &a_hat {
+            Some(a_hat) => mat_vec_mul_ntt(a_hat, &scratch.y),
+            None => mat_vec_mul_ntt_streamed(&sk.rho, &scratch.y),
+        };
         
         // w1 = HighBits(w)
         let mut w1 = PolyVec::<K>::default();
         for i in 0..K {
-            let mut wi = w.v[i];
+            let mut wi = scratch.w.v[i];
             normalize_poly(&mut wi);
             for j in 0..N {
                 w1.v[i].coeffs[j] = high_bits(wi.coeffs[j], GAMMA2);
             }
+            wi.zeroize();
         }
 
         // c_tilde = H(mu || w1_bytes)
-        let mut h_in = Vec::with_capacity(64 + K * POLYW1_PACKEDBYTES);
-        h_in.extend_from_slice(&mu);
-        h_in.extend_from_slice(&polyvec_w1_bytes::<Dilithium2, K>(&w1));
-        
-        let mut ctil = vec![0u8; 32];
-        shake256(&h_in, &mut ctil);
+        polyvec_w1_bytes_into::<Dilithium2, K>(&w1, &mut h_in[64..]);
         
-        let mut c_tilde = [0u8; 32];
-        c_tilde.copy_from_slice(&ctil);
+        let mut c_tilde = [0u8; CTILDE_BYTES];
+        shake256(&h_in, &mut c_tilde);
 
-        let c = challenge(&c_tilde);
-        let mut c_hat = c;
-        c_hat.ntt();
+        let c_hat = challenge_ntt(&c_tilde);
 
         // z = y + c*s1
         let mut z = PolyVec::<L>::default();
         for i in 0..L {
-            let mut prod = Poly::pointwise_mul(&c_hat, &s1_hat.v[i]);
-            prod.intt();
+            scratch.prod = Poly::pointwise_mul(&c_hat, &scratch.s1_hat.v[i]);
+            scratch.prod.intt();
             
-            z.v[i] = y.v[i];
-            z.v[i].add_assign(&prod);
+            z.v[i] = scratch.y.v[i];
+            z.v[i].add_assign(&scratch.prod);
             normalize_poly(&mut z.v[i]);
         }
 
+        let mut info = AttemptInfo {
+            attempt: attempts,
+            kappa,
+            z_norm: z.v.iter().map(poly_inf_norm).max().unwrap_or(0),
+            r0_norm: None,
+            ct0_norm: None,
+            hint_count: None,
+            rejected: None,
+        };
+
         // Check norm of z
         let z_bound = GAMMA1 - BETA;
-        if (0..L).any(|i| !poly_check_norm(&z.v[i], z_bound)) {
+        if !polyvec_check_norm(&z, z_bound) {
+            // a rejected z would leak s1, don't leave it on the stack
+            z.zeroize();
+            info.rejected = Some(Rejection::ZNorm);
+            observer(info);
             kappa = kappa.wrapping_add(L as u16);
             continue;
         }
 
         // Check norm of r0 = LowBits(w - cs2)
-        let mut w_minus_cs2 = PolyVec::<K>::default();
-        let mut ok = true;
-        
+        let mut r0_norm = 0;
         for i in 0..K {
-            let mut prod = Poly::pointwise_mul(&c_hat, &s2_hat.v[i]);
-            prod.intt();
+            scratch.prod = Poly::pointwise_mul(&c_hat, &scratch.s2_hat.v[i]);
+            scratch.prod.intt();
             
-            let mut t = w.v[i]; // w este Standard
-            t.sub_assign(&prod); // prod este Standard (după INTT mai sus)
-            normalize_poly(&mut t);
-            
-            w_minus_cs2.v[i] = t; 
+            let t = &mut scratch.w_minus_cs2.v[i];
+            *t = scratch.w.v[i]; // w este Standard
+            t.sub_assign(&scratch.prod); // prod este Standard (după INTT mai sus)
+            normalize_poly(t);
 
             for j in 0..N {
-                let r0 = low_bits(t.coeffs[j], GAMMA2);
-                if norm_bound(r0) >= GAMMA2 - BETA {
-                    ok = false;
-                    break;
-                }
+                r0_norm = r0_norm.max(norm_bound(low_bits(t.coeffs[j], GAMMA2)));
             }
-            if !ok { break; }
         }
-        if !ok {
+        info.r0_norm = Some(r0_norm);
+        if r0_norm >= GAMMA2 - BETA {
+            z.zeroize();
+            info.rejected = Some(Rejection::LowBits);
+            observer(info);
             kappa = kappa.wrapping_add(L as u16);
             continue;
         }
@@ -231,16 +662,16 @@ pub fn sign(sk: &SecretKey, msg: &[u8]) -> Signature {
         // Hints
         let mut h = PolyVec::<K>::default();
         let mut omega_cnt = 0usize;
+        let mut ct0_norm = 0;
         for i in 0..K {
-            let mut prod = Poly::pointwise_mul(&c_hat, &t0_hat.v[i]);
-            prod.intt();
+            scratch.prod = Poly::pointwise_mul(&c_hat, &scratch.t0_hat.v[i]);
+            scratch.prod.intt();
             
             for j in 0..N {
-                let mut ct0 = prod.coeffs[j] % Q;
-                if ct0 < 0 { ct0 += Q; }
-                if ct0 > Q/2 { ct0 -= Q; }
+                let ct0 = center_mod_q(scratch.prod.coeffs[j]);
+                ct0_norm = ct0_norm.max(ct0.abs());
                 
-                let val_verifier = w_minus_cs2.v[i].coeffs[j] + ct0;
+                let val_verifier = scratch.w_minus_cs2.v[i].coeffs[j] + ct0;
                 
                 let hint = make_hint(-ct0, val_verifier, GAMMA2);
                 
@@ -248,71 +679,91 @@ pub fn sign(sk: &SecretKey, msg: &[u8]) -> Signature {
                 if hint != 0 { omega_cnt += 1; }
             }
         }
+        info.ct0_norm = Some(ct0_norm);
+        info.hint_count = Some(omega_cnt);
+        if ct0_norm >= GAMMA2 {
+            z.zeroize();
+            info.rejected = Some(Rejection::Ct0);
+            observer(info);
+            kappa = kappa.wrapping_add(L as u16);
+            continue;
+        }
         if omega_cnt > OMEGA {
+            z.zeroize();
+            info.rejected = Some(Rejection::Hints);
+            observer(info);
             kappa = kappa.wrapping_add(L as u16);
             continue;
         }
 
-        return Signature { c_tilde, z, h };
+        observer(info);
+        return Some(Signature { c_tilde, z, h });
     }
 }
 
-pub fn verify(pk: &PublicKey, msg: &[u8], sig: &Signature) -> bool {
-    let z_bound = GAMMA1 - BETA;
-    if (0..L).any(|i| !poly_check_norm(&sig.z.v[i], z_bound)) {
-        return false;
-    }
-
-    let mut pk_bytes = Vec::with_capacity(PK_BYTES);
-    pk_bytes.extend_from_slice(&pk.rho);
-    for i in 0..K {
-        pk_bytes.extend_from_slice(&pack_poly_t1(&pk.t1.v[i]));
-    }
-    
-    let mut tr = vec![0u8; 64];
-    shake256(&pk_bytes, &mut tr);
-
-    let mut inbuf = Vec::with_capacity(64 + msg.len());
-    inbuf.extend_from_slice(&tr);
-    inbuf.extend_from_slice(msg);
-    
-    let mut mu = vec![0u8; 64];
-    shake256(&inbuf, &mut mu);
-
-    let a_hat = expand_a(&pk.rho);
-
-    let c = challenge(&sig.c_tilde);
-    let mut c_hat = c;
-    c_hat.ntt();
-
-    let mut z_hat = sig.z;
+/// w' = A*z - c*t1*2^d, centered. `a_hat` and `c_hat` are in NTT domain,
+/// `z` and `t1` in standard domain.
+pub(crate) fn compute_w_prime(
+    a_hat: &PolyMat<K, L>,
+    c_hat: &Poly,
+    z: &PolyVec<L>,
+    t1: &PolyVec<K>,
+) -> PolyVec<K> {
+    let mut z_hat = *z;
     z_hat.ntt();
 
-    // w' = A*z - c*t1*2^d
     let mut w_prime = PolyVec::<K>::default();
     for i in 0..K {
-        let mut acc = Poly::default();
-        for j in 0..L {
-            let tp = Poly::pointwise_mul(&a_hat.m[i][j], &z_hat.v[j]);
-            acc.add_assign(&tp);
-        }
+        let mut acc = a_hat.row_times_vec(i, &z_hat);
         acc.intt();
         normalize_poly(&mut acc);
         w_prime.v[i] = acc;
     }
 
-    let mut t1_shift = pk.t1;
+    let mut t1_shift = *t1;
     for i in 0..K {
         t1_shift.v[i].shiftl(D);
         t1_shift.v[i].ntt();
     }
     for i in 0..K {
-        let mut prod = Poly::pointwise_mul(&c_hat, &t1_shift.v[i]);
+        let mut prod = Poly::pointwise_mul(c_hat, &t1_shift.v[i]);
         prod.intt();
         
         w_prime.v[i].sub_assign(&prod);
         normalize_poly(&mut w_prime.v[i]);
     }
+    w_prime
+}
+
+pub fn verify(pk: &PublicKey, msg: &[u8], sig: &Signature) -> bool {
+    // t1 is bitlen(q-1) - d = 10 bits. A larger value would be truncated by
+    // pk.to_bytes() and reduced mod q by shiftl, verifying against a
+    // different key than the one given.
+    let t1_ok = pk.t1.v.iter().all(|p| p.coeffs.iter().all(|&c| (0..1 << 10).contains(&c)));
+    if !t1_ok {
+        return false;
+    }
+
+    let z_bound = GAMMA1 - BETA;
+    if !polyvec_check_norm(&sig.z, z_bound) {
+        return false;
+    }
+
+    let tr = pk.tr();
+
+    let mut inbuf = Vec::with_capacity(64 + msg.len());
+    inbuf.extend_from_slice(&tr);
+    inbuf.extend_from_slice(msg);
+    
+    let mut mu = vec![0u8; 64];
+    shake256(&inbuf, &mut mu);
+
+    let a_hat = expand_a(&pk.rho);
+
+    let c_hat = challenge_ntt(&sig.c_tilde);
+
+    // w' = A*z - c*t1*2^d
+    let w_prime = compute_w_prime(&a_hat, &c_hat, &sig.z, &pk.t1);
 
     // w1' = UseHint(h, w')
     let mut w1_prime = PolyVec::<K>::default();
@@ -331,8 +782,16 @@ pub fn verify(pk: &PublicKey, msg: &[u8], sig: &Signature) -> bool {
     h_in.extend_from_slice(&mu);
     h_in.extend_from_slice(&polyvec_w1_bytes::<Dilithium2, K>(&w1_prime));
     
-    let mut ctil2 = vec![0u8; 32];
-    shake256(&h_in, &mut ctil2);
+    c_tilde_matches::<Dilithium2>(&h_in, &sig.c_tilde)
+}
 
-    ctil2.as_slice() == sig.c_tilde
+/// c_tilde == H(mu || w1'_bytes) over exactly `P::CTILDE_BYTES` (lambda/4) bytes.
+/// c_tilde is public, so a short-circuit compare is fine: squeeze byte by
+/// byte and stop at the first mismatch.
+pub(crate) fn c_tilde_matches<P: DilithiumParams>(h_in: &[u8], c_tilde: &[u8]) -> bool {
+    if c_tilde.len() != P::CTILDE_BYTES {
+        return false;
+    }
+    let mut xof = ShakeStream::shake256(h_in);
+    c_tilde.iter().all(|&b| xof.next_u8() == b)
 }
\ No newline at end of file
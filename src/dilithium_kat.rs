@@ -0,0 +1,203 @@
+// Dilithium signature KAT files in the NIST PQCsignKAT .rsp layout:
+//   count / seed / mlen / msg / pk / sk / smlen / sm
+// where sm = signature || msg (the "signed message" of the NIST API).
+
+use core::fmt;
+
+use crate::dilithium_params::SIG_BYTES;
+use crate::util::hex::{self, HexError};
+
+/// One `count = ...` block of a signature .rsp file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignKat {
+    pub count: u32,
+    pub seed: Vec<u8>,
+    pub msg: Vec<u8>,
+    pub pk: Vec<u8>,
+    pub sk: Vec<u8>,
+    pub sm: Vec<u8>,
+}
+
+impl SignKat {
+    /// The signature part of `sm` (everything before the appended message).
+    pub fn signature(&self) -> &[u8] {
+        &self.sm[..self.sm.len() - self.msg.len()]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KatError {
+    MissingField { count: u32, field: &'static str },
+    BadHex { count: u32, field: &'static str, err: HexError },
+    BadNumber { line: usize },
+    /// `mlen`/`smlen` disagree with the decoded `msg`/`sm`.
+    LengthMismatch { count: u32, field: &'static str, declared: usize, actual: usize },
+    /// `sm` does not end with `msg`.
+    MessageMismatch { count: u32 },
+}
+
+impl fmt::Display for KatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KatError::MissingField { count, field } => write!(f, "count {}: missing {}", count, field),
+            KatError::BadHex { count, field, err } => write!(f, "count {}: {}: {}", count, field, err),
+            KatError::BadNumber { line } => write!(f, "line {}: invalid number", line),
+            KatError::LengthMismatch { count, field, declared, actual } => {
+                write!(f, "count {}: {} = {} but {} bytes present", count, field, declared, actual)
+            }
+            KatError::MessageMismatch { count } => write!(f, "count {}: sm does not end with msg", count),
+        }
+    }
+}
+
+impl std::error::Error for KatError {}
+
+/// Splits a signed message into (signature, message) given `mlen`.
+/// None if `sm` is not exactly `SIG_BYTES + mlen` long.
+pub fn split_sm(sm: &[u8], mlen: usize) -> Option<(&[u8], &[u8])> {
+    if sm.len() != SIG_BYTES + mlen {
+        return None;
+    }
+    Some(sm.split_at(SIG_BYTES))
+}
+
+#[derive(Default)]
+struct Block {
+    count: Option<u32>,
+    mlen: Option<usize>,
+    smlen: Option<usize>,
+    fields: Vec<(&'static str, Vec<u8>)>,
+}
+
+impl Block {
+    fn take(&mut self, count: u32, name: &'static str) -> Result<Vec<u8>, KatError> {
+        let pos = self.fields.iter().position(|(n, _)| *n == name);
+        pos.map(|i| self.fields.swap_remove(i).1)
+            .ok_or(KatError::MissingField { count, field: name })
+    }
+
+    fn finish(mut self) -> Result<SignKat, KatError> {
+        let count = self.count.unwrap_or(0);
+        let mlen = self.mlen.ok_or(KatError::MissingField { count, field: "mlen" })?;
+        let smlen = self.smlen.ok_or(KatError::MissingField { count, field: "smlen" })?;
+        let kat = SignKat {
+            count,
+            seed: self.take(count, "seed")?,
+            msg: self.take(count, "msg")?,
+            pk: self.take(count, "pk")?,
+            sk: self.take(count, "sk")?,
+            sm: self.take(count, "sm")?,
+        };
+
+        if kat.msg.len() != mlen {
+            return Err(KatError::LengthMismatch { count, field: "mlen", declared: mlen, actual: kat.msg.len() });
+        }
+        if kat.sm.len() != smlen {
+            return Err(KatError::LengthMismatch { count, field: "smlen", declared: smlen, actual: kat.sm.len() });
+        }
+        match split_sm(&kat.sm, mlen) {
+            None => Err(KatError::LengthMismatch { count, field: "smlen", declared: smlen, actual: SIG_BYTES + mlen }),
+            Some((_, m)) if m != kat.msg.as_slice() => Err(KatError::MessageMismatch { count }),
+            Some(_) => Ok(kat),
+        }
+    }
+}
+
+/// Parses every `count = ...` block of a signature .rsp file.
+pub fn parse_sign_rsp(text: &str) -> Result<Vec<SignKat>, KatError> {
+    let mut out = Vec::new();
+    let mut block: Option<Block> = None;
+
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let Some((key, value)) = line.split_once('=') else {
+            continue; // blank lines, "# Dilithium2" header
+        };
+        // "msg =" with an empty value is trimmed to no trailing space
+        let (key, value) = (key.trim(), value.trim());
+        let number = || value.parse::<usize>().map_err(|_| KatError::BadNumber { line: lineno + 1 });
+
+        if key == "count" {
+            if let Some(b) = block.take() {
+                out.push(b.finish()?);
+            }
+            block = Some(Block { count: Some(number()? as u32), ..Default::default() });
+            continue;
+        }
+        let Some(b) = block.as_mut() else { continue };
+        let count = b.count.unwrap_or(0);
+        match key {
+            "mlen" => b.mlen = Some(number()?),
+            "smlen" => b.smlen = Some(number()?),
+            "seed" | "msg" | "pk" | "sk" | "sm" => {
+                let field: &'static str = match key {
+                    "seed" => "seed",
+                    "msg" => "msg",
+                    "pk" => "pk",
+                    "sk" => "sk",
+                    _ => "sm",
+                };
+                let bytes = hex::decode(value).map_err(|err| KatError::BadHex { count, field, err })?;
+                b.fields.push((field, bytes));
+            }
+            _ => {}
+        }
+    }
+    if let Some(b) = block {
+        out.push(b.finish()?);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two synthetic blocks in the PQCsignKAT layout; signatures are filler bytes.
+    fn snippet(sig_fill: u8, tail: &str) -> String {
+        let sig = hex::encode(&vec![sig_fill; SIG_BYTES]);
+        format!(
+            "# Dilithium2\n\n\
+             count = 0\nseed = 00FF\nmlen = 3\nmsg = D81C4D\npk = 0102\nsk = 0304\n\
+             smlen = {}\nsm = {}D81C4D\n\n\
+             count = 1\nseed = 01\nmlen = 0\nmsg = \npk = 05\nsk = 06\n\
+             smlen = {}\nsm = {}{}\n",
+            SIG_BYTES + 3, sig, SIG_BYTES, sig, tail
+        )
+    }
+
+    #[test]
+    fn splits_signature_from_message() {
+        let kats = parse_sign_rsp(&snippet(0xAB, "")).unwrap();
+        assert_eq!(kats.len(), 2);
+
+        assert_eq!(kats[0].count, 0);
+        assert_eq!(kats[0].seed, [0x00, 0xFF]);
+        assert_eq!(kats[0].msg, [0xD8, 0x1C, 0x4D]);
+        assert_eq!(kats[0].signature(), &[0xABu8; SIG_BYTES][..]);
+        assert_eq!(split_sm(&kats[0].sm, 3).unwrap().1, &kats[0].msg[..]);
+
+        // empty message: sm is the bare signature
+        assert_eq!(kats[1].count, 1);
+        assert!(kats[1].msg.is_empty());
+        assert_eq!(kats[1].signature().len(), SIG_BYTES);
+        assert_eq!(kats[1].pk, [0x05]);
+    }
+
+    #[test]
+    fn rejects_inconsistent_sm() {
+        // one extra byte after the signature of the empty-message block
+        assert_eq!(
+            parse_sign_rsp(&snippet(0x11, "00")),
+            Err(KatError::LengthMismatch { count: 1, field: "smlen", declared: SIG_BYTES, actual: SIG_BYTES + 1 })
+        );
+
+        let text = snippet(0x11, "").replacen("D81C4D\n\ncount", "D81C4E\n\ncount", 1);
+        assert_eq!(parse_sign_rsp(&text), Err(KatError::MessageMismatch { count: 0 }));
+
+        let text = snippet(0x11, "").replacen("pk = 05\n", "", 1);
+        assert_eq!(parse_sign_rsp(&text), Err(KatError::MissingField { count: 1, field: "pk" }));
+
+        assert_eq!(SIG_BYTES, 2420);
+    }
+}
@@ -3,11 +3,13 @@
 
 use ml_dsa::{
     signature::{Signer, Verifier},
-    KeyGen, MlDsa44, Signature, SigningKey, VerifyingKey,
+    EncodedSigningKey, EncodedVerifyingKey, KeyGen, MlDsa44, Signature, SigningKey, VerifyingKey,
 };
 
 use rand_core::{CryptoRng, RngCore};
 
+use crate::dilithium;
+
 struct OsRngCompat;
 
 impl RngCore for OsRngCompat {
@@ -40,3 +42,73 @@ pub fn sign_44(sk: &SigningKey<MlDsa44>, msg: &[u8]) -> Signature<MlDsa44> {
 pub fn verify_44(vk: &VerifyingKey<MlDsa44>, msg: &[u8], sig: &Signature<MlDsa44>) -> bool {
     vk.verify(msg, sig).is_ok()
 }
+
+// -----------------------------------------------------------------------------
+// Loading ml-dsa keys into dilithium::{PublicKey, SecretKey}
+// -----------------------------------------------------------------------------
+//
+// Both crates use the FIPS 204 byte layout (pk = rho || t1, sk = rho || K || tr
+// || s1 || s2 || t0), so the bridge is a decode of the encoded key.
+
+/// The encoded key was rejected by `from_bytes` (bad length or s1/s2 range).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidKeyEncoding;
+
+impl TryFrom<&EncodedVerifyingKey<MlDsa44>> for dilithium::PublicKey {
+    type Error = InvalidKeyEncoding;
+
+    fn try_from(enc: &EncodedVerifyingKey<MlDsa44>) -> Result<Self, Self::Error> {
+        dilithium::PublicKey::from_bytes(enc.as_slice()).ok_or(InvalidKeyEncoding)
+    }
+}
+
+impl TryFrom<&EncodedSigningKey<MlDsa44>> for dilithium::SecretKey {
+    type Error = InvalidKeyEncoding;
+
+    fn try_from(enc: &EncodedSigningKey<MlDsa44>) -> Result<Self, Self::Error> {
+        dilithium::SecretKey::from_bytes(enc.as_slice()).ok_or(InvalidKeyEncoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dilithium_pack::{unpack_hints, unpack_poly_z};
+    use crate::dilithium_params::{Dilithium2, CTILDE_BYTES, K, L, POLYZ_PACKEDBYTES, SIG_BYTES};
+    use crate::dilithium_poly::PolyVec;
+
+    // c_tilde || z || h, the FIPS 204 signature layout.
+    fn decode_signature(bytes: &[u8]) -> Option<dilithium::Signature> {
+        if bytes.len() != SIG_BYTES {
+            return None;
+        }
+        let (c, rest) = bytes.split_at(CTILDE_BYTES);
+        let (z_bytes, h_bytes) = rest.split_at(L * POLYZ_PACKEDBYTES);
+        let mut z = PolyVec::<L>::default();
+        for (p, chunk) in z.v.iter_mut().zip(z_bytes.chunks(POLYZ_PACKEDBYTES)) {
+            *p = unpack_poly_z::<Dilithium2>(chunk);
+        }
+        let h = unpack_hints::<Dilithium2, K>(h_bytes)?;
+        Some(dilithium::Signature { c_tilde: c.try_into().unwrap(), z, h })
+    }
+
+    #[test]
+    fn mldsa_keys_load_into_dilithium_types() {
+        let (vk, sk) = keygen_44();
+
+        let vk_enc = vk.encode();
+        let pk = dilithium::PublicKey::try_from(&vk_enc).expect("verifying key loads");
+        assert_eq!(pk.to_bytes()[..], vk_enc.as_slice()[..]);
+
+        let sk_enc = sk.encode();
+        let dsk = dilithium::SecretKey::try_from(&sk_enc).expect("signing key loads");
+        assert_eq!(dsk.to_bytes()[..], sk_enc.as_slice()[..]);
+        assert_eq!(dsk.rho, pk.rho);
+
+        // ml-dsa signs pure ML-DSA with an empty context
+        let msg = b"signed by ml-dsa";
+        let sig = decode_signature(sign_44(&sk, msg).encode().as_slice()).expect("signature decodes");
+        assert!(dilithium::verify_ctx(&pk, msg, &sig, b""));
+        assert!(!dilithium::verify_ctx(&pk, b"signed by ml-dsb", &sig, b""));
+    }
+}
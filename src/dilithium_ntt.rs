@@ -9,6 +9,9 @@ use rhdl::prelude::*;
 use core::ops::ControlFlow;
 
 use crate::dilithium_params::{N, Q, QINV, F, ZETAS};
+use crate::dilithium_reduce::mont_fqmul;
+use crate::montgomery::Montgomery;
+use crate::ntt_control::{ControlOut, NttControl};
 
 pub type Coeff = SignedBits<U32>;
 pub type Wide  = SignedBits<U64>;
@@ -46,7 +49,7 @@ pub struct NttOut {
     pub done: bool,
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Phase {
     Idle,
     Read,
@@ -79,20 +82,28 @@ fn zeta_for_block(inverse: bool, k: Bits<U9>) -> Coeff {
     if inverse { s32(-z) } else { s32(z) }
 }
 
+/// Dilithium's `Montgomery` parameters: 32-bit coefficients, R = 2^32.
+pub struct DilithiumMont;
+
+impl Montgomery for DilithiumMont {
+    type N = U32;
+    type W = U64;
+    const Q: i128 = Q as i128;
+    const QINV: i128 = QINV as i128;
+    const WIDTH: i32 = 32;
+}
+
 #[inline(always)]
 pub fn montgomery_reduce(a: Wide) -> Coeff {
     // Dilithium ref:
     // t = (int32_t)a * QINV;
     // t = (a - (int64_t)t*Q) >> 32;
-    let t: Coeff = (a.resize::<U32>() * s32(QINV as i64)).resize::<U32>();
-    let r: Wide = (a - t.resize::<U64>() * s64(Q as i128)) >> 32;
-    r.resize::<U32>()
+    DilithiumMont::montgomery_reduce(a)
 }
 
 #[inline(always)]
 pub fn fqmul(a: Coeff, b: Coeff) -> Coeff {
-    let prod: Wide = (a.resize::<U64>() * b.resize::<U64>()).resize::<U64>();
-    montgomery_reduce(prod)
+    DilithiumMont::fqmul(a, b)
 }
 
 /// One-cycle Dilithium NTT/INTT step.
@@ -264,9 +275,41 @@ pub fn ntt_step(st: NttState, inp: NttIn) -> (NttState, NttOut) {
     (ns, out)
 }
 
+// -----------------------------------------------------------------------------
+// Fused final scaling (area/latency option for invntt)
+// -----------------------------------------------------------------------------
+
+pub use crate::ntt_control::FinalScaling;
+
+/// `ntt_step` with the invntt scaling fused into the last butterfly stage:
+/// a' = (a+b)*F and b' = ((a-b)*zeta)*F, the same Montgomery multiplies the
+/// FinalWrite pass would apply, so the output is bit-identical to the
+/// per-coefficient variant. Forward transforms are unchanged.
+pub fn ntt_step_fused(st: NttState, inp: NttIn) -> (NttState, NttOut) {
+    let (mut ns, mut out) = ntt_step(st, inp);
+
+    if st.phase == Phase::Write && st.inverse && st.len == b9(128) {
+        let f = s32(F as i64);
+        let a = inp.rdata_a;
+        let b = inp.rdata_b;
+
+        let z = zeta_for_block(true, st.k);
+
+        out.porta.wdata = fqmul(f, a + b);
+        out.portb.wdata = fqmul(f, fqmul(z, a - b));
+
+        if ns.phase == Phase::FinalRead {
+            ns.phase = Phase::Done;
+        }
+    }
+
+    (ns, out)
+}
+
 // -----------------------------------------------------------------------------
 // Optional software wrappers: emulate dual-port 1-cycle BRAM latency.
 // -----------------------------------------------------------------------------
+
 // Dacă nu vrei deloc “software wrapper”, poți șterge partea de mai jos fără
 // să afectezi core-ul FSM.
 
@@ -282,7 +325,16 @@ fn mem_write(mem: &mut [Coeff; N], req: MemReq) {
     }
 }
 
-fn run_fsm(mem: &mut [Coeff; N], inverse: bool) {
+fn run_fsm(mem: &mut [Coeff; N], inverse: bool) -> u64 {
+    run_fsm_with(mem, inverse, FinalScaling::PerCoeff)
+}
+
+/// Runs the FSM to completion; returns the cycle count including the done cycle.
+fn run_fsm_with(mem: &mut [Coeff; N], inverse: bool, scaling: FinalScaling) -> u64 {
+    let step = match scaling {
+        FinalScaling::PerCoeff => ntt_step,
+        FinalScaling::Fused => ntt_step_fused,
+    };
     let mut st = NttState::default();
     let mut start = true;
 
@@ -293,7 +345,9 @@ fn run_fsm(mem: &mut [Coeff; N], inverse: bool) {
     let mut rdata_a = s32(0);
     let mut rdata_b = s32(0);
 
+    let mut cycles = 0u64;
     let _ = (0usize..20_000usize).try_for_each(|_| {
+        cycles += 1;
         if pending_valid {
             rdata_a = mem_read(mem, pending_a);
             rdata_b = mem_read(mem, pending_b);
@@ -303,7 +357,7 @@ fn run_fsm(mem: &mut [Coeff; N], inverse: bool) {
         }
 
         let inp = NttIn { start, inverse, rdata_a, rdata_b };
-        let (ns, out) = ntt_step(st, inp);
+        let (ns, out) = step(st, inp);
         st = ns;
 
         mem_write(mem, out.porta);
@@ -320,6 +374,31 @@ fn run_fsm(mem: &mut [Coeff; N], inverse: bool) {
 
         if out.done { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
     });
+    cycles
+}
+
+// -------------------------------------------------------
+// Control transition relation (for model checking / state diagrams)
+// -------------------------------------------------------
+
+// `ntt_control::enumerate_transitions::<NttState>()` lists the relation.
+impl NttControl for NttState {
+    type Phase = Phase;
+    const MIN_LEN: usize = 1;
+    type Key = (u8, bool, u128, u128, u128, u128, u128);
+
+    fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    fn key(&self) -> Self::Key {
+        (self.phase as u8, self.inverse, self.len.raw(), self.start.raw(), self.j.raw(), self.k.raw(), self.idx.raw())
+    }
+
+    fn control_step(self, start: bool, inverse: bool) -> (Self, ControlOut) {
+        let (ns, o) = ntt_step(self, NttIn { start, inverse, ..Default::default() });
+        (ns, ControlOut { busy: o.busy, done: o.done, we_a: o.porta.we, we_b: o.portb.we })
+    }
 }
 
 /// Forward NTT (in-place) on i32 coefficients
@@ -330,10 +409,118 @@ pub fn ntt(a: &mut [i32; N]) {
     a.iter_mut().enumerate().for_each(|(i, slot)| *slot = mem[i].raw() as i32);
 }
 
+/// Forward NTT as plain loops (no FSM stepping). Same butterflies, twiddle
+/// order and arithmetic as `ntt_step`, so the result equals `ntt` exactly;
+/// `Poly::ntt` uses this and the FSM is kept for cycle counts.
+pub fn ntt_direct(a: &mut [i32; N]) {
+    let mut mem: [Coeff; N] = core::array::from_fn(|i| s32(a[i] as i64));
+    let mut k = 1usize;
+    let mut len = 128usize;
+    while len >= 1 {
+        for start in (0..N).step_by(2 * len) {
+            let z = zeta_for_block(false, b9(k as u16));
+            k += 1;
+            for j in start..start + len {
+                let t = fqmul(z, mem[j + len]);
+                mem[j + len] = mem[j] - t;
+                mem[j] += t;
+            }
+        }
+        len >>= 1;
+    }
+    a.iter_mut().zip(mem.iter()).for_each(|(slot, c)| *slot = c.raw() as i32);
+}
+
+/// Loop-form `intt` (invntt_tomont, per-coefficient F pass); equals `intt` exactly.
+pub fn intt_direct(a: &mut [i32; N]) {
+    let mut mem: [Coeff; N] = core::array::from_fn(|i| s32(a[i] as i64));
+    let mut k = 255usize;
+    let mut len = 1usize;
+    while len <= 128 {
+        for start in (0..N).step_by(2 * len) {
+            let z = zeta_for_block(true, b9(k as u16));
+            k -= 1;
+            for j in start..start + len {
+                let t = mem[j];
+                let b = mem[j + len];
+                mem[j] = t + b;
+                mem[j + len] = fqmul(z, t - b);
+            }
+        }
+        len <<= 1;
+    }
+    let f = s64(F as i128);
+    a.iter_mut().zip(mem.iter()).for_each(|(slot, &c)| {
+        *slot = montgomery_reduce((f * c.resize::<U64>()).resize::<U64>()).raw() as i32
+    });
+}
+
 /// Inverse NTT (invntt_tomont) (in-place) on i32 coefficients
 pub fn intt(a: &mut [i32; N]) {
+    intt_with(a, FinalScaling::PerCoeff);
+}
+
+/// Inverse NTT with a choice of how the FSM applies the final factor F.
+pub fn intt_with(a: &mut [i32; N], scaling: FinalScaling) {
     let mut mem = [s32(0); N];
     a.iter().enumerate().for_each(|(i, &v)| mem[i] = s32(v as i64));
-    run_fsm(&mut mem, true);
+    run_fsm_with(&mut mem, true, scaling);
     a.iter_mut().enumerate().for_each(|(i, slot)| *slot = mem[i].raw() as i32);
 }
+
+// -------------------------------------------------------
+// NTT -> pointwise -> INTT multiply pipeline (latency model)
+// -------------------------------------------------------
+
+/// Cycle counts of the stages of `poly_mul_pipeline`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PipelineCycles {
+    pub ntt_a: u64,
+    pub ntt_b: u64,
+    pub pointwise: u64,
+    pub intt: u64,
+}
+
+impl PipelineCycles {
+    pub fn total(&self) -> u64 {
+        self.ntt_a + self.ntt_b + self.pointwise + self.intt
+    }
+}
+
+/// a*b in Z_q[X]/(X^N+1) as one accelerator pass: two BRAM banks, one NTT core.
+///
+/// NTT(bank A), NTT(bank B), then a pointwise Montgomery multiply streaming
+/// both banks (read i while writing i-1, N+1 cycles) into bank A, then
+/// INTT(bank A). Stages run back to back with no re-load between them, so
+/// the result equals `Poly::mul_ntt` and the cycle counts are the latency of
+/// the chained hardware.
+pub fn poly_mul_pipeline(a: &[i32; N], b: &[i32; N]) -> ([i32; N], PipelineCycles) {
+    let mut bank_a = [s32(0); N];
+    let mut bank_b = [s32(0); N];
+    a.iter().enumerate().for_each(|(i, &v)| bank_a[i] = s32(v as i64));
+    b.iter().enumerate().for_each(|(i, &v)| bank_b[i] = s32(v as i64));
+
+    let mut cycles = PipelineCycles {
+        ntt_a: run_fsm(&mut bank_a, false),
+        ntt_b: run_fsm(&mut bank_b, false),
+        ..Default::default()
+    };
+
+    // one read per cycle from each bank, write-back one cycle later
+    let mut pending: Option<(usize, Coeff, Coeff)> = None;
+    for addr in 0..=N {
+        cycles.pointwise += 1;
+        if let Some((i, x, y)) = pending.take() {
+            bank_a[i] = s32(mont_fqmul(x.raw() as i32, y.raw() as i32) as i64);
+        }
+        if addr < N {
+            pending = Some((addr, bank_a[addr], bank_b[addr]));
+        }
+    }
+
+    cycles.intt = run_fsm(&mut bank_a, true);
+
+    let mut out = [0i32; N];
+    out.iter_mut().enumerate().for_each(|(i, slot)| *slot = bank_a[i].raw() as i32);
+    (out, cycles)
+}
@@ -1,4 +1,4 @@
-use crate::dilithium_params::{DilithiumParams, N};
+use crate::dilithium_params::{DilithiumParams, N, POLYT0_PACKEDBYTES};
 use crate::dilithium_poly::{Poly, PolyVec};
 use crate::dilithium_rounding::norm_bound;
 
@@ -37,11 +37,11 @@ pub fn unpack_poly_t1(buf: &[u8; 320]) -> Poly {
 }
 
 pub fn pack_poly_t0(a: &Poly) -> [u8; 416] {
-    // 13 bits each, store a0 in [0,2^13) by offsetting
+    // 13 bits each, store 2^12 - a0 in [0,2^13) (FIPS 204 skEncode)
     let mut out = [0u8; 416];
     let mut bitpos = 0usize;
     for &c in &a.coeffs {
-        let v = (c + (1 << 12)) as u32;
+        let v = ((1 << 12) - c) as u32;
         for k in 0..13 {
             let b = ((v >> k) & 1) as u8;
             let byte = bitpos / 8;
@@ -65,7 +65,7 @@ pub fn unpack_poly_t0(buf: &[u8; 416]) -> Poly {
             v |= (b as u32) << k;
             bitpos += 1;
         }
-        a.coeffs[i] = (v as i32) - (1 << 12);
+        a.coeffs[i] = (1 << 12) - (v as i32);
     }
     a
 }
@@ -126,14 +126,61 @@ pub fn unpack_poly_eta<P: DilithiumParams>(buf: &[u8]) -> Poly {
     a
 }
 
+/// s1 or s2: `DIM` eta-packed polynomials back to back.
+pub fn pack_polyvec_eta<P: DilithiumParams, const DIM: usize>(v: &PolyVec<DIM>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(DIM * P::POLYETA_PACKEDBYTES);
+    for p in v.v.iter() {
+        out.extend_from_slice(&pack_poly_eta::<P>(p));
+    }
+    out
+}
+
+/// Inverse of `pack_polyvec_eta`. None if the length is wrong or a
+/// coefficient decodes below -ETA (the packing has room for eta - c up to
+/// 7 resp. 15, only 0..=2*ETA is valid).
+pub fn unpack_polyvec_eta<P: DilithiumParams, const DIM: usize>(buf: &[u8]) -> Option<PolyVec<DIM>> {
+    if buf.len() != DIM * P::POLYETA_PACKEDBYTES {
+        return None;
+    }
+    let mut v = PolyVec::<DIM>::default();
+    for (p, chunk) in v.v.iter_mut().zip(buf.chunks_exact(P::POLYETA_PACKEDBYTES)) {
+        *p = unpack_poly_eta::<P>(chunk);
+        if p.coeffs.iter().any(|&c| c < -P::ETA) {
+            return None;
+        }
+    }
+    Some(v)
+}
+
+/// t0: `K` 13-bit packed polynomials back to back.
+pub fn pack_polyvec_t0<const K: usize>(v: &PolyVec<K>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(K * POLYT0_PACKEDBYTES);
+    for p in v.v.iter() {
+        out.extend_from_slice(&pack_poly_t0(p));
+    }
+    out
+}
+
+/// Inverse of `pack_polyvec_t0`. None if the length is wrong; every 13-bit
+/// pattern is a valid t0 coefficient.
+pub fn unpack_polyvec_t0<const K: usize>(buf: &[u8]) -> Option<PolyVec<K>> {
+    if buf.len() != K * POLYT0_PACKEDBYTES {
+        return None;
+    }
+    let mut v = PolyVec::<K>::default();
+    for (p, chunk) in v.v.iter_mut().zip(buf.chunks_exact(POLYT0_PACKEDBYTES)) {
+        *p = unpack_poly_t0(chunk.try_into().unwrap());
+    }
+    Some(v)
+}
+
 pub fn pack_poly_z<P: DilithiumParams>(a: &Poly) -> Vec<u8> {
-    // gamma1=2^17 -> 18 bits, gamma1=2^19 -> 20 bits
-    let bits = if P::GAMMA1 == (1 << 17) { 18 } else { 20 };
+    let bits = P::GAMMA1_BITS;
     let mut out = vec![0u8; P::POLYZ_PACKEDBYTES];
     let mut bitpos = 0usize;
 
     for &c in &a.coeffs {
-        let t = ((P::GAMMA1 - 1) - c) as u32; // match sample mapping
+        let t = (P::GAMMA1 - c) as u32; // FIPS 204 BitPack(z, gamma1 - 1, gamma1)
         for k in 0..bits {
             let b = ((t >> k) & 1) as u8;
             out[bitpos / 8] |= b << (bitpos % 8);
@@ -144,7 +191,7 @@ pub fn pack_poly_z<P: DilithiumParams>(a: &Poly) -> Vec<u8> {
 }
 
 pub fn unpack_poly_z<P: DilithiumParams>(buf: &[u8]) -> Poly {
-    let bits = if P::GAMMA1 == (1 << 17) { 18 } else { 20 };
+    let bits = P::GAMMA1_BITS;
     let mut a = Poly::default();
     let mut bitpos = 0usize;
 
@@ -155,16 +202,24 @@ pub fn unpack_poly_z<P: DilithiumParams>(buf: &[u8]) -> Poly {
             t |= (b as u32) << k;
             bitpos += 1;
         }
-        a.coeffs[i] = (P::GAMMA1 - 1) - (t as i32);
+        a.coeffs[i] = P::GAMMA1 - (t as i32);
     }
     a
 }
 
 pub fn pack_poly_w1<P: DilithiumParams>(a1: &Poly) -> Vec<u8> {
+    let mut out = vec![0u8; P::POLYW1_PACKEDBYTES];
+    pack_poly_w1_into::<P>(a1, &mut out);
+    out
+}
+
+/// Same as `pack_poly_w1` but into `out[..POLYW1_PACKEDBYTES]`, which is overwritten.
+pub fn pack_poly_w1_into<P: DilithiumParams>(a1: &Poly, out: &mut [u8]) {
     // gamma2: modes 2/3 -> 6 bits (0..43) => 192 bytes
     // mode 5 -> 4 bits (0..15) => 128 bytes
     let bits = if P::POLYW1_PACKEDBYTES == 192 { 6 } else { 4 };
-    let mut out = vec![0u8; P::POLYW1_PACKEDBYTES];
+    let out = &mut out[..P::POLYW1_PACKEDBYTES];
+    out.fill(0);
     let mut bitpos = 0usize;
 
     for &c in &a1.coeffs {
@@ -175,7 +230,24 @@ pub fn pack_poly_w1<P: DilithiumParams>(a1: &Poly) -> Vec<u8> {
             bitpos += 1;
         }
     }
-    out
+}
+
+pub fn unpack_poly_w1<P: DilithiumParams>(buf: &[u8]) -> Poly {
+    // inverse of pack_poly_w1: 6 bits (192 bytes) or 4 bits (128 bytes)
+    let bits = if P::POLYW1_PACKEDBYTES == 192 { 6 } else { 4 };
+    let mut a = Poly::default();
+    let mut bitpos = 0usize;
+
+    for c in a.coeffs.iter_mut() {
+        let mut v = 0u32;
+        for k in 0..bits {
+            let b = (buf[bitpos / 8] >> (bitpos % 8)) & 1;
+            v |= (b as u32) << k;
+            bitpos += 1;
+        }
+        *c = v as i32;
+    }
+    a
 }
 
 // MODIFICARE: Am adăugat `const K: usize` în semnătură pentru a putea folosi PolyVec<K>
@@ -188,6 +260,15 @@ pub fn polyvec_w1_bytes<P: DilithiumParams, const K: usize>(w1: &PolyVec<K>) ->
     out
 }
 
+/// Allocation-free `polyvec_w1_bytes`: packs into `out`, which must be
+/// exactly `K * POLYW1_PACKEDBYTES` long (sign reuses one buffer per attempt).
+pub fn polyvec_w1_bytes_into<P: DilithiumParams, const K: usize>(w1: &PolyVec<K>, out: &mut [u8]) {
+    assert_eq!(out.len(), K * P::POLYW1_PACKEDBYTES, "w1 buffer length");
+    for (p, chunk) in w1.v.iter().zip(out.chunks_exact_mut(P::POLYW1_PACKEDBYTES)) {
+        pack_poly_w1_into::<P>(p, chunk);
+    }
+}
+
 // MODIFICARE: Generic `const K` pentru consistență, deși corpul e gol
 pub fn hint_weight<const K: usize>(h: &PolyVec<K>) -> usize {
     let _ = h;
@@ -233,10 +314,232 @@ pub fn unpack_hints<P: DilithiumParams, const K: usize>(buf: &[u8]) -> Option<Po
     Some(h)
 }
 
+/// Packs a dense hint bitmap into the OMEGA + K wire format. None if more
+/// than OMEGA bits are set (`pack_hints` would silently drop the excess).
+pub fn pack_hint_bitmap<P: DilithiumParams, const K: usize>(bitmap: &[[bool; N]; K]) -> Option<Vec<u8>> {
+    let set = bitmap.iter().flatten().filter(|&&b| b).count();
+    if set > P::OMEGA {
+        return None;
+    }
+    let mut h = PolyVec::<K>::default();
+    for (p, row) in h.v.iter_mut().zip(bitmap.iter()) {
+        for (c, &bit) in p.coeffs.iter_mut().zip(row.iter()) {
+            *c = bit as i32;
+        }
+    }
+    Some(pack_hints::<P, K>(&h))
+}
+
+/// Inverse of `pack_hint_bitmap`, with the same validity checks as `unpack_hints`.
+pub fn unpack_hint_bitmap<P: DilithiumParams, const K: usize>(buf: &[u8]) -> Option<[[bool; N]; K]> {
+    let h = unpack_hints::<P, K>(buf)?;
+    Some(core::array::from_fn(|i| core::array::from_fn(|j| h.v[i].coeffs[j] != 0)))
+}
+
 // Check norms
 pub fn poly_check_norm(a: &Poly, bound: i32) -> bool {
     for &c in &a.coeffs {
         if norm_bound(c) >= bound { return false; }
     }
     true
-}
\ No newline at end of file
+}
+
+/// `poly_check_norm` over a whole vector, as one flat scan that stops at the
+/// first coefficient with norm >= `bound`.
+#[inline]
+pub fn polyvec_check_norm<const M: usize>(v: &PolyVec<M>, bound: i32) -> bool {
+    !v.v.iter()
+        .flat_map(|p| p.coeffs.iter())
+        .any(|&c| norm_bound(c) >= bound)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dilithium_params::Dilithium2;
+
+    // FIPS 204 SimpleBitPack(t1, 10): four 10-bit coefficients -> five bytes, LSB-first.
+    // Written out the same way as the reference `polyt1_pack`, independent of the bit loop above.
+    fn ref_pack_t1(a: &Poly) -> [u8; 320] {
+        let mut r = [0u8; 320];
+        for i in 0..N / 4 {
+            let c: [u32; 4] = core::array::from_fn(|j| a.coeffs[4 * i + j] as u32);
+            r[5 * i] = c[0] as u8;
+            r[5 * i + 1] = ((c[0] >> 8) | (c[1] << 2)) as u8;
+            r[5 * i + 2] = ((c[1] >> 6) | (c[2] << 4)) as u8;
+            r[5 * i + 3] = ((c[2] >> 4) | (c[3] << 6)) as u8;
+            r[5 * i + 4] = (c[3] >> 2) as u8;
+        }
+        r
+    }
+
+    // gamma2 = (q-1)/32 regime (Dilithium5): w1 in 0..=15, 4 bits
+    struct W1FourBit;
+    impl DilithiumParams for W1FourBit {
+        const K: usize = 8;
+        const L: usize = 7;
+        const ETA: i32 = 2;
+        const TAU: usize = 60;
+        const BETA: i32 = 120;
+        const GAMMA1: i32 = 1 << 19;
+        const GAMMA2: i32 = (crate::dilithium_params::Q - 1) / 32;
+        const OMEGA: usize = 75;
+        const CTILDE_BYTES: usize = 64;
+        const POLYETA_PACKEDBYTES: usize = 96;
+        const POLYZ_PACKEDBYTES: usize = 640;
+        const POLYW1_PACKEDBYTES: usize = 128;
+    }
+
+    fn w1_roundtrip<P: DilithiumParams>(max: i32) {
+        let mut a = Poly::default();
+        for (i, c) in a.coeffs.iter_mut().enumerate() {
+            *c = i as i32 % (max + 1);
+        }
+        let bytes = pack_poly_w1::<P>(&a);
+        assert_eq!(bytes.len(), P::POLYW1_PACKEDBYTES);
+        assert_eq!(unpack_poly_w1::<P>(&bytes), a);
+
+        // extremes in every slot
+        for v in [0, max] {
+            let a = Poly { coeffs: [v; N] };
+            assert_eq!(unpack_poly_w1::<P>(&pack_poly_w1::<P>(&a)), a);
+        }
+    }
+
+    #[test]
+    fn w1_pack_roundtrip_six_bit() {
+        // gamma2 = (q-1)/88: w1 in 0..=43
+        w1_roundtrip::<Dilithium2>(43);
+    }
+
+    #[test]
+    fn w1_pack_roundtrip_four_bit() {
+        w1_roundtrip::<W1FourBit>(15);
+    }
+
+    #[test]
+    fn secret_key_polyvecs_roundtrip() {
+        use crate::dilithium_params::{ETA, K, L, POLYETA_PACKEDBYTES};
+
+        // s1 / s2: every value of [-ETA, ETA] in every poly
+        let s1 = PolyVec::<L> {
+            v: core::array::from_fn(|i| Poly { coeffs: core::array::from_fn(|j| ((i + j) as i32 % (2 * ETA + 1)) - ETA) }),
+        };
+        let s2 = PolyVec::<K> {
+            v: core::array::from_fn(|i| Poly { coeffs: core::array::from_fn(|j| ETA - ((i * 3 + j) as i32 % (2 * ETA + 1))) }),
+        };
+        let s1_bytes = pack_polyvec_eta::<Dilithium2, L>(&s1);
+        let s2_bytes = pack_polyvec_eta::<Dilithium2, K>(&s2);
+        assert_eq!(s1_bytes.len(), L * POLYETA_PACKEDBYTES);
+        assert_eq!(s2_bytes.len(), K * POLYETA_PACKEDBYTES);
+        assert_eq!(unpack_polyvec_eta::<Dilithium2, L>(&s1_bytes), Some(s1));
+        assert_eq!(unpack_polyvec_eta::<Dilithium2, K>(&s2_bytes), Some(s2));
+        assert_eq!(unpack_polyvec_eta::<Dilithium2, L>(&s1_bytes[1..]), None);
+
+        // 3-bit field 7 -> coefficient -5, outside [-ETA, ETA]
+        let mut bad = s2_bytes.clone();
+        bad[POLYETA_PACKEDBYTES] |= 0x07;
+        assert_eq!(unpack_polyvec_eta::<Dilithium2, K>(&bad), None);
+
+        // t0 in [-(2^12 - 1), 2^12]
+        let t0 = PolyVec::<K> {
+            v: core::array::from_fn(|i| {
+                Poly { coeffs: core::array::from_fn(|j| ((i * 977 + j * 131) % (1 << 13)) as i32 - (1 << 12) + 1) }
+            }),
+        };
+        let t0_bytes = pack_polyvec_t0::<K>(&t0);
+        assert_eq!(t0_bytes.len(), K * POLYT0_PACKEDBYTES);
+        assert_eq!(unpack_polyvec_t0::<K>(&t0_bytes), Some(t0));
+        assert_eq!(unpack_polyvec_t0::<K>(&t0_bytes[..t0_bytes.len() - 1]), None);
+
+        // same layout SecretKey::to_bytes uses after rho || key || tr
+        let (_, sk) = crate::dilithium::keygen([0x42u8; 32]);
+        let sk_bytes = sk.to_bytes();
+        let mut tail = pack_polyvec_eta::<Dilithium2, L>(&sk.s1);
+        tail.extend(pack_polyvec_eta::<Dilithium2, K>(&sk.s2));
+        tail.extend(pack_polyvec_t0::<K>(&sk.t0));
+        assert_eq!(sk_bytes[128..], tail[..]);
+    }
+
+    #[test]
+    fn polyvec_w1_bytes_into_matches_allocating() {
+        use crate::dilithium_params::K;
+
+        fn check<P: DilithiumParams>(max: i32) {
+            let mut w1 = PolyVec::<K>::default();
+            for (i, p) in w1.v.iter_mut().enumerate() {
+                for (j, c) in p.coeffs.iter_mut().enumerate() {
+                    *c = ((i * 53 + j * 7) as i32) % (max + 1);
+                }
+            }
+            // stale contents from a previous attempt must not leak through
+            let mut buf = vec![0xA5u8; K * P::POLYW1_PACKEDBYTES];
+            polyvec_w1_bytes_into::<P, K>(&w1, &mut buf);
+            assert_eq!(buf, polyvec_w1_bytes::<P, K>(&w1));
+        }
+        check::<Dilithium2>(43);
+        check::<W1FourBit>(15);
+    }
+
+    #[test]
+    fn t1_pack_known_bytes() {
+        let mut a = Poly::default();
+        a.coeffs[0] = 0x3FF;
+        a.coeffs[1] = 0x000;
+        a.coeffs[2] = 0x155;
+        a.coeffs[3] = 0x2AA;
+        let out = pack_poly_t1(&a);
+        assert_eq!(out[..5], [0xFF, 0x03, 0x50, 0x95, 0xAA]);
+        assert!(out[5..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn t1_pack_single_bits_land_lsb_first() {
+        // coefficient 1 at index 1 starts at bit 10 => byte 1, bit 2
+        let mut a = Poly::default();
+        a.coeffs[1] = 1;
+        let out = pack_poly_t1(&a);
+        assert_eq!(out[1], 0x04);
+        assert_eq!(out.iter().filter(|&&b| b != 0).count(), 1);
+
+        // MSB (bit 9) of coefficient 0 => byte 1, bit 1
+        let mut a = Poly::default();
+        a.coeffs[0] = 0x200;
+        let out = pack_poly_t1(&a);
+        assert_eq!(out[0], 0x00);
+        assert_eq!(out[1], 0x02);
+    }
+
+    #[test]
+    fn t1_pack_matches_reference_layout() {
+        let mut a = Poly::default();
+        for i in 0..N {
+            a.coeffs[i] = ((i as i32) * 677 + 13) & 0x3FF;
+        }
+        let out = pack_poly_t1(&a);
+        assert_eq!(out, ref_pack_t1(&a));
+        assert_eq!(unpack_poly_t1(&out), a);
+    }
+
+    #[test]
+    fn polyvec_check_norm_matches_per_poly() {
+        use crate::dilithium_params::{L, Q};
+
+        let bound = 1000;
+        let mut v = PolyVec::<L>::default();
+        for (i, p) in v.v.iter_mut().enumerate() {
+            for (j, c) in p.coeffs.iter_mut().enumerate() {
+                *c = ((i * 97 + j * 31) % 1999) as i32 - 999;
+            }
+        }
+        let per_poly = |v: &PolyVec<L>| v.v.iter().all(|p| poly_check_norm(p, bound));
+        assert!(per_poly(&v));
+        assert_eq!(polyvec_check_norm(&v, bound), per_poly(&v));
+
+        // one violation anywhere, in both signs and in [0,q) representation
+        for (i, j, c) in [(0, 0, 1000), (L - 1, N - 1, -1000), (2, 128, Q - 1000), (1, 7, 999)] {
+            let mut w = v;
+            w.v[i].coeffs[j] = c;
+            assert_eq!(polyvec_check_norm(&w, bound), per_poly(&w), "({}, {}) = {}", i, j, c);
+        }
+    }
+}
@@ -23,6 +23,8 @@ pub const BETA: i32 = 78;
 pub const GAMMA1: i32 = 1 << 17; // 131072
 pub const GAMMA2: i32 = (Q - 1) / 88; // 95232
 pub const OMEGA: usize = 80;
+// c_tilde length lambda/4 (lambda = 128 for Dilithium2)
+pub const CTILDE_BYTES: usize = 32;
 
 // Byte sizes (as in typical Dilithium layouts)
 pub const SEEDBYTES: usize = 32;
@@ -78,6 +80,9 @@ pub trait DilithiumParams {
     const GAMMA1: i32;
     const GAMMA2: i32;
     const OMEGA: usize;
+    const CTILDE_BYTES: usize;
+    /// Bits per y/z coefficient: gamma1=2^17 -> 18, gamma1=2^19 -> 20
+    const GAMMA1_BITS: usize = if Self::GAMMA1 == (1 << 17) { 18 } else { 20 };
 
     const POLYETA_PACKEDBYTES: usize;
     const POLYZ_PACKEDBYTES: usize;
@@ -94,6 +99,7 @@ impl DilithiumParams for Dilithium2 {
     const GAMMA1: i32 = GAMMA1;
     const GAMMA2: i32 = GAMMA2;
     const OMEGA: usize = OMEGA;
+    const CTILDE_BYTES: usize = CTILDE_BYTES;
 
     const POLYETA_PACKEDBYTES: usize = POLYETA_PACKEDBYTES;
     const POLYZ_PACKEDBYTES: usize = POLYz_PACKEDBYTES;
@@ -120,9 +126,12 @@ pub const CRH_BYTES: usize = 64;
 pub const PK_BYTES: usize = SEED_BYTES + K * POLYT1_PACKEDBYTES;
 
 // Secret key bytes (rho||key||tr + s1 + s2 + t0)
-// Dilithium2 uses: 3*SEED_BYTES + L*POLYETA + K*POLYETA + K*POLYT0
-pub const SK_BYTES: usize =
-    3 * SEED_BYTES + L * POLYETA_PACKEDBYTES + K * POLYETA_PACKEDBYTES + K * POLYT0_PACKEDBYTES;
-
-// Signature bytes (z + h + omega + 1)
-pub const SIG_BYTES: usize = L * POLYZ_PACKEDBYTES + POLYVECH_PACKEDBYTES + OMEGA + 1;
+// tr is CRH_BYTES (64) here, as in FIPS 204: 2*SEED_BYTES + CRH_BYTES + L*POLYETA + K*POLYETA + K*POLYT0
+pub const SK_BYTES: usize = 2 * SEED_BYTES
+    + CRH_BYTES
+    + L * POLYETA_PACKEDBYTES
+    + K * POLYETA_PACKEDBYTES
+    + K * POLYT0_PACKEDBYTES;
+
+// Signature bytes (c_tilde + z + h), h packed as OMEGA positions + K counts
+pub const SIG_BYTES: usize = CTILDE_BYTES + L * POLYZ_PACKEDBYTES + OMEGA + K; // 2420
@@ -2,9 +2,11 @@
 
 use core::fmt;
 
-use crate::dilithium_params::{N, Q};
-use crate::dilithium_reduce::{add_mod, sub_mod, mod_q, mont_fqmul};
-use crate::dilithium_ntt::{ntt, intt};
+use zeroize::Zeroize;
+
+use crate::dilithium_params::N;
+use crate::dilithium_reduce::{add_mod, center_mod_q, freeze, sub_mod, mod_q, mont_fqmul};
+use crate::dilithium_ntt::{ntt_direct, intt_direct};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Poly {
@@ -26,7 +28,24 @@ impl fmt::Debug for Poly {
     }
 }
 
+// Coefficient representations. Any i32 congruent mod q is a valid value;
+// which range a Poly is in depends on what produced it:
+//   reduce, add_assign, sub_assign, shiftl, to_positive   [0, q)
+//   to_centered, center_coeff                             [-(q-1)/2, (q-1)/2]
+//   intt, pointwise_mul                                   (-q, q)
+//   ntt                                                   unreduced, |x| < |input| + 8q
+// The packers expect the ranges documented on the key/signature fields.
 impl Poly {
+    /// Copy with every coefficient in [-(q-1)/2, (q-1)/2].
+    pub fn to_centered(&self) -> Poly {
+        Poly { coeffs: self.coeffs.map(center_mod_q) }
+    }
+
+    /// Copy with every coefficient in [0, q).
+    pub fn to_positive(&self) -> Poly {
+        Poly { coeffs: self.coeffs.map(freeze) }
+    }
+
     /// Reduce coeffs into [0, q)
     pub fn reduce(&mut self) {
         self.coeffs
@@ -58,11 +77,11 @@ impl Poly {
     }
 
     pub fn ntt(&mut self) {
-        ntt(&mut self.coeffs);
+        ntt_direct(&mut self.coeffs);
     }
 
     pub fn intt(&mut self) {
-        intt(&mut self.coeffs);
+        intt_direct(&mut self.coeffs);
     }
 
     /// Pointwise multiplication in NTT domain:
@@ -72,13 +91,21 @@ impl Poly {
         Poly { coeffs }
     }
 
+    /// a*b in Z_q[X]/(X^N+1) through separate NTT, pointwise and INTT runs.
+    /// The Montgomery factor of `pointwise_mul` cancels the 2^32 of `intt`.
+    pub fn mul_ntt(a: &Poly, b: &Poly) -> Poly {
+        let (mut a_hat, mut b_hat) = (*a, *b);
+        a_hat.ntt();
+        b_hat.ntt();
+        let mut c = Poly::pointwise_mul(&a_hat, &b_hat);
+        c.intt();
+        c
+    }
+
     /// Optional helper: center into [-q/2, q/2]
     #[allow(dead_code)]
     pub fn center_coeff(x: i32) -> i32 {
-        let mut t = x % Q;
-        if t < 0 { t += Q; }
-        if t > Q / 2 { t -= Q; }
-        t
+        center_mod_q(x)
     }
 }
 
@@ -93,6 +120,20 @@ impl<const DIM: usize> Default for PolyVec<DIM> {
     }
 }
 
+impl Zeroize for Poly {
+    fn zeroize(&mut self) {
+        self.coeffs.zeroize();
+    }
+}
+
+impl<const DIM: usize> Zeroize for PolyVec<DIM> {
+    fn zeroize(&mut self) {
+        for p in self.v.iter_mut() {
+            p.zeroize();
+        }
+    }
+}
+
 impl<const DIM: usize> fmt::Debug for PolyVec<DIM> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Same idea: keep it small
@@ -104,6 +145,16 @@ impl<const DIM: usize> fmt::Debug for PolyVec<DIM> {
 }
 
 impl<const DIM: usize> PolyVec<DIM> {
+    /// `Poly::to_centered` on every polynomial.
+    pub fn to_centered(&self) -> Self {
+        Self { v: self.v.map(|p| p.to_centered()) }
+    }
+
+    /// `Poly::to_positive` on every polynomial.
+    pub fn to_positive(&self) -> Self {
+        Self { v: self.v.map(|p| p.to_positive()) }
+    }
+
     pub fn reduce(&mut self) {
         self.v.iter_mut().for_each(|p| p.reduce());
     }
@@ -131,18 +182,23 @@ impl<const DIM: usize> PolyVec<DIM> {
     }
 
     /// Sum_i (a[i] (*) b[i]) where (*) is NTT-domain pointwise mul.
-    #[allow(dead_code)]
     pub fn pointwise_acc(a: &PolyVec<DIM>, b: &PolyVec<DIM>) -> Poly {
-        a.v.iter()
-            .zip(b.v.iter())
-            .fold(Poly::default(), |mut acc, (pa, pb)| {
-                let t = Poly::pointwise_mul(pa, pb);
-                acc.add_assign(&t);
-                acc
-            })
+        pointwise_acc_slices(&a.v, &b.v)
     }
 }
 
+/// The multiply-accumulate behind `PolyVec::pointwise_acc` and
+/// `PolyMat::row_times_vec`.
+fn pointwise_acc_slices(a: &[Poly], b: &[Poly]) -> Poly {
+    a.iter()
+        .zip(b.iter())
+        .fold(Poly::default(), |mut acc, (pa, pb)| {
+            let t = Poly::pointwise_mul(pa, pb);
+            acc.add_assign(&t);
+            acc
+        })
+}
+
 /// Matrix type used by expand_a / mat-vec mul.
 #[derive(Clone, Copy)]
 pub struct PolyMat<const K: usize, const L: usize> {
@@ -155,6 +211,13 @@ impl<const K: usize, const L: usize> Default for PolyMat<K, L> {
     }
 }
 
+impl<const K: usize, const L: usize> PolyMat<K, L> {
+    /// Row i of A times v, all NTT domain: Sum_j A[i][j] (*) v[j].
+    pub fn row_times_vec(&self, i: usize, v: &PolyVec<L>) -> Poly {
+        pointwise_acc_slices(&self.m[i], &v.v)
+    }
+}
+
 impl<const K: usize, const L: usize> fmt::Debug for PolyMat<K, L> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("PolyMat")
@@ -38,6 +38,68 @@ pub fn mul_mod(a: i32, b: i32) -> i32 {
     mod_q((a as i64) * (b as i64))
 }
 
+/// Bring any i32 into [0, Q).
+#[inline]
+pub fn freeze(a: i32) -> i32 {
+    mod_q(a as i64)
+}
+
+/// Centered representative of any i32 mod Q, in [-(Q-1)/2, (Q-1)/2].
+#[inline]
+pub fn center_mod_q(a: i32) -> i32 {
+    let r = freeze(a);
+    if r > Q / 2 { r - Q } else { r }
+}
+
+pub fn pow_mod(base: i32, mut exp: i64) -> i32 {
+    let mut acc: i32 = 1;
+    let mut base = freeze(base);
+    while exp > 0 {
+        if (exp & 1) == 1 {
+            acc = mul_mod(acc, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    acc
+}
+
+pub fn inv_mod(a: i32) -> i32 {
+    // Fermat: a^(Q-2) mod Q, since Q is prime in Dilithium
+    pow_mod(a, (Q as i64) - 2)
+}
+
+/// Modular inverse via binary extended GCD (shifts and subtractions only,
+/// no multiplications). Same result as `inv_mod`; returns 0 for a = 0 mod Q.
+pub fn inv_mod_egcd(a: i32) -> i32 {
+    let (mut u, mut v) = (freeze(a), Q);
+    if u == 0 {
+        return 0;
+    }
+    // invariants: x1 * a == u, x2 * a == v (mod Q)
+    let (mut x1, mut x2) = (1i32, 0i32);
+    // Q is odd, so halving x mod Q is x/2 or (x+Q)/2
+    let half = |x: i32| if x & 1 == 0 { x >> 1 } else { ((x as i64 + Q as i64) >> 1) as i32 };
+    while u != 1 && v != 1 {
+        while u & 1 == 0 {
+            u >>= 1;
+            x1 = half(x1);
+        }
+        while v & 1 == 0 {
+            v >>= 1;
+            x2 = half(x2);
+        }
+        if u >= v {
+            u -= v;
+            x1 = sub_mod(x1, x2);
+        } else {
+            v -= u;
+            x2 = sub_mod(x2, x1);
+        }
+    }
+    if u == 1 { x1 } else { x2 }
+}
+
 // -----------------------------------------------------------------------------
 // Montgomery multiplication (software)
 //
@@ -48,7 +110,7 @@ pub fn mul_mod(a: i32, b: i32) -> i32 {
 //   mont_fqmul(a, b) = (a * b * R^{-1}) mod q
 // which yields a result also in Montgomery form.
 
-/// Software Montgomery reduction for q=8380417, QINV = -q^{-1} mod 2^32.
+/// Software Montgomery reduction for q=8380417, QINV = q^{-1} mod 2^32.
 #[inline(always)]
 pub fn montgomery_reduce(a: i64) -> i32 {
     // Match the reference's 32-bit behavior:
@@ -68,3 +130,172 @@ pub fn montgomery_reduce(a: i64) -> i32 {
 pub fn mont_fqmul(a: i32, b: i32) -> i32 {
     montgomery_reduce((a as i64) * (b as i64))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dilithium_ntt;
+
+    // The reduction computes (a - t*q) >> 32 with t = a*QINV, so the low
+    // word cancels only if q*QINV == +1 (mod 2^32). With the "a + t*q"
+    // form it would be -1 instead.
+    #[test]
+    fn qinv_is_inverse_of_q_mod_2_32() {
+        assert_eq!((Q as i64 * QINV as i64).rem_euclid(1 << 32), 1);
+    }
+
+    fn wide_inputs() -> Vec<i32> {
+        let mut v: Vec<i32> = vec![0, 1, 2, Q / 2, Q / 2 + 1, Q - 2, Q - 1];
+        let mut x: u32 = 0x1234_5678;
+        for _ in 0..2000 {
+            x = x.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            v.push((x % Q as u32) as i32);
+        }
+        v
+    }
+
+    // The bodies dilithium_arith carried before it became a re-export of
+    // this module, kept verbatim as the reference for the consolidation.
+    mod old_arith {
+        use crate::dilithium_params::Q;
+
+        pub fn freeze(mut a: i32) -> i32 {
+            a %= Q;
+            if a < 0 { a += Q; }
+            a
+        }
+
+        pub fn add_mod(a: i32, b: i32) -> i32 {
+            let mut s = a + b;
+            if s >= Q { s -= Q; }
+            s
+        }
+
+        pub fn sub_mod(a: i32, b: i32) -> i32 {
+            let mut d = a - b;
+            if d < 0 { d += Q; }
+            d
+        }
+
+        pub fn mul_mod(a: i32, b: i32) -> i32 {
+            ((a as i64 * b as i64) % (Q as i64)) as i32
+        }
+
+        pub fn pow_mod(mut base: i32, mut exp: i64) -> i32 {
+            let mut acc: i32 = 1;
+            base = freeze(base);
+            while exp > 0 {
+                if (exp & 1) == 1 {
+                    acc = mul_mod(acc, base);
+                }
+                base = mul_mod(base, base);
+                exp >>= 1;
+            }
+            acc
+        }
+
+        pub fn inv_mod(a: i32) -> i32 {
+            pow_mod(a, (Q as i64) - 2)
+        }
+    }
+
+    #[test]
+    fn modular_ops_match_old_arith() {
+        let q = Q as i64;
+        let canonical = wide_inputs();
+        for (&a, &b) in canonical.iter().zip(canonical.iter().rev()) {
+            let (a64, b64) = (a as i64, b as i64);
+            assert_eq!(add_mod(a, b) as i64, (a64 + b64).rem_euclid(q), "add {a} {b}");
+            assert_eq!(sub_mod(a, b) as i64, (a64 - b64).rem_euclid(q), "sub {a} {b}");
+            assert_eq!(mul_mod(a, b) as i64, (a64 * b64).rem_euclid(q), "mul {a} {b}");
+
+            // on canonical operands the two implementations agree exactly
+            assert_eq!(add_mod(a, b), old_arith::add_mod(a, b), "add {a} {b}");
+            assert_eq!(sub_mod(a, b), old_arith::sub_mod(a, b), "sub {a} {b}");
+            assert_eq!(mul_mod(a, b), old_arith::mul_mod(a, b), "mul {a} {b}");
+        }
+
+        // negative operands: either side may return a non-canonical
+        // representative here, so compare mod Q
+        let mut wide: Vec<i32> = canonical.iter().map(|&x| x - Q).collect();
+        wide.extend([-Q + 1, -1, 0, Q - 1]);
+        for (&a, &b) in wide.iter().zip(canonical.iter()) {
+            for (x, y) in [(a, b), (b, a), (a, a)] {
+                assert_eq!((add_mod(x, y) as i64).rem_euclid(q), (old_arith::add_mod(x, y) as i64).rem_euclid(q), "add {x} {y}");
+                assert_eq!((sub_mod(x, y) as i64).rem_euclid(q), (old_arith::sub_mod(x, y) as i64).rem_euclid(q), "sub {x} {y}");
+                assert_eq!(mul_mod(x, y), old_arith::mul_mod(x, y).rem_euclid(Q), "mul {x} {y}");
+            }
+        }
+
+        let extremes = [i32::MIN, i32::MIN + 1, -Q - 1, -Q, -1, Q, 2 * Q + 5, i32::MAX - 1, i32::MAX];
+        for a in canonical.iter().copied().chain(wide.iter().copied()).chain(extremes) {
+            assert_eq!(freeze(a), old_arith::freeze(a), "freeze {a}");
+            assert_eq!(freeze(a) as i64, (a as i64).rem_euclid(q), "freeze {a}");
+            assert_eq!(mod_q(a as i64 * 3) as i64, (a as i64 * 3).rem_euclid(q), "mod_q {a}");
+            for b in extremes {
+                assert_eq!(mul_mod(a, b), old_arith::mul_mod(a, b).rem_euclid(Q), "mul {a} {b}");
+            }
+            assert_eq!(pow_mod(a, 5), old_arith::pow_mod(a, 5), "pow {a}");
+        }
+        for &a in canonical.iter().step_by(10).chain(extremes.iter()) {
+            assert_eq!(inv_mod(a), old_arith::inv_mod(a), "inv {a}");
+        }
+        // add_mod also accepts a slightly negative sum
+        assert_eq!(add_mod(-5, 3), Q - 2);
+    }
+
+    #[test]
+    fn center_mod_q_boundaries() {
+        let half = Q / 2; // (Q-1)/2, Q is odd
+        assert_eq!(center_mod_q(half), half);
+        assert_eq!(center_mod_q(half + 1), -half);
+        assert_eq!(center_mod_q(-half), -half);
+        assert_eq!(center_mod_q(-half - 1), half);
+        assert_eq!(center_mod_q(Q), 0);
+        assert_eq!(center_mod_q(-Q), 0);
+        for a in wide_inputs().into_iter().chain([i32::MIN, i32::MAX, -1, -Q - 1]) {
+            let c = center_mod_q(a);
+            assert!((-half..=half).contains(&c), "a={a}");
+            assert_eq!((c as i64 - a as i64).rem_euclid(Q as i64), 0, "a={a}");
+        }
+    }
+
+    #[test]
+    fn pow_and_inverse_agree() {
+        for &a in wide_inputs().iter().skip(1).take(200) {
+            let inv = inv_mod(a);
+            assert_eq!(mul_mod(a, inv), 1, "a={a}");
+            assert_eq!(pow_mod(a, 3), mul_mod(mul_mod(a, a), a));
+            assert_eq!(pow_mod(a, 0), 1);
+        }
+        // 1753 is the 512th root of unity used for Dilithium's zetas
+        assert_eq!(pow_mod(1753, 512), 1);
+        assert_eq!(pow_mod(1753, 256), Q - 1);
+    }
+
+    #[test]
+    fn inv_mod_egcd_matches_fermat() {
+        let mut xs = wide_inputs();
+        xs.extend([-1, -Q + 1, Q + 7, 1 << 23]);
+        for &a in xs.iter().filter(|&&a| freeze(a) != 0) {
+            let inv = inv_mod_egcd(a);
+            assert_eq!(inv, inv_mod(a), "a={a}");
+            assert_eq!(mul_mod(freeze(a), inv), 1, "a={a}");
+        }
+        assert_eq!(inv_mod_egcd(0), 0);
+        assert_eq!(inv_mod_egcd(Q), 0);
+    }
+
+    #[test]
+    fn montgomery_reduce_divides_by_r() {
+        for a in [0i64, 1, -1, Q as i64, 123_456_789, -987_654_321, ((Q as i64) << 31) - 1, 1 - ((Q as i64) << 31)] {
+            let r = montgomery_reduce(a) as i64;
+            assert!(r.abs() < Q as i64, "a={}", a);
+            assert_eq!((r * (1i64 << 32) - a).rem_euclid(Q as i64), 0, "a={}", a);
+
+            // FSM datapath uses the same constant
+            let hw = dilithium_ntt::montgomery_reduce(rhdl::prelude::signed::<rhdl::prelude::U64>(a as i128));
+            assert_eq!(hw.raw() as i64, r, "a={}", a);
+        }
+    }
+}
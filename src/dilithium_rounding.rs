@@ -1,56 +1,56 @@
-use crate::dilithium_params::{Q, GAMMA2};
+use crate::dilithium_params::Q;
+use crate::dilithium_reduce::center_mod_q;
 
 // Decompose r = r1 * alpha + r0
 // Returns (r1, r0) such that r0 is in range.
-// Parametrul `_alpha` este prefixat cu _ pentru că algoritmul standard
-// folosește GAMMA2 direct, dar păstrăm semnătura generică.
-pub fn decompose(mut r: i32, _alpha: i32) -> (i32, i32) {
+// `alpha` = 2*gamma2; only gamma2 = (Q-1)/32 (levels 3, 5) and (Q-1)/88
+// (Dilithium2) are supported.
+pub fn decompose(mut r: i32, alpha: i32) -> (i32, i32) {
+    let gamma2 = alpha / 2;
+    debug_assert!(gamma2 == (Q - 1) / 32 || gamma2 == (Q - 1) / 88);
     r = r % Q;
     if r < 0 { r += Q; }
 
     // Calculăm r1 direct, fără inițializări inutile
     let mut r1 = (r + 127) >> 7;
     
-    if GAMMA2 == (Q - 1) / 32 {
+    if gamma2 == (Q - 1) / 32 {
         r1 = (r1 * 1025 + (1 << 21)) >> 22;
         r1 &= 15;
-    } else if GAMMA2 == (Q - 1) / 88 { // Dilithium2 case
+    } else if gamma2 == (Q - 1) / 88 { // Dilithium2 case
         r1 = (r1 * 11275 + (1 << 23)) >> 24;
         r1 ^= ((43 - r1) >> 31) & r1;
     }
 
-    let mut r0 = r - r1 * 2 * GAMMA2;
     // Reduce mod Q centrat
-    r0 = r0 % Q;
-    if r0 < -(Q/2) { r0 += Q; }
-    if r0 > Q/2 { r0 -= Q; }
+    let r0 = center_mod_q(r - r1 * 2 * gamma2);
     
     (r1, r0)
 }
 
-pub fn high_bits(r: i32, alpha: i32) -> i32 {
-    let (r1, _) = decompose(r, 2 * alpha);
+pub fn high_bits(r: i32, gamma2: i32) -> i32 {
+    let (r1, _) = decompose(r, 2 * gamma2);
     r1
 }
 
-pub fn low_bits(r: i32, alpha: i32) -> i32 {
-    let (_, r0) = decompose(r, 2 * alpha);
+pub fn low_bits(r: i32, gamma2: i32) -> i32 {
+    let (_, r0) = decompose(r, 2 * gamma2);
     r0
 }
 
-pub fn make_hint(z: i32, r: i32, alpha: i32) -> i32 {
-    let r1 = high_bits(r, alpha);
-    let v1 = high_bits(r + z, alpha);
+pub fn make_hint(z: i32, r: i32, gamma2: i32) -> i32 {
+    let r1 = high_bits(r, gamma2);
+    let v1 = high_bits(r + z, gamma2);
     if r1 != v1 { 1 } else { 0 }
 }
 
-pub fn use_hint(r: i32, hint: u8, alpha: i32) -> i32 {
-    let (r1, r0) = decompose(r, 2 * alpha);
+pub fn use_hint(r: i32, hint: u8, gamma2: i32) -> i32 {
+    let (r1, r0) = decompose(r, 2 * gamma2);
     if hint == 0 {
         return r1;
     }
     
-    if GAMMA2 == (Q - 1) / 32 {
+    if gamma2 == (Q - 1) / 32 {
         if r0 > 0 { (r1 + 1) & 15 } else { (r1 - 1) & 15 }
     } else {
         // Dilithium2 case
@@ -81,4 +81,52 @@ pub fn norm_bound(mut x: i32) -> i32 {
     if x < 0 { x += Q; }
     if x > Q / 2 { x = Q - x; }
     x
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAMMA2_32: i32 = (Q - 1) / 32; // levels 3 and 5
+    const GAMMA2_88: i32 = (Q - 1) / 88; // Dilithium2
+
+    // every r near a multiple of 2*gamma2, both ends of [0, q), plus a coarse sweep
+    fn sample_r(gamma2: i32) -> Vec<i32> {
+        let mut rs: Vec<i32> = (0..Q).step_by(997).collect();
+        for m in 0..=(Q / (2 * gamma2)) {
+            let c = m * 2 * gamma2;
+            rs.extend((c - gamma2 - 2..=c - gamma2 + 2).chain(c - 2..=c + 2));
+        }
+        rs.extend(Q - 3..Q);
+        rs.retain(|&r| (0..Q).contains(&r));
+        rs
+    }
+
+    #[test]
+    fn decompose_gamma2_32_reconstructs() {
+        for r in sample_r(GAMMA2_32) {
+            let (r1, r0) = decompose(r, 2 * GAMMA2_32);
+            assert!((0..16).contains(&r1), "r = {}: r1 = {}", r, r1);
+            assert!(r0.abs() <= GAMMA2_32, "r = {}: r0 = {}", r, r0);
+            assert_eq!((r1 * 2 * GAMMA2_32 + r0).rem_euclid(Q), r, "r = {}", r);
+        }
+        // q-1 wraps to r1 = 0 (the 16 -> 0 case the &15 mask handles)
+        assert_eq!(decompose(Q - 1, 2 * GAMMA2_32), (0, -1));
+        assert_eq!(decompose(16 * GAMMA2_32 * 2 - 1, 2 * GAMMA2_32).0, 0);
+    }
+
+    #[test]
+    fn use_hint_recovers_high_bits() {
+        for gamma2 in [GAMMA2_32, GAMMA2_88] {
+            let modulus = (Q - 1) / (2 * gamma2);
+            for r in sample_r(gamma2) {
+                for z in [-gamma2, -gamma2 / 2, -1, 0, 1, gamma2 / 3, gamma2] {
+                    let h = make_hint(z, r, gamma2);
+                    let r1 = use_hint(r, h as u8, gamma2);
+                    assert_eq!(r1, high_bits(r + z, gamma2), "gamma2 = {}, r = {}, z = {}", gamma2, r, z);
+                    assert!((0..modulus).contains(&r1));
+                }
+            }
+        }
+    }
+}
@@ -3,9 +3,9 @@
 
 #![allow(dead_code)]
 
-use crate::dilithium_params::{N, Q, K, L, ETA, GAMMA1, TAU};
+use crate::dilithium_params::{Dilithium2, DilithiumParams, N, Q, K, L, ETA, TAU};
 use crate::dilithium_poly::{Poly, PolyVec, PolyMat};
-use crate::xof_stream::ShakeStream;
+use crate::xof_stream::{rej_uniform_generic, ShakeStream};
 
 // Uniform poly in [0,q) using rejection sampling from SHAKE128(seed||nonce)
 pub fn poly_uniform(seed: &[u8], nonce: u16) -> Poly {
@@ -23,38 +23,30 @@ pub fn poly_uniform(seed: &[u8], nonce: u16) -> Poly {
 
     let mut stream = ShakeStream::shake128(&inbuf);
 
-    let mut out = Poly::default();
-    let mut ctr = 0usize;
+    let mut coeffs = [0u32; N];
+    rej_uniform_generic::<23, { Q as u32 }>(&mut stream, &mut coeffs);
 
-    while ctr < N {
-        let b0 = stream.next_u8() as u32;
-        let b1 = stream.next_u8() as u32;
-        let b2 = stream.next_u8() as u32;
-
-        let t = b0 | (b1 << 8) | (b2 << 16);
-        let a = (t & 0x7FFFFF) as i32; // 23-bit
-        if a < Q {
-            out.coeffs[ctr] = a;
-            ctr += 1;
-        }
+    let mut out = Poly::default();
+    for (c, &v) in out.coeffs.iter_mut().zip(coeffs.iter()) {
+        *c = v as i32;
     }
-
     out
 }
 
+// FIPS 204 ExpandA: the rejection samples are already NTT-domain coefficients.
 pub fn expand_a(rho: &[u8; 32]) -> PolyMat<K, L> {
     let mut mat = PolyMat::<K, L>::default();
     for i in 0..K {
         for j in 0..L {
             let nonce = ((i as u16) << 8) | (j as u16);
             mat.m[i][j] = poly_uniform(rho, nonce);
-            mat.m[i][j].ntt();
         }
     }
     mat
 }
 
-// Sample with small coefficients (your existing mapping logic), now using SHAKE256 stream.
+// Sample with small coefficients (FIPS 204 RejBoundedPoly), using a SHAKE256 stream.
+// Each byte gives two half-bytes; for eta = 2 a half-byte b < 15 maps to 2 - (b mod 5).
 pub fn poly_uniform_eta(seed: &[u8], nonce: u16) -> Poly {
     assert_eq!(seed.len(), 64, "poly_uniform_eta expects 64-byte seed (rho_prime)");
     let mut inbuf = [0u8; 66];
@@ -69,14 +61,12 @@ pub fn poly_uniform_eta(seed: &[u8], nonce: u16) -> Poly {
 
     while ctr < N {
         let b = stream.next_u8();
-        for shift in [0u8, 3u8] {
+        for t in [b & 0x0F, b >> 4] {
             if ctr >= N {
                 break;
             }
-            let t = (b >> shift) & 0x7;
-            if t < 5 {
-                // Same mapping as your previous code
-                out.coeffs[ctr] = ETA - (t as i32);
+            if t < 15 {
+                out.coeffs[ctr] = ETA - (t % 5) as i32;
                 ctr += 1;
             }
         }
@@ -102,6 +92,13 @@ pub fn expand_s(rho_prime: &[u8]) -> (PolyVec<L>, PolyVec<K>) {
 }
 
 pub fn poly_uniform_gamma1(seed: &[u8], nonce: u16) -> Poly {
+    poly_uniform_gamma1_with::<Dilithium2>(seed, nonce)
+}
+
+/// `poly_uniform_gamma1` for any parameter set: FIPS 204 ExpandMask, each
+/// `P::GAMMA1_BITS`-bit value t becomes gamma1 - t, so coefficients are in
+/// [-(gamma1-1), gamma1] (the `pack_poly_z` range). Every value is kept.
+pub fn poly_uniform_gamma1_with<P: DilithiumParams>(seed: &[u8], nonce: u16) -> Poly {
     assert_eq!(seed.len(), 64, "poly_uniform_gamma1 expects 64-byte seed (rho_prime)");
     let mut inbuf = [0u8; 66];
     inbuf[..64].copy_from_slice(seed);
@@ -110,32 +107,25 @@ pub fn poly_uniform_gamma1(seed: &[u8], nonce: u16) -> Poly {
 
     let mut stream = ShakeStream::shake256(&inbuf);
 
-    let bits = 18usize;
-    let mask = (1u32 << bits) - 1;
+    let bits = P::GAMMA1_BITS;
+    let mask = (1u64 << bits) - 1;
 
     let mut out = Poly::default();
-    let mut idx = 0usize;
 
-    let mut acc: u32 = 0;
+    let mut acc: u64 = 0;
     let mut acc_bits: u32 = 0;
 
-    while idx < N {
+    for c in out.coeffs.iter_mut() {
         while acc_bits < bits as u32 {
-            let b = stream.next_u8() as u32;
-            acc |= b << acc_bits;
-            acc_bits += 8;
+            acc |= (stream.next_u16_le() as u64) << acc_bits;
+            acc_bits += 16;
         }
 
         let t = (acc & mask) as i32;
         acc >>= bits;
         acc_bits -= bits as u32;
 
-        // Your old condition: t <= 2*GAMMA1 - 2
-        // With bits=18 and GAMMA1=2^17 => reject only one value (262143).
-        if t <= 2 * GAMMA1 - 2 {
-            out.coeffs[idx] = (GAMMA1 - 1) - t;
-            idx += 1;
-        }
+        *c = P::GAMMA1 - t;
     }
 
     out
@@ -153,27 +143,38 @@ pub fn expand_mask(rho_prime: &[u8], kappa: u16) -> PolyVec<L> {
 
 pub fn challenge(c_tilde: &[u8; 32]) -> Poly {
     let mut stream = ShakeStream::shake256(c_tilde);
+    sample_in_ball(|| stream.next_u8())
+}
 
+/// NTT(challenge(c_tilde)): c in the domain sign and verify multiply it in.
+pub fn challenge_ntt(c_tilde: &[u8; 32]) -> Poly {
+    let mut c_hat = challenge(c_tilde);
+    c_hat.ntt();
+    c_hat
+}
+
+/// FIPS 204 SampleInBall over any byte source: 8 sign bytes, then for each
+/// i in N-TAU..N a position j <= i (bytes above i are rejected); c[i] takes
+/// c[j] and c[j] gets the next sign. Reads as many bytes as it takes, like
+/// the reference squeezing without bound, so the result always has exactly
+/// TAU entries in {-1, +1}.
+pub fn sample_in_ball<F: FnMut() -> u8>(mut next_byte: F) -> Poly {
     let mut out = Poly::default();
 
     // signs from first 8 bytes
     let mut signs: u64 = 0;
     for i in 0..8 {
-        signs |= (stream.next_u8() as u64) << (8 * i);
+        signs |= (next_byte() as u64) << (8 * i);
     }
 
-    let mut used = [false; N];
-    let mut count = 0usize;
-
-    while count < TAU {
-        let b = stream.next_u8() as usize;
-        if b < N && !used[b] {
-            used[b] = true;
-            let s = if (signs & 1) == 1 { -1 } else { 1 };
-            signs >>= 1;
-            out.coeffs[b] = s;
-            count += 1;
+    for i in N - TAU..N {
+        let mut j = next_byte() as usize;
+        while j > i {
+            j = next_byte() as usize;
         }
+        out.coeffs[i] = out.coeffs[j];
+        out.coeffs[j] = if (signs & 1) == 1 { -1 } else { 1 };
+        signs >>= 1;
     }
 
     out
@@ -185,11 +186,24 @@ pub fn mat_vec_mul_ntt(a_hat: &PolyMat<K, L>, y: &PolyVec<L>) -> PolyVec<K> {
 
     let mut out = PolyVec::<K>::default();
     for i in 0..K {
-        let mut acc = Poly::default();
-        for j in 0..L {
-            let t = Poly::pointwise_mul(&a_hat.m[i][j], &y_hat.v[j]);
-            acc.add_assign(&t);
-        }
+        let mut acc = a_hat.row_times_vec(i, &y_hat);
+        acc.intt();
+        out.v[i] = acc;
+    }
+    out
+}
+
+/// `mat_vec_mul_ntt` without the K x L matrix in memory: row i of A is
+/// resampled from rho just before it is accumulated, so a single row is
+/// live at a time. Same result, K*L extra SHAKE128 expansions per call.
+pub fn mat_vec_mul_ntt_streamed(rho: &[u8; 32], y: &PolyVec<L>) -> PolyVec<K> {
+    let mut y_hat = *y;
+    y_hat.ntt();
+
+    let mut out = PolyVec::<K>::default();
+    for i in 0..K {
+        let row = PolyVec::<L> { v: core::array::from_fn(|j| poly_uniform(rho, ((i as u16) << 8) | (j as u16))) };
+        let mut acc = PolyVec::pointwise_acc(&row, &y_hat);
         acc.intt();
         out.v[i] = acc;
     }
@@ -46,20 +46,1047 @@ mod tests {
         let valid = verify(&pk1, msg, &sig);
         assert!(!valid, "Verificarea ar trebui sa esueze cu cheie publica gresita");
     }
+
+    #[test]
+    fn test_verify_rejects_any_c_tilde_byte() {
+        let (pk, sk) = keygen([44u8; 32]);
+        let msg = b"early exit";
+        let sig = sign(&sk, msg);
+        assert!(verify(&pk, msg, &sig));
+
+        // first, middle and last byte: the compare must not stop too early
+        for pos in [0usize, 15, 31] {
+            let mut bad = sig.clone();
+            bad.c_tilde[pos] ^= 0x01;
+            assert!(!verify(&pk, msg, &bad), "tampered byte {} accepted", pos);
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_out_of_range_t1() {
+        let (pk, sk) = keygen([0x3Fu8; 32]);
+        let msg = b"t1 range";
+        let sig = sign(&sk, msg);
+        assert!(verify(&pk, msg, &sig));
+
+        for bad_value in [1024, 1024 + pk.t1.v[1].coeffs[7], -1] {
+            let mut bad = pk.clone();
+            bad.t1.v[1].coeffs[7] = bad_value;
+            bad.tr = None;
+            assert!(!verify(&bad, msg, &sig), "t1 = {}", bad_value);
+        }
+    }
+
+    #[test]
+    fn test_cached_tr_matches_recomputed() {
+        let (pk, sk) = keygen([0x44u8; 32]);
+        let msg = b"cached tr";
+        let sig = sign(&sk, msg);
+
+        assert_eq!(pk.tr, Some(pk.compute_tr()));
+        assert_eq!(pk.tr(), sk.tr);
+        let decoded = crate::dilithium::PublicKey::from_bytes(&pk.to_bytes()).unwrap();
+        assert_eq!(decoded.tr, Some(sk.tr));
+
+        // no cached tr: verify recomputes it and gives the same answers
+        let mut bare = pk.clone();
+        bare.tr = None;
+        assert_eq!(bare.tr(), sk.tr);
+        assert!(verify(&pk, msg, &sig) && verify(&bare, msg, &sig));
+        assert!(!verify(&pk, b"other", &sig) && !verify(&bare, b"other", &sig));
+    }
+
+    #[test]
+    fn test_incremental_tr_matches_hash_of_encoded_key() {
+        use crate::dilithium::compute_tr;
+
+        for seed in [[0u8; 32], [0x44u8; 32], [0xE1u8; 32]] {
+            let (pk, sk) = keygen(seed);
+            let mut whole = [0u8; 64];
+            crate::shake::shake256(&pk.to_bytes(), &mut whole);
+            assert_eq!(compute_tr(&pk.rho, &pk.t1), whole);
+            assert_eq!(sk.tr, whole);
+        }
+    }
+
+    #[test]
+    fn test_key_bytes_roundtrip() {
+        use crate::dilithium::{PublicKey, SecretKey};
+        use crate::dilithium_params::{PK_BYTES, SK_BYTES};
+
+        let (pk, sk) = keygen([17u8; 32]);
+        let pk_bytes = pk.to_bytes();
+        let sk_bytes = sk.to_bytes();
+        assert_eq!(SK_BYTES, 2560);
+
+        let pk2 = PublicKey::from_bytes(&pk_bytes).unwrap();
+        let sk2 = SecretKey::from_bytes(&sk_bytes).unwrap();
+        assert_eq!(pk2.to_bytes(), pk_bytes);
+        assert_eq!(sk2.to_bytes(), sk_bytes);
+
+        let sig = sign(&sk2, b"bytes");
+        assert!(verify(&pk2, b"bytes", &sig));
+
+        assert!(PublicKey::from_bytes(&pk_bytes[..PK_BYTES - 1]).is_none());
+        // s1[0] coefficient encoded as 7 -> ETA - 7 is out of range
+        let mut bad = sk_bytes;
+        bad[128] |= 0x07;
+        assert!(SecretKey::from_bytes(&bad).is_none());
+    }
+
+    #[test]
+    fn test_keygen_fips204_seed_vector() {
+        use crate::shake::shake256;
+        use crate::util::hex;
+
+        // ML-DSA-44 KeyGen_internal(xi = 00 01 .. 1f) from OpenSSL 3.5.6:
+        //   openssl genpkey -algorithm ML-DSA-44 -pkeyopt hexseed:000102..1f
+        // rho and key are the first 64 bytes of its sk, the digests are
+        // SHAKE256 over its full pk (= tr) and sk.
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let (pk, sk) = keygen(seed);
+
+        let rho = hex::decode("d7b2b47254aae0db45e7930d4a98d2c97d8f1397d1789dafa17024b316e9bec9").unwrap();
+        let key = hex::decode("39ce0f7f77f8db5644dcda366bfe4734bd95f435ff9a613aa54aa41c2c694c04").unwrap();
+        assert_eq!(&pk.rho[..], &rho[..]);
+        assert_eq!(&sk.rho[..], &rho[..]);
+        assert_eq!(&sk.key[..], &key[..]);
+
+        let pk_bytes = pk.to_bytes();
+        assert_eq!(
+            hex::encode(&pk_bytes[32..48]),
+            "4fc9946d42f19b79a7413bbaa33e7149"
+        );
+        let mut digest = [0u8; 32];
+        shake256(&pk_bytes, &mut digest);
+        assert_eq!(
+            hex::encode(&digest),
+            "329a07b1fabb48f52a309f11a1898f848e2322ffe623ec810db3bee33685854a"
+        );
+        shake256(&sk.to_bytes(), &mut digest);
+        assert_eq!(
+            hex::encode(&digest),
+            "eaa4bd0a7064e1aef9623bfa4c0209a98884f08558bbe934daefb16a049ba28a"
+        );
+    }
+
+    // ML-DSA-65 / ML-DSA-87 parameters, only for the c_tilde length check
+    struct Level3;
+    impl crate::dilithium_params::DilithiumParams for Level3 {
+        const K: usize = 6;
+        const L: usize = 5;
+        const ETA: i32 = 4;
+        const TAU: usize = 49;
+        const BETA: i32 = 196;
+        const GAMMA1: i32 = 1 << 19;
+        const GAMMA2: i32 = (crate::dilithium_params::Q - 1) / 32;
+        const OMEGA: usize = 55;
+        const CTILDE_BYTES: usize = 48;
+        const POLYETA_PACKEDBYTES: usize = 128;
+        const POLYZ_PACKEDBYTES: usize = 640;
+        const POLYW1_PACKEDBYTES: usize = 128;
+    }
+
+    struct Level5;
+    impl crate::dilithium_params::DilithiumParams for Level5 {
+        const K: usize = 8;
+        const L: usize = 7;
+        const ETA: i32 = 2;
+        const TAU: usize = 60;
+        const BETA: i32 = 120;
+        const GAMMA1: i32 = 1 << 19;
+        const GAMMA2: i32 = (crate::dilithium_params::Q - 1) / 32;
+        const OMEGA: usize = 75;
+        const CTILDE_BYTES: usize = 64;
+        const POLYETA_PACKEDBYTES: usize = 96;
+        const POLYZ_PACKEDBYTES: usize = 640;
+        const POLYW1_PACKEDBYTES: usize = 128;
+    }
+
+    fn check_c_tilde_len<P: crate::dilithium_params::DilithiumParams>(expected_len: usize) {
+        use crate::dilithium::c_tilde_matches;
+        use crate::shake::shake256;
+
+        assert_eq!(P::CTILDE_BYTES, expected_len);
+        let h_in = b"mu || w1 bytes";
+        let mut full = [0u8; 64];
+        shake256(h_in, &mut full);
+
+        let good = &full[..expected_len];
+        assert!(c_tilde_matches::<P>(h_in, good));
+
+        // right length, wrong value (last byte is the one a short compare would miss)
+        let mut bad = good.to_vec();
+        *bad.last_mut().unwrap() ^= 0x80;
+        assert!(!c_tilde_matches::<P>(h_in, &bad));
+
+        // correct bytes, wrong count
+        assert!(!c_tilde_matches::<P>(h_in, &full[..expected_len - 1]));
+        if expected_len < 64 {
+            assert!(!c_tilde_matches::<P>(h_in, &full[..expected_len + 1]));
+        }
+        if expected_len > 32 {
+            assert!(!c_tilde_matches::<P>(h_in, &full[..32]));
+        }
+    }
+
+    #[test]
+    fn test_c_tilde_length_per_level() {
+        use crate::dilithium_params::Dilithium2;
+
+        check_c_tilde_len::<Dilithium2>(32);
+        check_c_tilde_len::<Level3>(48);
+        check_c_tilde_len::<Level5>(64);
+
+        let (pk, sk) = keygen([12u8; 32]);
+        let mut sig = sign(&sk, b"lambda/4");
+        assert!(verify(&pk, b"lambda/4", &sig));
+        sig.c_tilde[31] ^= 0x80;
+        assert!(!verify(&pk, b"lambda/4", &sig));
+    }
+
+    #[test]
+    fn test_deterministic_sign_is_repeatable() {
+        let (_pk, sk) = keygen([0x61u8; 32]);
+        let msg = b"same message twice";
+        let sig = sign(&sk, msg);
+        assert_eq!(sign(&sk, msg), sig);
+
+        // and inequality is seen in each component
+        let mut other = sig.clone();
+        other.c_tilde[0] ^= 1;
+        assert_ne!(other, sig);
+        let mut other = sig.clone();
+        other.z.v[1].coeffs[5] += 1;
+        assert_ne!(other, sig);
+        let mut other = sig.clone();
+        other.h.v[0].coeffs[0] ^= 1;
+        assert_ne!(other, sig);
+        assert_ne!(sign(&sk, b"another message"), sig);
+    }
+
+    #[test]
+    fn test_sign_output_pinned() {
+        use crate::dilithium_pack::{pack_hints, pack_poly_z};
+        use crate::dilithium_params::{Dilithium2, K};
+        use crate::shake::shake256;
+        use crate::util::hex;
+
+        // Digest of c_tilde || z || h taken before the mat-vec products were
+        // routed through pointwise_acc; any change to A*y, A*z or t = A*s1 + s2
+        // shows up here.
+        let (pk, sk) = keygen([0x2Bu8; 32]);
+        let msg = b"pinned signature";
+        let sig = sign(&sk, msg);
+        assert!(verify(&pk, msg, &sig));
+
+        let mut enc = sig.c_tilde.to_vec();
+        for p in sig.z.v.iter() {
+            enc.extend_from_slice(&pack_poly_z::<Dilithium2>(p));
+        }
+        enc.extend_from_slice(&pack_hints::<Dilithium2, K>(&sig.h));
+        enc.extend_from_slice(&pk.to_bytes());
+        let mut digest = [0u8; 32];
+        shake256(&enc, &mut digest);
+        assert_eq!(hex::encode(&digest), "f0cea435fe8d5eb2074e5ad5848f8c67f5d0f96a249103dd5dae5d8349b05ca9");
+    }
+
+    #[test]
+    fn test_hint_bitmap_roundtrips_through_packed() {
+        use crate::dilithium::Signature;
+        use crate::dilithium_pack::{pack_hint_bitmap, pack_hints, unpack_hint_bitmap};
+        use crate::dilithium_params::{Dilithium2, K, N, OMEGA};
+
+        let (pk, sk) = keygen([0x4Eu8; 32]);
+        let msg = b"bitmap hints";
+        let sig = sign(&sk, msg);
+
+        let bitmap = sig.hint_bitmap();
+        let packed = pack_hint_bitmap::<Dilithium2, K>(&bitmap).unwrap();
+        assert_eq!(packed, pack_hints::<Dilithium2, K>(&sig.h));
+        assert_eq!(unpack_hint_bitmap::<Dilithium2, K>(&packed).unwrap(), bitmap);
+
+        let rebuilt = Signature::from_bitmap(sig.c_tilde, sig.z, &bitmap);
+        assert_eq!(rebuilt, sig);
+        assert!(verify(&pk, msg, &rebuilt));
+
+        // synthetic bitmap using the full OMEGA budget, spread over every row
+        let mut full = [[false; N]; K];
+        for n in 0..OMEGA {
+            full[n % K][(n * 37) % N] = true;
+        }
+        let packed = pack_hint_bitmap::<Dilithium2, K>(&full).unwrap();
+        assert_eq!(unpack_hint_bitmap::<Dilithium2, K>(&packed).unwrap(), full);
+
+        full[0][1] = true; // OMEGA + 1 bits cannot be encoded
+        assert_eq!(pack_hint_bitmap::<Dilithium2, K>(&full), None);
+    }
+
+    #[test]
+    fn test_challenge_keeps_squeezing_past_first_block() {
+        use crate::dilithium_params::TAU;
+        use crate::dilithium_sample::{challenge, sample_in_ball};
+        use crate::xof_stream::ShakeStream;
+
+        // First SHAKE256 block (136 bytes): 8 sign bytes, then only 0xFF,
+        // which is above every i < N - 1, so no position is placed from it.
+        let mut first = vec![0xA5u8; 8];
+        first.extend([0xFFu8; 128]);
+        let mut tail = ShakeStream::shake256(b"tail");
+        let mut read = 0usize;
+        let c = sample_in_ball(|| {
+            read += 1;
+            match first.get(read - 1) {
+                Some(&b) => b,
+                None => tail.next_u8(),
+            }
+        });
+        assert!(read > 136);
+        assert_eq!(c.coeffs.iter().filter(|&&x| x != 0).count(), TAU);
+        assert!(c.coeffs.iter().all(|&x| (-1..=1).contains(&x)));
+
+        for seed in [[0u8; 32], [0xFF; 32], [9u8; 32]] {
+            let c = challenge(&seed);
+            assert_eq!(c.coeffs.iter().filter(|&&x| x != 0).count(), TAU);
+        }
+    }
+
+    #[test]
+    fn test_sign_rng_replays_tape() {
+        use crate::dilithium::{sign_randomized, sign_rng};
+        use crate::util::rand::RandTape;
+
+        let (pk, sk) = keygen([0x7Au8; 32]);
+        let msg = b"hedged";
+        let fixed: [u8; 32] = core::array::from_fn(|i| (i as u8).wrapping_mul(29) ^ 0x5C);
+
+        let sig = sign_rng(&sk, msg, RandTape::new(&fixed));
+        assert_eq!(sig, sign_randomized(&sk, msg, &fixed));
+        assert!(verify(&pk, msg, &sig));
+
+        // rnd actually feeds rho'
+        assert_ne!(sig, sign(&sk, msg));
+        assert_ne!(sig, sign_randomized(&sk, msg, &[0u8; 32]));
+
+        // a tape longer than 32 bytes is consumed 32 bytes per signature
+        let two: Vec<u8> = fixed.iter().chain([0u8; 32].iter()).copied().collect();
+        let mut tape = RandTape::new(&two);
+        assert_eq!(sign_rng(&sk, msg, &mut tape), sig);
+        assert_eq!(tape.remaining(), 32);
+        assert_eq!(sign_rng(&sk, msg, &mut tape), sign_randomized(&sk, msg, &[0u8; 32]));
+    }
+
+    #[test]
+    fn test_dilithium_montgomery_instance_matches_reference() {
+        use crate::dilithium_ntt::{fqmul, montgomery_reduce, Coeff, Wide};
+        use crate::dilithium_params::{Q, QINV};
+        use rhdl::prelude::*;
+
+        // The hand-written reduction DilithiumMont replaced.
+        fn montgomery_reduce_ref(a: Wide) -> Coeff {
+            let t: Coeff = (a.resize::<U32>() * signed::<U32>(QINV as i128)).resize::<U32>();
+            let r: Wide = (a - t.resize::<U64>() * signed::<U64>(Q as i128)) >> 32;
+            r.resize::<U32>()
+        }
+
+        let q = Q as i128;
+        let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut samples: Vec<i128> = vec![0, 1, -1, q, -q, (q << 31) - 1, -(q << 31)];
+        for _ in 0..20_000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            // products of two coefficients below q stay under q^2 < 2^46
+            samples.push(((x >> 18) as i64 - (1i64 << 45)) as i128);
+        }
+        for a in samples {
+            let a = signed::<U64>(a);
+            assert_eq!(montgomery_reduce(a), montgomery_reduce_ref(a), "a={:?}", a);
+        }
+        for (a, b) in [(1, 1), (Q - 1, Q - 1), (-(Q - 1), Q - 1), (1753, 4_193_792), (-12345, 67890)] {
+            let (a, b) = (signed::<U32>(a as i128), signed::<U32>(b as i128));
+            let prod = a.resize::<U64>() * b.resize::<U64>();
+            assert_eq!(fqmul(a, b), montgomery_reduce_ref(prod));
+        }
+    }
+
+    #[test]
+    fn test_challenge_ntt_matches_two_step() {
+        use crate::dilithium_sample::{challenge, challenge_ntt};
+
+        let (_pk, sk) = keygen([0x5Bu8; 32]);
+        let sig = sign(&sk, b"challenge ntt");
+        for c_tilde in [sig.c_tilde, [0u8; 32], [0xA7u8; 32]] {
+            let mut expect = challenge(&c_tilde);
+            expect.ntt();
+            assert_eq!(challenge_ntt(&c_tilde), expect);
+
+            // and back: INTT(NTT(c)) is c times the Montgomery factor 2^32
+            let mut back = challenge_ntt(&c_tilde);
+            back.intt();
+            let c = challenge(&c_tilde);
+            let r = (1i64 << 32) % crate::dilithium_params::Q as i64;
+            let q = crate::dilithium_params::Q as i64;
+            for (x, &y) in back.coeffs.iter().zip(c.coeffs.iter()) {
+                assert_eq!((*x as i64).rem_euclid(q), (y as i64 * r).rem_euclid(q));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sign_low_mem_matches_sign() {
+        use crate::dilithium::sign_low_mem;
+
+        let (pk, sk) = keygen([0x2Du8; 32]);
+        for msg in [&b""[..], b"streamed matrix rows"] {
+            let sig = sign(&sk, msg);
+            let low = sign_low_mem(&sk, msg);
+            assert_eq!(low, sig);
+            assert!(verify(&pk, msg, &low));
+        }
+    }
+
+    #[test]
+    fn test_direct_ntt_matches_fsm_exactly() {
+        use crate::dilithium_ntt::{intt, intt_direct, ntt, ntt_direct};
+        use crate::dilithium_params::{N, Q};
+
+        let mut x = 0x0BAD_F00Du32;
+        for _ in 0..8 {
+            let a: [i32; N] = core::array::from_fn(|_| {
+                x = x.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (x % Q as u32) as i32 - Q / 2
+            });
+
+            let (mut fsm, mut direct) = (a, a);
+            ntt(&mut fsm);
+            ntt_direct(&mut direct);
+            assert_eq!(direct, fsm);
+
+            let (mut fsm, mut direct) = (a, a);
+            intt(&mut fsm);
+            intt_direct(&mut direct);
+            assert_eq!(direct, fsm);
+        }
+    }
+
+    #[test]
+    fn test_ntt_len1_stage_has_no_read_after_write_hazard() {
+        use crate::dilithium_ntt::{ntt, ntt_step, NttIn, NttState, Phase};
+        use crate::dilithium_params::{N, Q, ZETAS};
+        use rhdl::prelude::*;
+
+        // Same 1-cycle BRAM model as the driver: reads issued in Read are
+        // latched and served next cycle, before that cycle's writes land.
+        // Per address we count writes; a butterfly of stage s must see every
+        // address written exactly s times, i.e. never a stale value.
+        let a: [i32; N] = core::array::from_fn(|i| ((i as i64 * 1_234_567 + 89) % Q as i64) as i32);
+        let mut mem = a;
+        let mut writes = [0u32; N];
+        let mut st = NttState::default();
+        let mut pending: Option<(usize, usize)> = None;
+        let (mut stage, mut len, mut len1_butterflies) = (0u32, 0u64, 0usize);
+
+        for cycle in 0..20_000 {
+            let (rdata_a, rdata_b) = match pending {
+                Some((x, y)) => (mem[x], mem[y]),
+                None => (0, 0),
+            };
+            let inp = NttIn {
+                start: cycle == 0,
+                inverse: false,
+                rdata_a: signed::<U32>(rdata_a as i128),
+                rdata_b: signed::<U32>(rdata_b as i128),
+            };
+            if st.phase == Phase::Write {
+                if st.len.raw() as u64 != len {
+                    if len != 0 {
+                        stage += 1;
+                    }
+                    len = st.len.raw() as u64;
+                }
+                let (x, y) = pending.expect("Write without a pending read");
+                assert_eq!(y, x + len as usize, "stage len {}", len);
+                assert_eq!((writes[x], writes[y]), (stage, stage), "stale read at {}/{} (len {})", x, y, len);
+                if len == 1 {
+                    len1_butterflies += 1;
+                }
+            }
+
+            let (ns, out) = ntt_step(st, inp);
+            st = ns;
+            for port in [out.porta, out.portb] {
+                if port.we {
+                    let addr = port.addr.raw() as usize;
+                    mem[addr] = port.wdata.raw() as i32;
+                    writes[addr] += 1;
+                }
+            }
+            pending = (!out.porta.we).then(|| (out.porta.addr.raw() as usize, out.portb.addr.raw() as usize));
+            if out.done {
+                break;
+            }
+        }
+        assert_eq!(len1_butterflies, N / 2);
+        assert!(writes.iter().all(|&w| w == 8));
+
+        // and the stepped result is what the real driver and a plain-arithmetic NTT give
+        let mut via_driver = a;
+        ntt(&mut via_driver);
+        assert_eq!(mem, via_driver);
+
+        let r_inv = {
+            // 2^-32 mod q
+            let mut x = 1i64;
+            for _ in 0..32 {
+                x = if x % 2 == 0 { x / 2 } else { (x + Q as i64) / 2 };
+            }
+            x
+        };
+        let mut plain: Vec<i64> = a.iter().map(|&c| c as i64).collect();
+        let (mut k, mut len) = (0usize, 128usize);
+        while len > 0 {
+            for start in (0..N).step_by(2 * len) {
+                k += 1;
+                let zeta = ZETAS[k] as i64 * r_inv % Q as i64;
+                for j in start..start + len {
+                    let t = zeta * plain[j + len] % Q as i64;
+                    plain[j + len] = (plain[j] - t).rem_euclid(Q as i64);
+                    plain[j] = (plain[j] + t).rem_euclid(Q as i64);
+                }
+            }
+            len >>= 1;
+        }
+        for i in 0..N {
+            assert_eq!((mem[i] as i64).rem_euclid(Q as i64), plain[i], "coeff {}", i);
+        }
+    }
+
+    #[test]
+    fn test_intt_final_scaling_covers_every_coefficient() {
+        use crate::dilithium_ntt::{intt, montgomery_reduce, ntt_step, NttIn, NttState, Phase};
+        use crate::dilithium_params::{F, N, Q};
+        use rhdl::prelude::*;
+
+        // Same 1-cycle BRAM model as above. The butterfly stages leave `mem`
+        // untouched once the FSM enters the final phase, so a snapshot taken
+        // there is the unscaled input; every address must then be written
+        // exactly once with montgomery_reduce(F * snapshot[idx]).
+        let a: [i32; N] = core::array::from_fn(|i| ((i as i64 * 7_654_321 + 3) % Q as i64) as i32);
+        let mut mem = a;
+        let mut st = NttState::default();
+        let mut pending: Option<(usize, usize)> = None;
+        let mut before_scaling: Option<[i32; N]> = None;
+        let mut scaled = [0u32; N];
+        let mut final_order = Vec::with_capacity(N);
+
+        for cycle in 0..20_000 {
+            let (rdata_a, rdata_b) = match pending {
+                Some((x, y)) => (mem[x], mem[y]),
+                None => (0, 0),
+            };
+            let inp = NttIn {
+                start: cycle == 0,
+                inverse: true,
+                rdata_a: signed::<U32>(rdata_a as i128),
+                rdata_b: signed::<U32>(rdata_b as i128),
+            };
+            if st.phase == Phase::FinalRead && before_scaling.is_none() {
+                before_scaling = Some(mem);
+            }
+            let in_final_write = st.phase == Phase::FinalWrite;
+
+            let (ns, out) = ntt_step(st, inp);
+            st = ns;
+            for port in [out.porta, out.portb] {
+                if port.we {
+                    let addr = port.addr.raw() as usize;
+                    if in_final_write {
+                        let snap = before_scaling.expect("FinalWrite before FinalRead");
+                        let want = montgomery_reduce(signed::<U64>(F as i128 * snap[addr] as i128));
+                        assert_eq!(port.wdata, want, "coeff {} not scaled by F", addr);
+                        scaled[addr] += 1;
+                        final_order.push(addr);
+                    }
+                    mem[addr] = port.wdata.raw() as i32;
+                }
+            }
+            pending = (!out.porta.we).then(|| (out.porta.addr.raw() as usize, out.portb.addr.raw() as usize));
+            if out.done {
+                break;
+            }
+        }
+        assert!(before_scaling.is_some(), "FSM never reached the final phase");
+        let missed: Vec<usize> = (0..N).filter(|&i| scaled[i] != 1).collect();
+        assert!(missed.is_empty(), "final pass missed or repeated coefficients {:?}", missed);
+        assert_eq!(final_order, (0..N).collect::<Vec<_>>(), "final pass must walk idx 0..N in order");
+
+        let mut via_driver = a;
+        intt(&mut via_driver);
+        assert_eq!(mem, via_driver);
+    }
+
+    #[test]
+    fn test_expected_ntt_cycles_match_measured() {
+        use crate::dilithium_ntt::{
+            ntt_step, ntt_step_fused, poly_mul_pipeline, FinalScaling, NttIn, NttState,
+        };
+        use crate::ntt_control::expected_ntt_cycles;
+        use crate::dilithium_params::N;
+
+        let (_, cycles) = poly_mul_pipeline(&[1; N], &[2; N]);
+        assert_eq!(cycles.ntt_a, expected_ntt_cycles::<NttState>(N, false, FinalScaling::PerCoeff));
+        assert_eq!(cycles.intt, expected_ntt_cycles::<NttState>(N, true, FinalScaling::PerCoeff));
+
+        for (step, scaling) in [
+            (ntt_step as fn(_, _) -> _, FinalScaling::PerCoeff),
+            (ntt_step_fused, FinalScaling::Fused),
+        ] {
+            for inverse in [false, true] {
+                let mut st = NttState::default();
+                let mut measured = 0u64;
+                while measured < 20_000 {
+                    measured += 1;
+                    let (ns, out) = step(st, NttIn { start: measured == 1, inverse, ..Default::default() });
+                    st = ns;
+                    if out.done {
+                        break;
+                    }
+                }
+                assert_eq!(measured, expected_ntt_cycles::<NttState>(N, inverse, scaling), "inverse={} {:?}", inverse, scaling);
+            }
+        }
+    }
+
+    #[test]
+    fn test_poly_mul_pipeline_matches_mul_ntt() {
+        use crate::dilithium_ntt::poly_mul_pipeline;
+        use crate::dilithium_params::{N, Q};
+        use crate::dilithium_poly::Poly;
+
+        let mut a = Poly::default();
+        let mut b = Poly::default();
+        for i in 0..N {
+            a.coeffs[i] = ((i as i64 * 7919 + 13) % Q as i64) as i32;
+            b.coeffs[i] = (i as i32 % 9) - 4;
+        }
+
+        let (c, cycles) = poly_mul_pipeline(&a.coeffs, &b.coeffs);
+        assert_eq!(c, Poly::mul_ntt(&a, &b).coeffs);
+
+        // negacyclic schoolbook product
+        let mut expect = [0i64; N];
+        for i in 0..N {
+            for j in 0..N {
+                let p = a.coeffs[i] as i64 * b.coeffs[j] as i64;
+                if i + j < N { expect[i + j] += p } else { expect[i + j - N] -= p }
+            }
+        }
+        for i in 0..N {
+            assert_eq!((c[i] as i64).rem_euclid(Q as i64), expect[i].rem_euclid(Q as i64), "coeff {}", i);
+        }
+
+        // same per-core numbers as eval_perf_table (done cycle included)
+        assert_eq!((cycles.ntt_a, cycles.ntt_b), (2050, 2050));
+        assert_eq!(cycles.pointwise, N as u64 + 1);
+        assert_eq!(cycles.intt, 2562);
+        assert_eq!(cycles.total(), 6919);
+    }
+
+    fn check_gamma1_sampling<P: crate::dilithium_params::DilithiumParams>(bits: usize) {
+        use crate::dilithium_pack::{pack_poly_z, unpack_poly_z};
+        use crate::dilithium_sample::poly_uniform_gamma1_with;
+
+        assert_eq!(P::GAMMA1_BITS, bits);
+        assert_eq!(P::POLYZ_PACKEDBYTES, 256 * bits / 8);
+        let mut widest = 0;
+        for nonce in 0..8u16 {
+            let y = poly_uniform_gamma1_with::<P>(&[0x3Cu8; 64], nonce);
+            for &c in &y.coeffs {
+                assert!((-(P::GAMMA1 - 1)..=P::GAMMA1).contains(&c), "gamma1 = {}: coefficient {}", P::GAMMA1, c);
+                widest = widest.max(c.abs());
+            }
+            let packed = pack_poly_z::<P>(&y);
+            assert_eq!(packed.len(), P::POLYZ_PACKEDBYTES);
+            assert_eq!(unpack_poly_z::<P>(&packed), y);
+        }
+        // the top candidate bit is really used
+        assert!(widest >= P::GAMMA1 / 2, "gamma1 = {}: max |y| = {}", P::GAMMA1, widest);
+    }
+
+    #[test]
+    fn test_gamma1_sampling_per_level() {
+        use crate::dilithium_params::Dilithium2;
+
+        check_gamma1_sampling::<Dilithium2>(18);
+        check_gamma1_sampling::<Level3>(20);
+        check_gamma1_sampling::<Level5>(20);
+    }
+
+    #[test]
+    fn test_sign_recovers_from_forced_rejections() {
+        use crate::dilithium::sign_from_kappa;
+        use crate::dilithium_params::L;
+
+        let (pk, sk) = keygen([31u8; 32]);
+        let msg = b"forced rejection";
+        let first_attempt = |kappa: u16| {
+            let mut log = Vec::new();
+            sign_from_kappa(&sk, msg, kappa, &mut |info| log.push(info));
+            log[0]
+        };
+
+        // find two consecutive rejecting attempts to start from
+        let k0 = (0..200u16)
+            .map(|n| n * L as u16)
+            .find(|&k| first_attempt(k).rejected.is_some() && first_attempt(k + L as u16).rejected.is_some())
+            .expect("no rejecting kappa pair found");
+
+        let mut log = Vec::new();
+        let sig = sign_from_kappa(&sk, msg, k0, &mut |info| log.push(info));
+        assert!(log.len() >= 3, "expected at least two forced rejections");
+        assert!(log[0].rejected.is_some() && log[1].rejected.is_some());
+        for (n, info) in log.iter().enumerate() {
+            assert_eq!(info.kappa, k0 + (n * L) as u16);
+        }
+        let last = log.last().unwrap();
+        assert!(last.rejected.is_none());
+        assert!(verify(&pk, msg, &sig));
+
+        // starting directly at the accepting kappa gives the same signature at once
+        let mut again = Vec::new();
+        let sig2 = sign_from_kappa(&sk, msg, last.kappa, &mut |info| again.push(info));
+        assert_eq!(again.len(), 1);
+        assert_eq!(sig2.c_tilde, sig.c_tilde);
+        assert_eq!(sig2.z, sig.z);
+    }
+
+    #[test]
+    fn test_verify_ctx_binds_context() {
+        use crate::dilithium::{sign_ctx, sign_randomized, verify_ctx};
+
+        let (pk, sk) = keygen([0x2Cu8; 32]);
+        let msg = b"context separated";
+
+        let sig = sign_ctx(&sk, msg, b"app-A").unwrap();
+        assert!(verify_ctx(&pk, msg, &sig, b"app-A"));
+        assert!(!verify_ctx(&pk, msg, &sig, b"app-B"));
+        assert!(!verify_ctx(&pk, msg, &sig, b"app-"));
+        assert!(!verify_ctx(&pk, msg, &sig, b""));
+        assert!(!verify(&pk, msg, &sig));
+
+        // empty ctx still carries the 0 || 0 prefix, so it is its own domain
+        let empty = sign_ctx(&sk, msg, b"").unwrap();
+        assert!(verify_ctx(&pk, msg, &empty, b""));
+        assert!(!verify(&pk, msg, &empty));
+        assert!(!verify_ctx(&pk, msg, &sign(&sk, msg), b""));
+
+        // the length byte keeps ctx and msg from sliding into each other
+        let shifted = sign_ctx(&sk, b"text separated", b"app-Acon").unwrap();
+        assert!(!verify_ctx(&pk, msg, &shifted, b"app-A"));
+
+        // deterministic FIPS 204 signing: rnd = 0^32 over M', not `sign`'s H(key || mu)
+        let m_prime = [&[0u8, 5][..], b"app-A", msg].concat();
+        assert_eq!(sig, sign_randomized(&sk, &m_prime, &[0u8; 32]));
+        assert_ne!(sig, sign(&sk, &m_prime));
+
+        let long = [0u8; 256];
+        assert_eq!(sign_ctx(&sk, msg, &long), None);
+        assert!(sign_ctx(&sk, msg, &long[..255]).is_some());
+        assert!(!verify_ctx(&pk, msg, &sig, &long));
+    }
+
+    #[test]
+    fn test_sign_ctx_matches_fips204_deterministic_vector() {
+        use crate::dilithium::sign_ctx;
+        use crate::dilithium_pack::{pack_hints, pack_poly_z};
+        use crate::dilithium_params::{Dilithium2, K};
+        use crate::shake::shake256;
+        use crate::util::hex;
+
+        // OpenSSL 3.5.6, deterministic ML-DSA-44 with a context string:
+        //   openssl genpkey -algorithm ML-DSA-44 -pkeyopt hexseed:2c2c..2c
+        //   openssl pkeyutl -sign -rawin -pkeyopt deterministic:1 \
+        //       -pkeyopt context-string:app-A   (message "context separated")
+        // c_tilde prefix and SHAKE256 of the whole 2420-byte signature.
+        let (_, sk) = keygen([0x2Cu8; 32]);
+        let sig = sign_ctx(&sk, b"context separated", b"app-A").unwrap();
+
+        let mut enc = sig.c_tilde.to_vec();
+        for p in sig.z.v.iter() {
+            enc.extend_from_slice(&pack_poly_z::<Dilithium2>(p));
+        }
+        enc.extend_from_slice(&pack_hints::<Dilithium2, K>(&sig.h));
+        assert_eq!(hex::encode(&enc[..16]), "2bd38d3b9ff9430fd2f48f4025b9ce52");
+        let mut digest = [0u8; 32];
+        shake256(&enc, &mut digest);
+        assert_eq!(hex::encode(&digest), "913b6d11e0c5b5a23a1769c7b0975062164da632a13649b0a47c8c631d353bfc");
+    }
+
+    #[test]
+    fn test_coefficient_representation_per_stage() {
+        use crate::dilithium_params::{BETA, D, ETA, GAMMA1, K, N, Q};
+        use crate::dilithium_poly::Poly;
+        use crate::dilithium_sample::expand_a;
+
+        let in_range = |p: &Poly, lo: i32, hi: i32| p.coeffs.iter().all(|c| (lo..=hi).contains(c));
+
+        let (pk, sk) = keygen([0x77u8; 32]);
+        for p in pk.t1.v.iter() {
+            assert_eq!(*p, p.to_positive());
+            assert!(in_range(p, 0, (1 << 10) - 1));
+        }
+        for p in sk.s1.v.iter().chain(sk.s2.v.iter()) {
+            assert_eq!(*p, p.to_centered());
+            assert!(in_range(p, -ETA, ETA));
+        }
+        for p in sk.t0.v.iter() {
+            assert_eq!(*p, p.to_centered());
+            assert!(in_range(p, -(1 << (D - 1)) + 1, 1 << (D - 1)));
+        }
+
+        // t1*2^D + t0 == A*s1 + s2 once both are taken positive
+        let a_hat = expand_a(&sk.rho);
+        let mut s1_hat = sk.s1;
+        s1_hat.ntt();
+        for i in 0..K {
+            let mut t = a_hat.row_times_vec(i, &s1_hat);
+            t.intt();
+            t.add_assign(&sk.s2.v[i]);
+            let mut split = pk.t1.v[i];
+            split.shiftl(D);
+            split.add_assign(&sk.t0.v[i]);
+            assert_eq!(split.to_positive(), t.to_positive(), "t[{}]", i);
+        }
+
+        let sig = sign(&sk, b"representation");
+        assert_eq!(sig.z, sig.z.to_centered());
+        assert!(sig.z.v.iter().all(|p| in_range(p, -(GAMMA1 - BETA) + 1, GAMMA1 - BETA - 1)));
+        assert!(sig.h.v.iter().all(|p| in_range(p, 0, 1)));
+
+        // the conversions only move between representatives
+        let mixed = Poly { coeffs: core::array::from_fn(|j| (j as i32 - 128) * 65_537 + [0, Q, -Q, 2 * Q][j % 4]) };
+        let (c, p) = (mixed.to_centered(), mixed.to_positive());
+        assert!(in_range(&c, -(Q - 1) / 2, (Q - 1) / 2));
+        assert!(in_range(&p, 0, Q - 1));
+        assert_eq!(c.to_positive(), p);
+        assert_eq!(p.to_centered(), c);
+        assert!((0..N).all(|j| (c.coeffs[j] - mixed.coeffs[j]) % Q == 0));
+    }
+
+    #[test]
+    fn test_sign_bounded_gives_up() {
+        use crate::dilithium::{sign_bounded, sign_with_observer};
+
+        let (pk, sk) = keygen([0x1Du8; 32]);
+        // a message whose deterministic signing needs more than one attempt
+        let (msg, attempts) = (0u32..200)
+            .map(|n| {
+                let msg = n.to_le_bytes().to_vec();
+                let mut count = 0u32;
+                sign_with_observer(&sk, &msg, &mut |_| count += 1);
+                (msg, count)
+            })
+            .find(|&(_, count)| count >= 2)
+            .expect("no message with a rejected first attempt");
+
+        assert_eq!(sign_bounded(&sk, &msg, 0), None);
+        assert_eq!(sign_bounded(&sk, &msg, attempts - 1), None);
+        let sig = sign_bounded(&sk, &msg, attempts).unwrap();
+        assert_eq!(sig, sign(&sk, &msg));
+        assert!(verify(&pk, &msg, &sig));
+    }
+
+    #[test]
+    fn test_seed_expanders() {
+        use crate::dilithium::{keygen_with, Fips204, Legacy, SeedExpander};
+        use crate::util::hex;
+
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+        // FIPS 204: H(seed || 4 || 4, 128); rho matches the ML-DSA-44 vector above
+        let (rho, rho_prime, key) = Fips204::expand(&seed);
+        assert_eq!(hex::encode(&rho), "d7b2b47254aae0db45e7930d4a98d2c97d8f1397d1789dafa17024b316e9bec9");
+        assert_eq!(hex::encode(&rho_prime[..16]), "55b7500e191bd031221335a1f89134a5");
+        assert_eq!(hex::encode(&key), "39ce0f7f77f8db5644dcda366bfe4734bd95f435ff9a613aa54aa41c2c694c04");
+        let (pk, sk) = keygen(seed);
+        let (pk_f, sk_f) = keygen_with::<Fips204>(seed);
+        assert_eq!((pk.to_bytes(), sk.to_bytes()), (pk_f.to_bytes(), sk_f.to_bytes()));
+
+        // Legacy: z = H(seed, 96), key = H(z, 32)
+        let (rho, rho_prime, key) = Legacy::expand(&seed);
+        assert_eq!(hex::encode(&rho), "69f07c8840ce80024db30939882c3d5bbc9c98b3e31e4513ebd2ca9b4503cdd3");
+        assert_eq!(hex::encode(&rho_prime[..16]), "c9c90742452c7173d4a75ac49163e14e");
+        assert_eq!(hex::encode(&key), "e19254f9e3528d083b3abc34760ef7d4103246299897c84f1c81c693820606ca");
+
+        let (pk_l, sk_l) = keygen_with::<Legacy>(seed);
+        assert_eq!((pk_l.rho, sk_l.key), (rho, key));
+        assert_ne!(pk_l.to_bytes(), pk.to_bytes());
+        let sig = sign(&sk_l, b"legacy key");
+        assert!(verify(&pk_l, b"legacy key", &sig));
+        assert!(!verify(&pk, b"legacy key", &sig));
+    }
+
+    #[test]
+    fn test_sign_scratch_cleared() {
+        use crate::dilithium::{sign_with_scratch, SignScratch};
+
+        let (pk, sk) = keygen([3u8; 32]);
+        let msg = b"scratch";
+        let mut scratch = SignScratch::new();
+        let sig = sign_with_scratch(&sk, msg, &mut scratch);
+
+        assert!(verify(&pk, msg, &sig));
+        assert!(scratch.is_cleared(), "secret intermediates left in scratch after sign");
+
+        // the same scratch is reusable for the next signature
+        let sig2 = sign_with_scratch(&sk, msg, &mut scratch);
+        assert_eq!(sig.c_tilde, sig2.c_tilde);
+        assert!(scratch.is_cleared());
+    }
+
+    #[test]
+    fn test_sign_observer_called_per_attempt() {
+        use crate::dilithium::{sign_with_observer, Rejection};
+        use crate::dilithium_params::{BETA, GAMMA1, GAMMA2, L, OMEGA};
+
+        let (pk, sk) = keygen([8u8; 32]);
+        for msg in [&b"a"[..], b"observer", b"rejection loop"] {
+            let mut log = Vec::new();
+            let sig = sign_with_observer(&sk, msg, &mut |info| log.push(info));
+
+            assert!(verify(&pk, msg, &sig));
+            assert_eq!(sig.c_tilde, sign(&sk, msg).c_tilde);
+
+            let (last, rejected) = log.split_last().unwrap();
+            assert!(last.rejected.is_none());
+            assert!(last.z_norm < GAMMA1 - BETA);
+            assert!(last.r0_norm.unwrap() < GAMMA2 - BETA);
+            assert!(last.ct0_norm.unwrap() < GAMMA2);
+            assert!(last.hint_count.unwrap() <= OMEGA);
+
+            for (n, info) in log.iter().enumerate() {
+                assert_eq!(info.attempt as usize, n + 1);
+                assert_eq!(info.kappa as usize, n * L);
+            }
+            for info in rejected {
+                match info.rejected {
+                    Some(Rejection::ZNorm) => assert!(info.z_norm >= GAMMA1 - BETA),
+                    Some(Rejection::LowBits) => assert!(info.r0_norm.unwrap() >= GAMMA2 - BETA),
+                    Some(Rejection::Ct0) => assert!(info.ct0_norm.unwrap() >= GAMMA2),
+                    Some(Rejection::Hints) => assert!(info.hint_count.unwrap() > OMEGA),
+                    None => panic!("only the last attempt may be accepted"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fiat_shamir_c_tilde_recomputes() {
+        use crate::dilithium::compute_w_prime;
+        use crate::dilithium_pack::polyvec_w1_bytes;
+        use crate::dilithium_params::{Dilithium2, CTILDE_BYTES, GAMMA2, K, N};
+        use crate::dilithium_poly::{Poly, PolyVec};
+        use crate::dilithium_rounding::{high_bits, use_hint};
+        use crate::dilithium_sample::{challenge_ntt, expand_a};
+        use crate::shake::shake256;
+
+        let (pk, sk) = keygen([0x5Au8; 32]);
+        let a_hat = expand_a(&pk.rho);
+        for msg in [&b""[..], b"fiat-shamir", &[0xA5u8; 300][..]] {
+            let sig = sign(&sk, msg);
+
+            // verify side, without the norm / hint-count gates
+            let c_hat = challenge_ntt(&sig.c_tilde);
+            let w_prime = compute_w_prime(&a_hat, &c_hat, &sig.z, &pk.t1);
+            let mut w1 = PolyVec::<K>::default();
+            for i in 0..K {
+                for j in 0..N {
+                    w1.v[i].coeffs[j] = use_hint(w_prime.v[i].coeffs[j], sig.h.v[i].coeffs[j] as u8, GAMMA2);
+                }
+            }
+
+            let mut mu = [0u8; 64];
+            shake256(&[&pk.tr()[..], msg].concat(), &mut mu);
+            let mut c_tilde = [0u8; CTILDE_BYTES];
+            shake256(&[&mu[..], &polyvec_w1_bytes::<Dilithium2, K>(&w1)].concat(), &mut c_tilde);
+            assert_eq!(c_tilde, sig.c_tilde, "msg len {}", msg.len());
+
+            // and UseHint really recovered the signer's HighBits(w): w = w' + c*s2 - c*t0
+            let c_times = |v: &Poly| {
+                let mut v_hat = *v;
+                v_hat.ntt();
+                let mut p = Poly::pointwise_mul(&c_hat, &v_hat);
+                p.intt();
+                p
+            };
+            for i in 0..K {
+                let mut w = w_prime.v[i];
+                w.add_assign(&c_times(&sk.s2.v[i]));
+                w.sub_assign(&c_times(&sk.t0.v[i]));
+                w.reduce();
+                for j in 0..N {
+                    assert_eq!(high_bits(w.coeffs[j], GAMMA2), w1.v[i].coeffs[j], "poly {} coeff {}", i, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_w_prime_commitment() {
+        use crate::dilithium::compute_w_prime;
+        use crate::dilithium_poly::{Poly, PolyVec};
+        use crate::dilithium_params::{K, L};
+        use crate::dilithium_sample::{challenge, expand_a, expand_mask, mat_vec_mul_ntt};
+
+        let (pk, sk) = keygen([21u8; 32]);
+        let a_hat = expand_a(&sk.rho);
+
+        // any y and c work for the identity; rejection bounds are irrelevant here
+        let y = expand_mask(&[5u8; 64], 0);
+        let c = challenge(&[9u8; 32]);
+        let mut c_hat = c;
+        c_hat.ntt();
+
+        let c_times = |v: &Poly| {
+            let mut v_hat = *v;
+            v_hat.ntt();
+            let mut p = Poly::pointwise_mul(&c_hat, &v_hat);
+            p.intt();
+            p
+        };
+
+        // z = y + c*s1
+        let mut z = PolyVec::<L>::default();
+        for i in 0..L {
+            z.v[i] = y.v[i];
+            z.v[i].add_assign(&c_times(&sk.s1.v[i]));
+        }
+
+        // A*z - c*t1*2^d  ==  w - c*s2 + c*t0   (mod q), with w = A*y
+        let mut lhs = compute_w_prime(&a_hat, &c_hat, &z, &pk.t1);
+        let w = mat_vec_mul_ntt(&a_hat, &y);
+        for i in 0..K {
+            let mut rhs = w.v[i];
+            rhs.sub_assign(&c_times(&sk.s2.v[i]));
+            rhs.add_assign(&c_times(&sk.t0.v[i]));
+            rhs.reduce();
+            lhs.v[i].reduce();
+            assert_eq!(lhs.v[i], rhs, "w' mismatch in row {}", i);
+        }
+    }
 }
 
 #[cfg(test)]
 mod debug_math_tests {
     use crate::dilithium_poly::Poly;
     use crate::dilithium_params::Q;
+    use crate::dilithium_reduce::center_mod_q;
 
-    // Funcție locală de normalizare pentru test (copy-paste din dilithium.rs)
+    // Funcție locală de normalizare pentru test (ca normalize_poly din dilithium.rs)
     fn normalize_temp(p: &mut Poly) {
         for c in p.coeffs.iter_mut() {
-            let mut v = *c % Q;
-            if v < 0 { v += Q; }
-            if v > Q / 2 { v -= Q; }
-            *c = v;
+            *c = center_mod_q(*c);
         }
     }
 
@@ -93,26 +1120,101 @@ mod debug_math_tests {
         assert_eq!(p3.coeffs[1], 0, "NTT Multiplication produced artifacts");
     }
 
+    #[test]
+    fn test_ntt_zero_and_impulse() {
+        use crate::dilithium_ntt::{intt, ntt};
+        use crate::dilithium_params::N;
+
+        // 2^32 mod Q: intt is invntt_tomont
+        let mont = ((1i64 << 32) % Q as i64) as i32;
+        let modq = |x: i32| x.rem_euclid(Q);
+
+        let mut a = [0i32; N];
+        ntt(&mut a);
+        assert!(a.iter().all(|&c| modq(c) == 0));
+        intt(&mut a);
+        assert!(a.iter().all(|&c| modq(c) == 0));
+
+        // NTT of delta at position 0 is all ones (no scaling on the forward transform)
+        let mut a = [0i32; N];
+        a[0] = 1;
+        ntt(&mut a);
+        for (i, &c) in a.iter().enumerate() {
+            assert_eq!(modq(c), 1, "delta0 idx {}", i);
+        }
+
+        for pos in [0usize, 1, 127, 128, 255] {
+            let mut a = [0i32; N];
+            a[pos] = 1;
+            ntt(&mut a);
+            intt(&mut a);
+            for (i, &c) in a.iter().enumerate() {
+                assert_eq!(modq(c), if i == pos { mont } else { 0 }, "pos {} idx {}", pos, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_intt_fused_scaling_matches_per_coeff() {
+        use crate::dilithium_ntt::{intt, intt_with, FinalScaling};
+        use crate::dilithium_params::N;
+
+        let mut x = 0x9E37_79B9u32;
+        for _ in 0..4 {
+            let a: [i32; N] = core::array::from_fn(|_| {
+                x = x.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (x % Q as u32) as i32 - Q / 2
+            });
+
+            let mut per_coeff = a;
+            let mut fused = a;
+            intt(&mut per_coeff);
+            intt_with(&mut fused, FinalScaling::Fused);
+            for i in 0..N {
+                assert_eq!(fused[i], per_coeff[i], "idx {}", i);
+            }
+        }
+    }
+
     #[test]
     fn test_ntt_roundtrip_simple() {
+        use crate::dilithium_params::N;
+        use crate::dilithium_reduce::mont_fqmul;
+
+        // intt is invntt_tomont, so intt(ntt(a)) = a * 2^32; one Montgomery
+        // multiply by 1 takes the factor back off before comparing.
+        let roundtrip = |a: &Poly| {
+            let mut p = *a;
+            p.ntt();
+            p.intt();
+            let mut back = Poly { coeffs: p.coeffs.map(|c| mont_fqmul(c, 1)) };
+            back.reduce();
+            back
+        };
+
         let mut p1 = Poly::default();
         p1.coeffs[0] = 123;
         p1.coeffs[1] = -50;
-        
-        let _original = p1.clone();
-        
-        p1.ntt();
-        p1.intt();
-        normalize_temp(&mut p1);
-        
-        // Coeficientul 1 e negativ (-50), modulo Q e mare, normalizat e -50.
-        // Verificăm dacă revine la forma inițială.
-        assert_eq!(p1.coeffs[0], 123);
-        // Trebuie să gestionăm faptul că originalul nu e modulo Q, dar 123 e mic.
-        
-        // Verificăm consistența
-        if p1.coeffs[1] != -50 {
-             println!("NTT Roundtrip mismatch at idx 1. Got: {}, Expected: -50", p1.coeffs[1]);
+        let mut r = roundtrip(&p1);
+        normalize_temp(&mut r);
+        assert_eq!(r, p1);
+
+        let mut x = 0x2545_F491u32;
+        for round in 0..8 {
+            let a = Poly {
+                coeffs: core::array::from_fn(|_| {
+                    x = x.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    // alternate canonical [0, q) and centered inputs
+                    let c = (x % Q as u32) as i32;
+                    if round % 2 == 0 { c } else { c - Q / 2 }
+                }),
+            };
+            let mut want = a;
+            want.reduce();
+            let got = roundtrip(&a);
+            for i in 0..N {
+                assert_eq!(got.coeffs[i], want.coeffs[i], "round {} coeff {}", round, i);
+            }
         }
     }
-}
\ No newline at end of file
+}
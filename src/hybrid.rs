@@ -0,0 +1,156 @@
+// Kyber512 + Dilithium2 identity for signed key exchange.
+// Integration glue only: the KEM key is authenticated by a Dilithium
+// signature over a labelled copy of its public key bytes.
+
+use crate::dilithium::{self, Signature};
+use crate::dilithium_params::{PK_BYTES as DSA_PK_BYTES, SK_BYTES as DSA_SK_BYTES};
+use crate::kyber_kem::kem_keypair;
+use crate::kyber_params::{PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES};
+use crate::shake::shake256;
+
+/// Domain label prepended to the KEM public key before signing.
+const KEM_PK_LABEL: &[u8] = b"hybrid-kem-pk-v1";
+
+/// kem_pk || dsa_pk
+pub const HYBRID_PK_BYTES: usize = PUBLICKEYBYTES + DSA_PK_BYTES;
+/// kem_sk || dsa_sk
+pub const HYBRID_SK_BYTES: usize = SECRETKEYBYTES + DSA_SK_BYTES;
+
+/// Public half of a `HybridIdentity`, as held by a peer.
+#[derive(Clone, Debug)]
+pub struct HybridPublicKey {
+    pub kem_pk: [u8; PUBLICKEYBYTES],
+    pub dsa_pk: dilithium::PublicKey,
+}
+
+/// A Kyber keypair and a Dilithium keypair owned by the same party.
+#[derive(Clone, Debug)]
+pub struct HybridIdentity {
+    pub kem_pk: [u8; PUBLICKEYBYTES],
+    pub kem_sk: [u8; SECRETKEYBYTES],
+    pub dsa_pk: dilithium::PublicKey,
+    pub dsa_sk: dilithium::SecretKey,
+}
+
+fn kem_pk_message(kem_pk: &[u8; PUBLICKEYBYTES]) -> Vec<u8> {
+    let mut m = Vec::with_capacity(KEM_PK_LABEL.len() + PUBLICKEYBYTES);
+    m.extend_from_slice(KEM_PK_LABEL);
+    m.extend_from_slice(kem_pk);
+    m
+}
+
+impl HybridIdentity {
+    /// Both keypairs from one 32-byte seed, split as SHAKE256(seed) = kem_seed || dsa_seed.
+    pub fn from_seed(seed: &[u8; SYMBYTES]) -> Self {
+        let mut z = [0u8; 2 * SYMBYTES];
+        shake256(seed, &mut z);
+        let kem_seed: [u8; SYMBYTES] = z[..SYMBYTES].try_into().unwrap();
+        let dsa_seed: [u8; SYMBYTES] = z[SYMBYTES..].try_into().unwrap();
+
+        let (kem_pk, kem_sk) = kem_keypair(&kem_seed);
+        let (dsa_pk, dsa_sk) = dilithium::keygen(dsa_seed);
+        HybridIdentity { kem_pk, kem_sk, dsa_pk, dsa_sk }
+    }
+
+    pub fn public_key(&self) -> HybridPublicKey {
+        HybridPublicKey { kem_pk: self.kem_pk, dsa_pk: self.dsa_pk.clone() }
+    }
+
+    /// Signs a (typically fresh) Kyber public key with this identity's Dilithium key.
+    pub fn sign_kem_public_key(&self, kem_pk: &[u8; PUBLICKEYBYTES]) -> Signature {
+        dilithium::sign(&self.dsa_sk, &kem_pk_message(kem_pk))
+    }
+
+    /// kem_sk || dsa_sk
+    pub fn secret_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HYBRID_SK_BYTES);
+        out.extend_from_slice(&self.kem_sk);
+        out.extend_from_slice(&self.dsa_sk.to_bytes());
+        out
+    }
+
+    /// Rebuilds an identity from `HybridPublicKey::to_bytes` and `secret_bytes`.
+    pub fn from_bytes(public: &[u8], secret: &[u8]) -> Option<Self> {
+        if secret.len() != HYBRID_SK_BYTES {
+            return None;
+        }
+        let pk = HybridPublicKey::from_bytes(public)?;
+        let (kem_sk, dsa_sk) = secret.split_at(SECRETKEYBYTES);
+        let kem_sk: [u8; SECRETKEYBYTES] = kem_sk.try_into().unwrap();
+        let dsa_sk = dilithium::SecretKey::from_bytes(dsa_sk)?;
+        Some(HybridIdentity { kem_pk: pk.kem_pk, kem_sk, dsa_pk: pk.dsa_pk, dsa_sk })
+    }
+}
+
+impl HybridPublicKey {
+    /// kem_pk || dsa_pk
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HYBRID_PK_BYTES);
+        out.extend_from_slice(&self.kem_pk);
+        out.extend_from_slice(&self.dsa_pk.to_bytes());
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != HYBRID_PK_BYTES {
+            return None;
+        }
+        let (kem_pk, dsa_pk) = bytes.split_at(PUBLICKEYBYTES);
+        Some(HybridPublicKey {
+            kem_pk: kem_pk.try_into().unwrap(),
+            dsa_pk: dilithium::PublicKey::from_bytes(dsa_pk)?,
+        })
+    }
+
+    /// Checks a signature produced by `HybridIdentity::sign_kem_public_key`.
+    pub fn verify_kem_public_key(&self, kem_pk: &[u8; PUBLICKEYBYTES], sig: &Signature) -> bool {
+        dilithium::verify(&self.dsa_pk, &kem_pk_message(kem_pk), sig)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kyber_kem::{kem_decaps, kem_encaps};
+
+    #[test]
+    fn signed_fresh_kem_key_verifies() {
+        let alice = HybridIdentity::from_seed(&[1u8; SYMBYTES]);
+        let alice_pub = HybridPublicKey::from_bytes(&alice.public_key().to_bytes()).unwrap();
+
+        // ephemeral Kyber key for this session
+        let (eph_pk, eph_sk) = kem_keypair(&[2u8; SYMBYTES]);
+        let sig = alice.sign_kem_public_key(&eph_pk);
+        assert!(alice_pub.verify_kem_public_key(&eph_pk, &sig));
+
+        let mut tampered = eph_pk;
+        tampered[0] ^= 1;
+        assert!(!alice_pub.verify_kem_public_key(&tampered, &sig));
+
+        let bob = HybridIdentity::from_seed(&[3u8; SYMBYTES]);
+        assert!(!bob.public_key().verify_kem_public_key(&eph_pk, &sig));
+
+        // and the authenticated key is usable
+        let (ct, ss) = kem_encaps(&[4u8; SYMBYTES], &eph_pk);
+        assert_eq!(kem_decaps(&ct, &eph_sk), ss);
+    }
+
+    #[test]
+    fn identity_bytes_roundtrip() {
+        let id = HybridIdentity::from_seed(&[9u8; SYMBYTES]);
+        let pk_bytes = id.public_key().to_bytes();
+        let sk_bytes = id.secret_bytes();
+        assert_eq!(pk_bytes.len(), HYBRID_PK_BYTES);
+        assert_eq!(sk_bytes.len(), HYBRID_SK_BYTES);
+
+        let id2 = HybridIdentity::from_bytes(&pk_bytes, &sk_bytes).unwrap();
+        assert_eq!(id2.public_key().to_bytes(), pk_bytes);
+        assert_eq!(id2.secret_bytes(), sk_bytes);
+
+        let sig = id2.sign_kem_public_key(&id2.kem_pk);
+        assert!(id.public_key().verify_kem_public_key(&id.kem_pk, &sig));
+
+        assert!(HybridPublicKey::from_bytes(&pk_bytes[1..]).is_none());
+        assert!(HybridIdentity::from_bytes(&pk_bytes, &sk_bytes[1..]).is_none());
+    }
+}
@@ -1,5 +1,6 @@
 // src/keccak.rs
-// Keccak-f[1600] permutation (24 rounds)
+// Keccak-f[1600] permutation (24 rounds), plus Keccak-p[1600, n_r] for
+// reduced-round users (TurboSHAKE, 12 rounds).
 // Fix: rotația folosește rotate_left ca să nu facă shift cu 64.
 
 #![allow(dead_code)]
@@ -29,42 +30,57 @@ fn rol(x: u64, n: u32) -> u64 {
 }
 
 pub fn keccak_f1600(state: &mut [u64; 25]) {
-    for &rc in RC.iter() {
-        // theta
-        let mut c = [0u64; 5];
-        for x in 0..5 {
-            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
-        }
-        let mut d = [0u64; 5];
+    keccak_p1600(state, ROUNDS);
+}
+
+/// Keccak-p[1600, rounds]: the last `rounds` rounds of Keccak-f, i.e. round
+/// constants RC[24 - rounds..].
+pub fn keccak_p1600(state: &mut [u64; 25], rounds: usize) {
+    assert!(rounds <= ROUNDS, "keccak_p1600: at most 24 rounds");
+    for round in ROUNDS - rounds..ROUNDS {
+        keccak_round(state, round);
+    }
+}
+
+/// Round `round` (0..24) of Keccak-f[1600] on its own: theta, rho, pi, chi,
+/// iota with RC[round]. For per-round vectors (keccak_dump).
+pub fn keccak_round(state: &mut [u64; 25], round: usize) {
+    let rc = RC[round];
+
+    // theta
+    let mut c = [0u64; 5];
+    for x in 0..5 {
+        c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+    }
+    let mut d = [0u64; 5];
+    for x in 0..5 {
+        d[x] = c[(x + 4) % 5] ^ rol(c[(x + 1) % 5], 1);
+    }
+    for y in 0..5 {
         for x in 0..5 {
-            d[x] = c[(x + 4) % 5] ^ rol(c[(x + 1) % 5], 1);
-        }
-        for y in 0..5 {
-            for x in 0..5 {
-                state[x + 5 * y] ^= d[x];
-            }
+            state[x + 5 * y] ^= d[x];
         }
+    }
 
-        // rho + pi
-        let mut b = [0u64; 25];
-        for y in 0..5 {
-            for x in 0..5 {
-                let v = state[x + 5 * y];
-                let nx = y;
-                let ny = (2 * x + 3 * y) % 5;
-                b[nx + 5 * ny] = rol(v, ROT[x][y]);
-            }
+    // rho + pi
+    let mut b = [0u64; 25];
+    for y in 0..5 {
+        for x in 0..5 {
+            let v = state[x + 5 * y];
+            let nx = y;
+            let ny = (2 * x + 3 * y) % 5;
+            b[nx + 5 * ny] = rol(v, ROT[x][y]);
         }
+    }
 
-        // chi
-        for y in 0..5 {
-            for x in 0..5 {
-                state[x + 5 * y] =
-                    b[x + 5 * y] ^ ((!b[((x + 1) % 5) + 5 * y]) & b[((x + 2) % 5) + 5 * y]);
-            }
+    // chi
+    for y in 0..5 {
+        for x in 0..5 {
+            state[x + 5 * y] =
+                b[x + 5 * y] ^ ((!b[((x + 1) % 5) + 5 * y]) & b[((x + 2) % 5) + 5 * y]);
         }
-
-        // iota
-        state[0] ^= rc;
     }
+
+    // iota
+    state[0] ^= rc;
 }
@@ -0,0 +1,158 @@
+// Kyber512 + AES-256-GCM sealed box: encrypt a message to a KEM public key.
+// Integration glue only: each seal encapsulates afresh and the KEM's KDF
+// output is split into the GCM key and nonce. GCM itself is the RustCrypto
+// `aes-gcm` crate (96-bit nonce, 128-bit tag).
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::Aes256Gcm;
+
+use crate::kyber_kem::{kem_decaps_kdf, kem_encaps_kdf};
+use crate::kyber_params::{CIPHERTEXTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES};
+use crate::util::rand::RandomBytes;
+
+pub const AEAD_KEYBYTES: usize = 32;
+pub const AEAD_NONCEBYTES: usize = 12;
+pub const AEAD_TAGBYTES: usize = 16;
+
+/// Seals `plaintext` to `pk`. Returns the KEM ciphertext and
+/// `gcm_ciphertext || tag`. The encapsulation seed (32 bytes) is drawn from
+/// `rng`, so every call gets its own GCM key and nonce.
+pub fn kem_seal<R: RandomBytes>(
+    pk: &[u8; PUBLICKEYBYTES],
+    plaintext: &[u8],
+    mut rng: R,
+) -> ([u8; CIPHERTEXTBYTES], Vec<u8>) {
+    let mut seed_m = [0u8; SYMBYTES];
+    rng.randombytes(&mut seed_m);
+    kem_seal_seeded(&seed_m, pk, plaintext)
+}
+
+/// `kem_seal` with a fixed encapsulation seed. The GCM key and nonce are
+/// both derived from `seed_m`, so a repeated seed reuses the nonce; only
+/// for tests that need a reproducible output.
+pub(crate) fn kem_seal_seeded(
+    seed_m: &[u8; SYMBYTES],
+    pk: &[u8; PUBLICKEYBYTES],
+    plaintext: &[u8],
+) -> ([u8; CIPHERTEXTBYTES], Vec<u8>) {
+    let mut kn = [0u8; AEAD_KEYBYTES + AEAD_NONCEBYTES];
+    let ct = kem_encaps_kdf(seed_m, pk, &mut kn);
+    let (key, nonce) = split_key_nonce(&kn);
+    (ct, aes256_gcm_seal(&key, &nonce, &[], plaintext))
+}
+
+/// Opens a `kem_seal` output. None if `ct` or `sealed` was modified (a
+/// tampered `ct` decapsulates to the implicit-rejection key, so the tag
+/// check fails) or `sealed` is shorter than a tag.
+pub fn kem_open(
+    sk: &[u8; SECRETKEYBYTES],
+    ct: &[u8; CIPHERTEXTBYTES],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    let mut kn = [0u8; AEAD_KEYBYTES + AEAD_NONCEBYTES];
+    kem_decaps_kdf(ct, sk, &mut kn);
+    let (key, nonce) = split_key_nonce(&kn);
+    aes256_gcm_open(&key, &nonce, &[], sealed)
+}
+
+fn split_key_nonce(kn: &[u8; AEAD_KEYBYTES + AEAD_NONCEBYTES]) -> ([u8; AEAD_KEYBYTES], [u8; AEAD_NONCEBYTES]) {
+    (kn[..AEAD_KEYBYTES].try_into().unwrap(), kn[AEAD_KEYBYTES..].try_into().unwrap())
+}
+
+// -----------------------------------------------------------------------------
+// AES-256-GCM
+// -----------------------------------------------------------------------------
+
+/// `ciphertext || tag`.
+fn aes256_gcm_seal(key: &[u8; AEAD_KEYBYTES], nonce: &[u8; AEAD_NONCEBYTES], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let gcm = Aes256Gcm::new_from_slice(key).expect("32-byte key");
+    gcm.encrypt(GenericArray::from_slice(nonce), Payload { msg: plaintext, aad }).expect("GCM input length limit")
+}
+
+/// Inverse of `aes256_gcm_seal`; None on a tag mismatch or when `sealed` is
+/// shorter than a tag.
+fn aes256_gcm_open(key: &[u8; AEAD_KEYBYTES], nonce: &[u8; AEAD_NONCEBYTES], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    let gcm = Aes256Gcm::new_from_slice(key).expect("32-byte key");
+    gcm.decrypt(GenericArray::from_slice(nonce), Payload { msg: sealed, aad }).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kyber_kem::kem_keypair;
+    use crate::nist_drbg::NistDrbg;
+    use crate::util::hex;
+    use crate::util::rand::RandTape;
+
+    // McGrew & Viega, "The Galois/Counter Mode of Operation", test case 16
+    // (AES-256, 96-bit IV, 20-byte AAD, 60-byte plaintext).
+    #[test]
+    fn gcm_matches_published_vector() {
+        let key: [u8; 32] = hex::decode("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let nonce: [u8; 12] = hex::decode("cafebabefacedbaddecaf888").unwrap().try_into().unwrap();
+        let aad = hex::decode("feedfacedeadbeeffeedfacedeadbeefabaddad2").unwrap();
+        let pt = hex::decode(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        )
+        .unwrap();
+
+        let sealed = aes256_gcm_seal(&key, &nonce, &aad, &pt);
+        assert_eq!(
+            hex::encode(&sealed),
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+             8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662\
+             76fc6ece0f4e1768cddf8853bb2d551b"
+        );
+        assert_eq!(aes256_gcm_open(&key, &nonce, &aad, &sealed), Some(pt));
+        assert_eq!(aes256_gcm_open(&key, &nonce, &aad[1..], &sealed), None);
+
+        // empty message: the tag alone
+        assert_eq!(hex::encode(&aes256_gcm_seal(&[0u8; 32], &[0u8; 12], &[], &[])), "530f8afbc74536b9a963b4f1c4cb738b");
+    }
+
+    #[test]
+    fn seal_open_roundtrip_and_tamper() {
+        let (pk, sk) = kem_keypair(&[5u8; SYMBYTES]);
+        let msg = b"sealed to a Kyber512 public key, more than one GCM block long";
+
+        let (ct, sealed) = kem_seal(&pk, msg, RandTape::new(&[6u8; SYMBYTES]));
+        assert_eq!((ct, sealed.clone()), kem_seal_seeded(&[6u8; SYMBYTES], &pk, msg));
+        assert_eq!(sealed.len(), msg.len() + AEAD_TAGBYTES);
+        assert_ne!(&sealed[..msg.len()], &msg[..]);
+        assert_eq!(kem_open(&sk, &ct, &sealed).as_deref(), Some(&msg[..]));
+
+        // every byte of the sealed body and tag is authenticated
+        for i in [0, msg.len() - 1, msg.len(), sealed.len() - 1] {
+            let mut bad = sealed.clone();
+            bad[i] ^= 0x01;
+            assert_eq!(kem_open(&sk, &ct, &bad), None, "sealed byte {}", i);
+        }
+
+        // a modified KEM ciphertext gives the implicit-rejection key
+        let mut bad_ct = ct;
+        bad_ct[0] ^= 0x01;
+        assert_eq!(kem_open(&sk, &bad_ct, &sealed), None);
+
+        // the wrong recipient cannot open it either
+        let (_, other_sk) = kem_keypair(&[7u8; SYMBYTES]);
+        assert_eq!(kem_open(&other_sk, &ct, &sealed), None);
+
+        assert_eq!(kem_open(&sk, &ct, &sealed[..AEAD_TAGBYTES - 1]), None);
+        let mut drbg = NistDrbg::new(&[8u8; 48]);
+        let (ct0, empty) = kem_seal(&pk, &[], &mut drbg);
+        assert_eq!(kem_open(&sk, &ct0, &empty), Some(Vec::new()));
+
+        // fresh randomness per call: the same message seals differently
+        let (ct1, sealed1) = kem_seal(&pk, msg, &mut drbg);
+        let (ct2, sealed2) = kem_seal(&pk, msg, &mut drbg);
+        assert_ne!(ct1, ct2);
+        assert_ne!(sealed1, sealed2);
+        assert_eq!(kem_open(&sk, &ct1, &sealed1).as_deref(), Some(&msg[..]));
+        assert_eq!(kem_open(&sk, &ct2, &sealed2).as_deref(), Some(&msg[..]));
+    }
+}
@@ -1,5 +1,6 @@
 use rhdl::prelude::*;
 use crate::kyber_params::{KYBER_Q, Q, QINV};
+use crate::montgomery::Montgomery;
 
 pub type Coeff = SignedBits<U16>;
 pub type Wide = SignedBits<U32>;
@@ -52,6 +53,17 @@ pub fn freeze(a: Coeff) -> Coeff {
     csubq(r1)
 }
 
+/// Kyber's `Montgomery` parameters: 16-bit coefficients, R = 2^16.
+pub struct KyberMont;
+
+impl Montgomery for KyberMont {
+    type N = U16;
+    type W = U32;
+    const Q: i128 = KYBER_Q as i128;
+    const QINV: i128 = QINV as i128;
+    const WIDTH: i32 = 16;
+}
+
 /// Montgomery reduction (Kyber reference):
 /// int16_t montgomery_reduce(int32_t a) {
 ///   int16_t t = (int16_t)a * QINV;
@@ -60,21 +72,13 @@ pub fn freeze(a: Coeff) -> Coeff {
 /// }
 #[inline(always)]
 pub fn montgomery_reduce(a: Wide) -> Coeff {
-    // Cast BEFORE multiply, exactly like (int16_t)a.
-    let a16: Coeff = a.resize::<U16>();
-
-    // t = (int16)a * QINV, then truncated to 16 bits
-    let t: Coeff = (a16.resize::<U32>() * s16(QINV).resize::<U32>()).resize::<U16>();
-
-    // (a - (int32)t*q) >> 16
-    let r: Wide = (a - t.resize::<U32>() * s32(KYBER_Q as i64)) >> 16;
-    r.resize::<U16>()
+    KyberMont::montgomery_reduce(a)
 }
 
 /// fqmul(a,b) = montgomery_reduce(a*b)
 #[inline(always)]
 pub fn fqmul(a: Coeff, b: Coeff) -> Coeff {
-    montgomery_reduce(a.resize::<U32>() * b.resize::<U32>())
+    KyberMont::fqmul(a, b)
 }
 
 /// add modulo q with a single conditional subtract
@@ -104,3 +108,56 @@ pub fn tomont(a: Coeff) -> Coeff {
 pub fn frommont(a: Coeff) -> Coeff {
     montgomery_reduce(a.resize::<U32>())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // montgomery_reduce subtracts t*q with t = a*QINV (mod 2^16), so the
+    // low half cancels only if q*QINV == +1 (mod 2^16).
+    #[test]
+    fn qinv_is_inverse_of_q_mod_2_16() {
+        assert_eq!((KYBER_Q * QINV).rem_euclid(1 << 16), 1);
+        assert_eq!(Q as i32, KYBER_Q);
+    }
+
+    #[test]
+    fn montgomery_reduce_divides_by_r() {
+        let bound = (KYBER_Q as i64) << 15;
+        for a in [0i64, 1, -1, KYBER_Q as i64, 1_234_567, -7_654_321, bound - 1, -bound] {
+            let r = montgomery_reduce(s32(a)).raw() as i64;
+            assert!(r.abs() < KYBER_Q as i64, "a={}", a);
+            assert_eq!((r * (1i64 << 16) - a).rem_euclid(KYBER_Q as i64), 0, "a={}", a);
+        }
+    }
+
+    // The hand-written reduction KyberMont replaced.
+    fn montgomery_reduce_ref(a: Wide) -> Coeff {
+        let a16: Coeff = a.resize::<U16>();
+        let t: Coeff = (a16.resize::<U32>() * s16(QINV).resize::<U32>()).resize::<U16>();
+        let r: Wide = (a - t.resize::<U32>() * s32(KYBER_Q as i64)) >> 16;
+        r.resize::<U16>()
+    }
+
+    #[test]
+    fn kyber_montgomery_instance_matches_reference() {
+        let q = KYBER_Q as i64;
+        let mut x: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut samples: Vec<i64> = vec![0, 1, -1, q, -q, q * 32767, -q * 32768, i32::MAX as i64, i32::MIN as i64];
+        for _ in 0..20_000 {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            samples.push((x as u32) as i32 as i64);
+        }
+        for a in samples {
+            assert_eq!(montgomery_reduce(s32(a)), montgomery_reduce_ref(s32(a)), "a={}", a);
+        }
+        for a in (-3328..=3328).step_by(7) {
+            for b in [-3328, -1, 0, 1, 17, 1353, 2285, 3328] {
+                let expect = montgomery_reduce_ref(s32(a as i64 * b as i64));
+                assert_eq!(fqmul(s16(a), s16(b)), expect, "a={} b={}", a, b);
+            }
+        }
+    }
+}
@@ -5,14 +5,17 @@
 
 use rhdl::prelude::*;
 
-use crate::kyber_arith::{freeze, Coeff};
+use crate::kyber_arith::Coeff;
+use crate::kyber_poly::poly_canonicalize;
+use crate::util::bytes::load_le64;
 use crate::kyber_params::{
-    CIPHERTEXTBYTES, DU, DV, K, KYBER_N, KYBER_Q, POLYBYTES, POLYCOMPRESSEDBYTES_DU10,
+    CIPHERTEXTBYTES, DU, DV, INDCPA_MSGBYTES, K, KYBER_N, KYBER_Q, POLYBYTES, POLYCOMPRESSEDBYTES_DU10,
     POLYCOMPRESSEDBYTES_DV4, POLYVECBYTES, PUBLICKEYBYTES,
 };
 
-pub type Poly = [Coeff; KYBER_N];
-pub type PolyVec = [Poly; K];
+// One definition for all Kyber modules; re-exported so `kyber_codec::Poly`
+// keeps working.
+pub use crate::kyber_poly::{Poly, PolyVec};
 
 #[inline(always)]
 fn c16(x: i16) -> Coeff {
@@ -24,10 +27,19 @@ fn coeff_to_i16(x: Coeff) -> i16 {
     x.raw() as i16
 }
 
+/// Copy of `p` with every coefficient in [0, q).
+#[inline(always)]
+fn canonical(p: &Poly) -> Poly {
+    let mut c = *p;
+    poly_canonicalize(&mut c);
+    c
+}
+
 #[inline(always)]
 fn coeff_to_u16_canon(x: Coeff) -> u16 {
-    // freeze => [0, q)
-    coeff_to_i16(freeze(x)) as u16
+    let v = coeff_to_i16(x);
+    debug_assert!((0..KYBER_Q as i16).contains(&v), "coefficient {} not canonical", v);
+    v as u16
 }
 
 #[inline(always)]
@@ -48,10 +60,11 @@ fn decompress_coeff(t: u16, d: usize) -> Coeff {
 }
 
 // -----------------------------------------------------------------------------
-// 12-bit poly encode/decode (polys are assumed reduced, freeze applied internally)
+// 12-bit poly encode/decode (input canonicalized internally)
 // -----------------------------------------------------------------------------
 
 pub fn poly_encode12(p: &Poly) -> [u8; POLYBYTES] {
+    let p = canonical(p);
     let mut out = [0u8; POLYBYTES];
     for i in 0..(KYBER_N / 2) {
         let t0 = coeff_to_u16_canon(p[2 * i]);
@@ -74,6 +87,64 @@ pub fn poly_decode12(b: &[u8; POLYBYTES]) -> Poly {
     p
 }
 
+/// Implementation behind `poly_encode12_with` / `poly_decode12_with`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Pack12Backend {
+    /// Two coefficients / three bytes per step (`poly_encode12`).
+    #[default]
+    Scalar,
+    /// Four coefficients / six bytes per step through one u64 lane.
+    Swar,
+}
+
+/// `kyber_arith::freeze` (Barrett, then one conditional add/subtract) on
+/// native integers, so the wide path does not pay for SignedBits arithmetic.
+#[inline(always)]
+fn freeze_native(x: Coeff) -> u16 {
+    let a = coeff_to_i16(x) as i32;
+    let q = KYBER_Q;
+    let mut r = a - ((20159 * a + (1 << 25)) >> 26) * q;
+    r += (r >> 31) & q;
+    r -= q;
+    r += (r >> 31) & q;
+    r as u16
+}
+
+/// `poly_encode12` with the packing loop picked at runtime. Same bytes.
+pub fn poly_encode12_with(p: &Poly, backend: Pack12Backend) -> [u8; POLYBYTES] {
+    match backend {
+        Pack12Backend::Scalar => poly_encode12(p),
+        Pack12Backend::Swar => {
+            let mut out = [0u8; POLYBYTES];
+            for (o, c) in out.chunks_exact_mut(6).zip(p.chunks_exact(4)) {
+                let w = c
+                    .iter()
+                    .enumerate()
+                    .fold(0u64, |w, (k, &x)| w | (freeze_native(x) as u64) << (12 * k));
+                o.copy_from_slice(&w.to_le_bytes()[..6]);
+            }
+            out
+        }
+    }
+}
+
+/// `poly_decode12` with the unpacking loop picked at runtime. Same output.
+pub fn poly_decode12_with(b: &[u8; POLYBYTES], backend: Pack12Backend) -> Poly {
+    match backend {
+        Pack12Backend::Scalar => poly_decode12(b),
+        Pack12Backend::Swar => {
+            let mut p = [c16(0); KYBER_N];
+            for (c, i) in p.chunks_exact_mut(4).zip(b.chunks_exact(6)) {
+                let w = load_le64::<6>(i);
+                for (k, x) in c.iter_mut().enumerate() {
+                    *x = c16(((w >> (12 * k)) & 0x0fff) as i16);
+                }
+            }
+            p
+        }
+    }
+}
+
 pub fn polyvec_encode12(v: &PolyVec) -> [u8; POLYVECBYTES] {
     let mut out = [0u8; POLYVECBYTES];
     for i in 0..K {
@@ -97,9 +168,9 @@ pub fn polyvec_decode12(b: &[u8; POLYVECBYTES]) -> PolyVec {
 // msg <-> poly (Kyber message mapping)
 // -----------------------------------------------------------------------------
 
-pub fn poly_frommsg(m: &[u8; 32]) -> Poly {
+pub fn poly_frommsg(m: &[u8; INDCPA_MSGBYTES]) -> Poly {
     let mut p = [c16(0); KYBER_N];
-    for i in 0..32 {
+    for i in 0..INDCPA_MSGBYTES {
         for j in 0..8 {
             let bit = (m[i] >> j) & 1;
             p[8 * i + j] = if bit == 0 {
@@ -112,9 +183,10 @@ pub fn poly_frommsg(m: &[u8; 32]) -> Poly {
     p
 }
 
-pub fn poly_tomsg(p: &Poly) -> [u8; 32] {
-    let mut m = [0u8; 32];
-    for i in 0..32 {
+pub fn poly_tomsg(p: &Poly) -> [u8; INDCPA_MSGBYTES] {
+    let p = canonical(p);
+    let mut m = [0u8; INDCPA_MSGBYTES];
+    for i in 0..INDCPA_MSGBYTES {
         let mut b = 0u8;
         for j in 0..8 {
             let t = compress_coeff(p[8 * i + j], 1);
@@ -131,6 +203,7 @@ pub fn poly_tomsg(p: &Poly) -> [u8; 32] {
 
 pub fn poly_compress_du10(p: &Poly) -> [u8; POLYCOMPRESSEDBYTES_DU10] {
     debug_assert_eq!(DU, 10);
+    let p = canonical(p);
     let mut out = [0u8; POLYCOMPRESSEDBYTES_DU10];
     for i in 0..(KYBER_N / 4) {
         let t0 = compress_coeff(p[4 * i + 0], 10);
@@ -166,6 +239,7 @@ pub fn poly_decompress_du10(b: &[u8; POLYCOMPRESSEDBYTES_DU10]) -> Poly {
 
 pub fn poly_compress_dv4(p: &Poly) -> [u8; POLYCOMPRESSEDBYTES_DV4] {
     debug_assert_eq!(DV, 4);
+    let p = canonical(p);
     let mut out = [0u8; POLYCOMPRESSEDBYTES_DV4];
     for i in 0..(KYBER_N / 2) {
         let t0 = compress_coeff(p[2 * i], 4);
@@ -231,3 +305,150 @@ pub fn ct_decode(ct: &[u8; CIPHERTEXTBYTES]) -> (PolyVec, Poly) {
     let v = poly_decompress_dv4(&vchunk);
     (u, v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack_test_polys() -> Vec<Poly> {
+        let mut x: u32 = 0xC0DE_5EED;
+        let mut next = || {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            x >> 8
+        };
+        let mut polys = vec![[c16(0); KYBER_N], [c16(KYBER_Q as i16 - 1); KYBER_N]];
+        for _ in 0..64 {
+            // anything in the i16 range a caller may hand in, canonical or not
+            polys.push(core::array::from_fn(|_| c16((next() % 6658) as i16 - 3329)));
+        }
+        polys
+    }
+
+    #[test]
+    fn compression_is_bounded_idempotent_and_monotonic() {
+        let q = KYBER_Q;
+        // Kyber512 (du=10, dv=4) and the Kyber1024 widths (du=11, dv=5)
+        for d in [4usize, 5, 10, 11] {
+            // |decompress(compress(x)) - x| mod q, centred, is at most round(q / 2^(d+1))
+            let bound = (q + (1 << d)) >> (d + 1);
+            let mut prev = 0u16;
+            for x in 0..q {
+                let t = compress_coeff(c16(x as i16), d);
+                assert!(t < (1 << d), "d={} x={}", d, x);
+                let y = decompress_coeff(t, d).raw() as i32;
+                let err = (y - x).rem_euclid(q);
+                let err = err.min(q - err);
+                assert!(err <= bound, "d={} x={} t={} y={} err={}", d, x, t, y, err);
+
+                // non-decreasing until the top values wrap round to 0
+                if t != 0 || x < q / 2 {
+                    assert!(t >= prev, "d={} x={}", d, x);
+                    prev = t;
+                }
+            }
+            for t in 0..(1u16 << d) {
+                let y = decompress_coeff(t, d);
+                assert!((0..q).contains(&(y.raw() as i32)));
+                assert_eq!(compress_coeff(y, d), t, "d={} t={}", d, t);
+            }
+        }
+    }
+
+    #[test]
+    fn codec_and_poly_share_one_poly_type() {
+        use crate::kyber_poly;
+
+        // identity coercions only compile if the aliases name the same type
+        let to_codec: fn(kyber_poly::Poly) -> Poly = |p| p;
+        let to_poly_vec: fn(PolyVec) -> kyber_poly::PolyVec = |v| v;
+
+        let mut p: kyber_poly::Poly = core::array::from_fn(|i| c16((i as i16 * 13) % KYBER_Q as i16));
+        kyber_poly::poly_ntt(&mut p);
+        kyber_poly::poly_reduce(&mut p);
+        let decoded = poly_decode12(&poly_encode12(&to_codec(p)));
+        kyber_poly::poly_canonicalize(&mut p);
+        assert_eq!(decoded, p);
+
+        let v = to_poly_vec(polyvec_decode12(&polyvec_encode12(&[decoded, p])));
+        let mut acc = [c16(0); KYBER_N];
+        kyber_poly::polyvec_pointwise_acc(&mut acc, &v, &v);
+
+        // both entries are p, so the accumulation is 2 * basemul(p, p)
+        let mut single = [c16(0); KYBER_N];
+        kyber_poly::poly_basemul_montgomery(&mut single, &p, &p);
+        for (a, s) in acc.iter().zip(single.iter()) {
+            assert_eq!((a.raw() - 2 * s.raw()).rem_euclid(KYBER_Q as i128), 0);
+        }
+    }
+
+    #[test]
+    fn swar_pack12_matches_scalar() {
+        for v in i16::MIN..=i16::MAX {
+            let x = c16(v);
+            assert_eq!(freeze_native(x), crate::kyber_arith::freeze(x).raw() as u16, "{}", v);
+        }
+        for p in pack_test_polys() {
+            let bytes = poly_encode12_with(&p, Pack12Backend::Swar);
+            assert_eq!(bytes, poly_encode12(&p));
+            assert_eq!(poly_decode12_with(&bytes, Pack12Backend::Swar), poly_decode12(&bytes));
+        }
+        // decode of arbitrary bytes, including 12-bit values >= q
+        for seed in 0..16u8 {
+            let b: [u8; POLYBYTES] = core::array::from_fn(|i| (i as u8).wrapping_mul(seed | 1).wrapping_add(seed));
+            assert_eq!(poly_decode12_with(&b, Pack12Backend::Swar), poly_decode12(&b));
+        }
+    }
+
+    // cargo test --release --lib pack12_backend_timing -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn pack12_backend_timing() {
+        use std::time::Instant;
+
+        let polys = pack_test_polys();
+        let rounds = 2000;
+        for backend in [Pack12Backend::Scalar, Pack12Backend::Swar] {
+            let t = Instant::now();
+            let mut sink = 0u8;
+            for _ in 0..rounds {
+                for p in &polys {
+                    let b = poly_encode12_with(core::hint::black_box(p), backend);
+                    sink ^= poly_decode12_with(&b, backend)[7].raw() as u8;
+                }
+            }
+            let per = t.elapsed() / (rounds * polys.len() as u32);
+            println!("{:?}: {:?} per encode+decode (sink {})", backend, per, sink);
+        }
+    }
+
+    fn roundtrip(m: &[u8; INDCPA_MSGBYTES]) {
+        assert_eq!(&poly_tomsg(&poly_frommsg(m)), m);
+    }
+
+    #[test]
+    fn msg_roundtrip_bit_exact() {
+        roundtrip(&[0u8; INDCPA_MSGBYTES]);
+        roundtrip(&[0xFFu8; INDCPA_MSGBYTES]);
+
+        // every single-bit message: bit i must land in coefficient i and nowhere else
+        for bit in 0..KYBER_N {
+            let mut m = [0u8; INDCPA_MSGBYTES];
+            m[bit / 8] = 1 << (bit % 8);
+            let p = poly_frommsg(&m);
+            for (i, c) in p.iter().enumerate() {
+                let expect = if i == bit { (KYBER_Q + 1) / 2 } else { 0 };
+                assert_eq!(c.raw() as i32, expect, "bit {} coeff {}", bit, i);
+            }
+            roundtrip(&m);
+        }
+
+        // every byte value at every position
+        for pos in 0..INDCPA_MSGBYTES {
+            for v in 0..=255u8 {
+                let mut m: [u8; INDCPA_MSGBYTES] = core::array::from_fn(|i| (i as u8).wrapping_mul(37));
+                m[pos] = v;
+                roundtrip(&m);
+            }
+        }
+    }
+}
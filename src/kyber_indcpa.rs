@@ -5,31 +5,90 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
-use crate::kyber_codec::{ct_decode, ct_encode, pk_decode, pk_encode, poly_frommsg, poly_tomsg, polyvec_decode12, polyvec_encode12};
-use crate::kyber_params::{CIPHERTEXTBYTES, INDCPA_SECRETKEYBYTES, K, POLYVECBYTES, PUBLICKEYBYTES, SYMBYTES, ETA1, ETA2};
-use crate::kyber_poly::{poly_invntt, poly_ntt, poly_reduce, polyvec_ntt, polyvec_pointwise_acc, Poly, PolyVec};
-use crate::kyber_sampling::gen_matrix;
-use crate::kyber_sampling::poly_getnoise;
+use crate::kyber_codec::{ct_decode, ct_encode, pk_decode, pk_encode, poly_decode12, poly_frommsg, poly_tomsg, polyvec_encode12};
+use crate::kyber_params::{CIPHERTEXTBYTES, INDCPA_MSGBYTES, INDCPA_SECRETKEYBYTES, K, KYBER_Q, N, POLYBYTES, PUBLICKEYBYTES, SYMBYTES, ETA1, ETA2};
+use crate::kyber_poly::{poly_add, poly_frommont, poly_invntt, poly_ntt, poly_reduce, poly_sub, poly_tomont, polyvec_ntt, polyvec_pointwise_acc, Poly, PolyVec};
+use crate::kyber_sampling::{poly_getnoise, MatrixA, PolyMat};
 use crate::shake::{sha3_256, sha3_512};
+use crate::xof_stream::{rej_uniform_generic, ShakeStream};
 
 #[inline(always)]
 fn zero_poly() -> Poly {
     [rhdl::prelude::signed::<rhdl::prelude::U16>(0); crate::kyber_params::N]
 }
 
+// -----------------------------------------------------------------------------
+// Software reference sampler (no FSM), used to cross-check kyber_sampling.rs
+// -----------------------------------------------------------------------------
+
+/// Uniform rejection sampling from SHAKE128(rho || x || y), as in the Kyber ref `rej_uniform`.
+pub fn rej_uniform(r: &mut Poly, rho: &[u8; SYMBYTES], x: u8, y: u8) {
+    let mut inbuf = [0u8; SYMBYTES + 2];
+    inbuf[..SYMBYTES].copy_from_slice(rho);
+    inbuf[SYMBYTES] = x;
+    inbuf[SYMBYTES + 1] = y;
+    let mut stream = ShakeStream::shake128(&inbuf);
+
+    let mut coeffs = [0u32; N];
+    rej_uniform_generic::<12, { KYBER_Q as u32 }>(&mut stream, &mut coeffs);
+    for (c, &v) in r.iter_mut().zip(coeffs.iter()) {
+        *c = rhdl::prelude::signed::<rhdl::prelude::U16>(v as i128);
+    }
+}
+
+/// Reference matrix generation: A[i][j] from XOF(rho, j, i), A^T[i][j] from XOF(rho, i, j).
+/// Same output domain as `kyber_sampling::gen_matrix` (NTT domain, times R).
+pub fn gen_matrix(rho: &[u8; SYMBYTES], transposed: bool) -> PolyMat {
+    let mut a = [[zero_poly(); K]; K];
+    for (i, row) in a.iter_mut().enumerate() {
+        for (j, p) in row.iter_mut().enumerate() {
+            let (x, y) = if transposed { (i as u8, j as u8) } else { (j as u8, i as u8) };
+            rej_uniform(p, rho, x, y);
+            poly_tomont(p);
+        }
+    }
+    a
+}
+
+/// How keygen expands d into (rho || sigma).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KeygenDomain {
+    /// Kyber Round 3: G(d).
+    #[default]
+    Round3,
+    /// FIPS 203 ML-KEM: G(d || k), the module rank appended as one byte.
+    MlKem,
+}
+
 /// Deterministic CPAPKE keypair from seed d (32 bytes): returns (pk, sk_indcpa)
 pub fn indcpa_keypair_deterministic(
     d: &[u8; SYMBYTES],
 ) -> ([u8; PUBLICKEYBYTES], [u8; INDCPA_SECRETKEYBYTES]) {
-    // (rho || sigma) = G(d) where G = SHA3-512
-    let g = sha3_512(d);
+    indcpa_keypair_deterministic_with(d, KeygenDomain::Round3)
+}
+
+/// `indcpa_keypair_deterministic` with an explicit (rho, sigma) derivation.
+pub fn indcpa_keypair_deterministic_with(
+    d: &[u8; SYMBYTES],
+    domain: KeygenDomain,
+) -> ([u8; PUBLICKEYBYTES], [u8; INDCPA_SECRETKEYBYTES]) {
+    // (rho || sigma) = G(d) or G(d || k), where G = SHA3-512
+    let g = match domain {
+        KeygenDomain::Round3 => sha3_512(d),
+        KeygenDomain::MlKem => {
+            let mut dk = [0u8; SYMBYTES + 1];
+            dk[..SYMBYTES].copy_from_slice(d);
+            dk[SYMBYTES] = K as u8;
+            sha3_512(&dk)
+        }
+    };
     let mut rho = [0u8; SYMBYTES];
     let mut sigma = [0u8; SYMBYTES];
     rho.copy_from_slice(&g[..SYMBYTES]);
     sigma.copy_from_slice(&g[SYMBYTES..]);
 
     // Generate A_hat (NTT domain)
-    let A_hat = gen_matrix(&rho, false);
+    let A_hat = MatrixA::from_rho(&rho);
 
     // Sample s and e (standard domain), then NTT
     let mut s: PolyVec = [zero_poly(); K];
@@ -52,16 +111,19 @@ pub fn indcpa_keypair_deterministic(
     let mut t: PolyVec = [zero_poly(); K];
     for i in 0..K {
         // dot product of row i with s
-        let row: PolyVec = A_hat[i];
-        let mut acc = zero_poly();
-        polyvec_pointwise_acc(&mut acc, &row, &s);
+        let acc = A_hat.row_times_vec(i, &s);
 
         // add noise
-        for j in 0..crate::kyber_params::N {
-            acc[j] = acc[j] + e[i][j];
-        }
-        poly_reduce(&mut acc);
-        t[i] = acc;
+        poly_add(&mut t[i], &acc, &e[i]);
+        poly_reduce(&mut t[i]);
+    }
+
+    // t_hat and s_hat are R-scaled here (poly_ntt output); serialize them in
+    // the reference NTT domain so keys are KAT-compatible. s stays in NTT
+    // domain in sk, so decryption never transforms it.
+    for i in 0..K {
+        poly_frommont(&mut t[i]);
+        poly_frommont(&mut s[i]);
     }
 
     let pk = pk_encode(&t, &rho);
@@ -72,13 +134,16 @@ pub fn indcpa_keypair_deterministic(
 /// CPAPKE encryption: ct = Enc(pk, m, coins)
 pub fn indcpa_enc(
     pk: &[u8; PUBLICKEYBYTES],
-    m: &[u8; SYMBYTES],
+    m: &[u8; INDCPA_MSGBYTES],
     coins: &[u8; SYMBYTES],
 ) -> [u8; CIPHERTEXTBYTES] {
-    let (t_hat, rho) = pk_decode(pk);
+    let (mut t_hat, rho) = pk_decode(pk);
+    for p in t_hat.iter_mut() {
+        poly_tomont(p);
+    }
 
-    // A_hat^T
-    let A_hat_t = gen_matrix(&rho, true);
+    // A_hat, read transposed below
+    let A_hat = MatrixA::from_rho(&rho);
 
     // Sample r, e1, e2 in standard domain
     let mut n: u8 = 0;
@@ -101,15 +166,10 @@ pub fn indcpa_enc(
     // u = InvNTT(A_hat^T * r) + e1
     let mut u: PolyVec = [zero_poly(); K];
     for i in 0..K {
-        let row: PolyVec = A_hat_t[i];
-        let mut acc = zero_poly();
-        polyvec_pointwise_acc(&mut acc, &row, &r);
+        let mut acc = A_hat.transposed_row_times_vec(i, &r);
         poly_invntt(&mut acc);
 
-        for j in 0..crate::kyber_params::N {
-            acc[j] = acc[j] + e1[i][j];
-        }
-        u[i] = acc;
+        poly_add(&mut u[i], &acc, &e1[i]);
     }
 
     // v = InvNTT(t_hat^T * r) + e2 + m_poly
@@ -117,13 +177,10 @@ pub fn indcpa_enc(
     polyvec_pointwise_acc(&mut v, &t_hat, &r);
     poly_invntt(&mut v);
 
-    for j in 0..crate::kyber_params::N {
-        v[j] = v[j] + e2[j];
-    }
     let mpoly = poly_frommsg(m);
-    for j in 0..crate::kyber_params::N {
-        v[j] = v[j] + mpoly[j];
-    }
+    let mut ve = zero_poly();
+    poly_add(&mut ve, &v, &e2);
+    poly_add(&mut v, &ve, &mpoly);
 
     ct_encode(&u, &v)
 }
@@ -132,13 +189,10 @@ pub fn indcpa_enc(
 pub fn indcpa_dec(
     sk: &[u8; INDCPA_SECRETKEYBYTES],
     ct: &[u8; CIPHERTEXTBYTES],
-) -> [u8; SYMBYTES] {
+) -> [u8; INDCPA_MSGBYTES] {
     let (u, v) = ct_decode(ct);
 
-    // Decode s_hat
-    let mut skbytes = [0u8; POLYVECBYTES];
-    skbytes.copy_from_slice(sk);
-    let s_hat = polyvec_decode12(&skbytes);
+    let s_hat = decode_to_ntt(sk, SecretKeyForm::Ntt);
 
     // NTT(u)
     let mut u_hat = u;
@@ -152,15 +206,214 @@ pub fn indcpa_dec(
     poly_invntt(&mut mp);
 
     // v - mp
-    let mut w = v;
-    for j in 0..crate::kyber_params::N {
-        w[j] = w[j] - mp[j];
-    }
+    let mut w = zero_poly();
+    poly_sub(&mut w, &v, &mp);
 
     poly_tomsg(&w)
 }
 
+/// Domain of the polynomials packed in an IND-CPA secret key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SecretKeyForm {
+    /// NTT(s), as written by keygen (reference / FIPS 203 layout).
+    Ntt,
+    /// s itself, 12-bit packed with canonical coefficients.
+    Normal,
+}
+
+/// Decodes the packed secret vector straight into the operand `indcpa_dec`
+/// feeds to `polyvec_pointwise_acc` (NTT domain, times R). One polynomial at
+/// a time: 384 bytes in, one transformed polynomial out, so a streaming
+/// datapath never holds the whole decoded vector in normal domain.
+pub fn decode_to_ntt(sk: &[u8; INDCPA_SECRETKEYBYTES], form: SecretKeyForm) -> PolyVec {
+    let mut s_hat = [zero_poly(); K];
+    for (p, bytes) in s_hat.iter_mut().zip(sk.chunks_exact(POLYBYTES)) {
+        *p = poly_decode12(bytes.try_into().unwrap());
+        match form {
+            // stored canonically; only the R scaling has to be restored
+            SecretKeyForm::Ntt => poly_tomont(p),
+            // poly_ntt output is already R-scaled
+            SecretKeyForm::Normal => poly_ntt(p),
+        }
+    }
+    s_hat
+}
+
 /// Convenience: hash public key (Kyber uses H=SHA3-256)
 pub fn hash_pk(pk: &[u8; PUBLICKEYBYTES]) -> [u8; SYMBYTES] {
     sha3_256(pk)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kyber_codec::polyvec_decode12;
+    use crate::kyber_params::POLYVECBYTES;
+
+    // Byte layout of the reference poly_tobytes on canonical coefficients.
+    fn ref_tobytes(coeffs: &[u16]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(coeffs.len() * 3 / 2);
+        for pair in coeffs.chunks(2) {
+            let (t0, t1) = (pair[0], pair[1]);
+            out.push(t0 as u8);
+            out.push(((t0 >> 8) as u8) | ((t1 << 4) as u8));
+            out.push((t1 >> 4) as u8);
+        }
+        out
+    }
+
+    #[test]
+    fn keypair_pk_matches_codec_layout() {
+        for seed in [0u8, 0x5A, 0xFF] {
+            let (pk, sk) = indcpa_keypair_deterministic(&[seed; SYMBYTES]);
+
+            let (t, rho) = pk_decode(&pk);
+            assert_eq!(pk_encode(&t, &rho), pk);
+            assert_eq!(pk[..POLYVECBYTES], polyvec_encode12(&t)[..]);
+
+            let coeffs: Vec<u16> = t.iter().flatten().map(|c| c.raw() as u16).collect();
+            assert!(coeffs.iter().all(|&c| (c as i32) < KYBER_Q));
+            assert_eq!(ref_tobytes(&coeffs), pk[..POLYVECBYTES]);
+
+            let mut skbytes = [0u8; POLYVECBYTES];
+            skbytes.copy_from_slice(&sk);
+            assert_eq!(polyvec_encode12(&polyvec_decode12(&skbytes)), sk);
+        }
+    }
+
+    #[test]
+    fn dec_uses_stored_s_hat_as_is() {
+        // Decryption only succeeds if the packed sk is already the NTT of s:
+        // indcpa_dec multiplies it with NTT(u) without transforming it first.
+        for seed in [0x11u8, 0x7C, 0xE3] {
+            let (pk, sk) = indcpa_keypair_deterministic(&[seed; SYMBYTES]);
+            for fill in [0x00u8, 0x5A, 0xFF] {
+                let m: [u8; SYMBYTES] = core::array::from_fn(|i| fill ^ (i as u8).wrapping_mul(seed));
+                let ct = indcpa_enc(&pk, &m, &[seed ^ fill; SYMBYTES]);
+                assert_eq!(indcpa_dec(&sk, &ct), m, "seed {:#x} fill {:#x}", seed, fill);
+            }
+        }
+    }
+
+    #[test]
+    fn stored_secret_key_is_ntt_domain_s() {
+        // Plain FIPS 203 NTT (zeta = 17, bit-reversed twiddles), no Montgomery.
+        fn plain_ntt(f: &mut [i64; N]) {
+            let q = KYBER_Q as i64;
+            let zeta = |k: usize| {
+                let e = (k as u8).reverse_bits() >> 1;
+                (0..e).fold(1i64, |acc, _| acc * 17 % q)
+            };
+            let (mut k, mut len) = (1usize, 128usize);
+            while len >= 2 {
+                for start in (0..N).step_by(2 * len) {
+                    let z = zeta(k);
+                    k += 1;
+                    for j in start..start + len {
+                        let t = z * f[j + len] % q;
+                        f[j + len] = (f[j] - t).rem_euclid(q);
+                        f[j] = (f[j] + t).rem_euclid(q);
+                    }
+                }
+                len >>= 1;
+            }
+        }
+
+        for seed in [0x01u8, 0x9C] {
+            let d = [seed; SYMBYTES];
+            let (_pk, sk) = indcpa_keypair_deterministic(&d);
+            let mut skbytes = [0u8; POLYVECBYTES];
+            skbytes.copy_from_slice(&sk);
+            let s_hat = polyvec_decode12(&skbytes);
+
+            let sigma: [u8; SYMBYTES] = sha3_512(&d)[SYMBYTES..].try_into().unwrap();
+            for (i, stored) in s_hat.iter().enumerate() {
+                let s_i = poly_getnoise(&sigma, i as u8, ETA1);
+                let mut expect: [i64; N] = core::array::from_fn(|j| s_i[j].raw() as i64);
+                plain_ntt(&mut expect);
+                for j in 0..N {
+                    // stored canonically, no R factor
+                    assert_eq!(stored[j].raw() as i64, expect[j], "s_hat[{}][{}]", i, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gen_matrix_matches_official_kat_keys() {
+        use crate::util::hex;
+
+        // Official Kyber512 KAT (PQCkemKAT_1632.rsp): pk = t_hat || rho and
+        // the sk starts with s_hat, both in the reference NTT domain, with
+        // t_hat = A_hat o s_hat + e_hat. Only the right A_hat leaves a residue
+        // t_hat - A_hat o s_hat whose inverse NTT is a CBD(ETA1) noise vector;
+        // any other matrix gives coefficients spread over all of Z_q.
+        let rsp = include_str!("kat/PQCkemKAT_1632.rsp");
+        for block in rsp.split("\n\n").filter(|b| b.contains("count = ")).take(4) {
+            let field = |name: &str| -> Vec<u8> {
+                let prefix = format!("{} = ", name);
+                let line = block.lines().find(|l| l.starts_with(&prefix)).unwrap();
+                hex::decode(&line[prefix.len()..]).unwrap()
+            };
+            let pk: [u8; PUBLICKEYBYTES] = field("pk").try_into().unwrap();
+            let sk: [u8; POLYVECBYTES] = field("sk")[..POLYVECBYTES].try_into().unwrap();
+
+            let (t_hat, rho) = pk_decode(&pk);
+            let mut s_hat = polyvec_decode12(&sk);
+            for p in s_hat.iter_mut() {
+                poly_tomont(p);
+            }
+            let a = gen_matrix(&rho, false);
+            let a_t = gen_matrix(&rho, true);
+            for i in 0..K {
+                // A_hat and s_hat both carry R, the basemul removes one
+                let mut acc = zero_poly();
+                polyvec_pointwise_acc(&mut acc, &a[i], &s_hat);
+                poly_frommont(&mut acc);
+                let mut e = zero_poly();
+                poly_sub(&mut e, &t_hat[i], &acc);
+                // poly_invntt leaves a factor R^-1 on its output
+                poly_invntt(&mut e);
+                poly_tomont(&mut e);
+                for (j, c) in e.iter().enumerate() {
+                    let c = (c.raw() as i64).rem_euclid(KYBER_Q as i64);
+                    let centered = if c > KYBER_Q as i64 / 2 { c - KYBER_Q as i64 } else { c };
+                    assert!(centered.abs() <= ETA1 as i64, "e[{}][{}] = {}", i, j, centered);
+                }
+                for j in 0..K {
+                    assert_eq!(a_t[j][i], a[i][j], "A^T[{}][{}]", j, i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decode_to_ntt_keeps_decaps_unchanged() {
+        use crate::kyber_codec::poly_encode12;
+
+        let d = [0x5Au8; SYMBYTES];
+        let (pk, sk) = indcpa_keypair_deterministic(&d);
+
+        // same secret stored in normal domain
+        let sigma: [u8; SYMBYTES] = sha3_512(&d)[SYMBYTES..].try_into().unwrap();
+        let mut sk_normal = [0u8; INDCPA_SECRETKEYBYTES];
+        for (i, chunk) in sk_normal.chunks_exact_mut(POLYBYTES).enumerate() {
+            chunk.copy_from_slice(&poly_encode12(&poly_getnoise(&sigma, i as u8, ETA1)));
+        }
+
+        let q = KYBER_Q as i64;
+        let a = decode_to_ntt(&sk, SecretKeyForm::Ntt);
+        let b = decode_to_ntt(&sk_normal, SecretKeyForm::Normal);
+        for i in 0..K {
+            for j in 0..N {
+                assert_eq!((a[i][j].raw() as i64).rem_euclid(q), (b[i][j].raw() as i64).rem_euclid(q), "[{}][{}]", i, j);
+            }
+        }
+
+        for seed in 0u8..4 {
+            let m = [seed.wrapping_mul(0x3B); INDCPA_MSGBYTES];
+            let ct = indcpa_enc(&pk, &m, &[seed; SYMBYTES]);
+            assert_eq!(indcpa_dec(&sk, &ct), m);
+        }
+    }
+}
@@ -3,9 +3,16 @@
 
 #![allow(dead_code)]
 
-use crate::kyber_indcpa::{hash_pk, indcpa_dec, indcpa_enc, indcpa_keypair_deterministic};
-use crate::kyber_params::{CIPHERTEXTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES, INDCPA_SECRETKEYBYTES};
+use std::fmt;
+
+use crate::kyber_codec::pk_decode;
+use crate::kyber_indcpa::{
+    hash_pk, indcpa_dec, indcpa_enc, indcpa_keypair_deterministic, indcpa_keypair_deterministic_with, KeygenDomain,
+};
+use crate::kyber_params::{KYBER_Q, CIPHERTEXTBYTES, POLYVECBYTES, POLYVECCOMPRESSEDBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SYMBYTES, INDCPA_SECRETKEYBYTES};
 use crate::shake::{sha3_256, sha3_512, shake256};
+use crate::xof_stream::ShakeStream;
+use crate::util::key_header::{self, Algorithm, KYBER512_LEVEL};
 
 #[inline(always)]
 fn ct_equal(a: &[u8], b: &[u8]) -> bool {
@@ -29,7 +36,17 @@ pub fn crypto_kem_keypair_deterministic(
     d: &[u8; SYMBYTES],
     z: &[u8; SYMBYTES],
 ) -> ([u8; PUBLICKEYBYTES], [u8; SECRETKEYBYTES]) {
-    let (pk, sk_indcpa) = indcpa_keypair_deterministic(d);
+    crypto_kem_keypair_deterministic_with(d, z, KeygenDomain::Round3)
+}
+
+/// Keypair with the Round 3 G(d) or the ML-KEM G(d || k) seed expansion.
+/// The sk layout is the same for both.
+pub fn crypto_kem_keypair_deterministic_with(
+    d: &[u8; SYMBYTES],
+    z: &[u8; SYMBYTES],
+    domain: KeygenDomain,
+) -> ([u8; PUBLICKEYBYTES], [u8; SECRETKEYBYTES]) {
+    let (pk, sk_indcpa) = indcpa_keypair_deterministic_with(d, domain);
     let hpk = hash_pk(&pk);
 
     // sk = sk_indcpa || pk || H(pk) || z
@@ -50,19 +67,66 @@ pub fn crypto_kem_keypair_deterministic(
     (pk, sk)
 }
 
+/// Final key derivation of the KEM. The two variants share Enc/Dec and the
+/// FO re-encryption check but produce different `ss` (and so match different
+/// KAT files).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KemKdf {
+    /// Kyber Round 3: m = H(seed_m), ss = SHAKE256(Kbar || H(c)), implicit
+    /// rejection replaces Kbar by z.
+    #[default]
+    Round3,
+    /// FIPS 203 ML-KEM: m = seed_m, ss = Kbar, implicit rejection returns
+    /// J(z || c) = SHAKE256(z || c).
+    MlKem,
+}
+
 /// Deterministic encaps using seed_m as entropy.
 /// Returns (ct, ss).
 pub fn crypto_kem_enc_deterministic(
     pk: &[u8; PUBLICKEYBYTES],
     seed_m: &[u8; SYMBYTES],
 ) -> ([u8; CIPHERTEXTBYTES], [u8; SYMBYTES]) {
-    // m = H(seed_m)
-    let m = sha3_256(seed_m);
+    crypto_kem_enc_deterministic_with(pk, seed_m, KemKdf::Round3)
+}
+
+/// `crypto_kem_enc_deterministic` with the final derivation selected by `kdf`.
+pub fn crypto_kem_enc_deterministic_with(
+    pk: &[u8; PUBLICKEYBYTES],
+    seed_m: &[u8; SYMBYTES],
+    kdf: KemKdf,
+) -> ([u8; CIPHERTEXTBYTES], [u8; SYMBYTES]) {
+    let (ct, kbar) = encaps_kbar(pk, seed_m, kdf);
+
+    let ss = match kdf {
+        KemKdf::Round3 => decaps_kdf(&kbar, &ct),
+        KemKdf::MlKem => kbar,
+    };
+
+    (ct, ss)
+}
+
+/// Encaps up to the final derivation. Returns (ct, Kbar).
+fn encaps_kbar(
+    pk: &[u8; PUBLICKEYBYTES],
+    seed_m: &[u8; SYMBYTES],
+    kdf: KemKdf,
+) -> ([u8; CIPHERTEXTBYTES], [u8; SYMBYTES]) {
+    // m = H(seed_m) (Round 3) or seed_m itself (ML-KEM)
+    let m = match kdf {
+        KemKdf::Round3 => sha3_256(seed_m),
+        KemKdf::MlKem => *seed_m,
+    };
+    encaps_kbar_from_m(pk, &m)
+}
+
+/// `encaps_kbar` after the seed -> m step.
+fn encaps_kbar_from_m(pk: &[u8; PUBLICKEYBYTES], m: &[u8; SYMBYTES]) -> ([u8; CIPHERTEXTBYTES], [u8; SYMBYTES]) {
     let hpk = hash_pk(pk);
 
     // (Kbar || r) = G(m || H(pk)) where G=SHA3-512
     let mut buf = [0u8; 2 * SYMBYTES];
-    buf[..SYMBYTES].copy_from_slice(&m);
+    buf[..SYMBYTES].copy_from_slice(m);
     buf[SYMBYTES..].copy_from_slice(&hpk);
 
     let gr = sha3_512(&buf);
@@ -72,30 +136,40 @@ pub fn crypto_kem_enc_deterministic(
     coins.copy_from_slice(&gr[SYMBYTES..]);
 
     // c = Enc(pk, m, coins)
-    let ct = indcpa_enc(pk, &m, &coins);
+    let ct = indcpa_enc(pk, m, &coins);
 
-    // ss = KDF(Kbar || H(c)) where KDF=SHAKE256 to 32 bytes
-    let hc = sha3_256(&ct);
-    let mut kd_in = [0u8; 2 * SYMBYTES];
-    kd_in[..SYMBYTES].copy_from_slice(&kbar);
-    kd_in[SYMBYTES..].copy_from_slice(&hc);
+    (ct, kbar)
+}
 
-    let mut ss = [0u8; SYMBYTES];
-    shake256(&kd_in, &mut ss);
+/// The encapsulation key embedded in a decapsulation key
+/// (sk_indcpa || pk || H(pk) || z). No integrity check; see
+/// `secret_key_hash_matches`.
+pub fn public_key_from_secret(sk: &[u8; SECRETKEYBYTES]) -> [u8; PUBLICKEYBYTES] {
+    let mut pk = [0u8; PUBLICKEYBYTES];
+    pk.copy_from_slice(&sk[INDCPA_SECRETKEYBYTES..INDCPA_SECRETKEYBYTES + PUBLICKEYBYTES]);
+    pk
+}
 
-    (ct, ss)
+/// Whether the H(pk) stored in `sk` is the hash of the embedded pk (the
+/// FIPS 203 decapsulation-key check). False means the key is corrupted.
+pub fn secret_key_hash_matches(sk: &[u8; SECRETKEYBYTES]) -> bool {
+    let off = INDCPA_SECRETKEYBYTES + PUBLICKEYBYTES;
+    ct_equal(&hash_pk(&public_key_from_secret(sk)), &sk[off..off + SYMBYTES])
 }
 
-/// KEM decapsulation: returns shared secret ss.
-pub fn crypto_kem_dec(sk: &[u8; SECRETKEYBYTES], ct: &[u8; CIPHERTEXTBYTES]) -> [u8; SYMBYTES] {
+/// Shared front half of decapsulation: re-encrypts m' = Dec(sk, c).
+/// Returns (Kbar', c', z).
+fn decaps_reencrypt(
+    sk: &[u8; SECRETKEYBYTES],
+    ct: &[u8; CIPHERTEXTBYTES],
+) -> ([u8; SYMBYTES], [u8; CIPHERTEXTBYTES], [u8; SYMBYTES]) {
     // Layout: sk_indcpa || pk || H(pk) || z
     let sk_indcpa_len = INDCPA_SECRETKEYBYTES;
     let pk_off = sk_indcpa_len;
     let hpk_off = pk_off + PUBLICKEYBYTES;
     let z_off = hpk_off + SYMBYTES;
 
-    let mut pk = [0u8; PUBLICKEYBYTES];
-    pk.copy_from_slice(&sk[pk_off..pk_off + PUBLICKEYBYTES]);
+    let pk = public_key_from_secret(sk);
 
     let mut hpk = [0u8; SYMBYTES];
     hpk.copy_from_slice(&sk[hpk_off..hpk_off + SYMBYTES]);
@@ -123,26 +197,184 @@ pub fn crypto_kem_dec(sk: &[u8; SECRETKEYBYTES], ct: &[u8; CIPHERTEXTBYTES]) ->
     // c' = Enc(pk, m', r')
     let ct_prime = indcpa_enc(&pk, &mprime, &coins);
 
-    // If c != c' then Kbar = z (constant-time)
-    let ok = ct_equal(ct, &ct_prime);
-    cmov(&mut kbar, &z, !ok);
-
-    // ss = KDF(Kbar || H(c))
-    let hc = sha3_256(ct);
-    let mut kd_in = [0u8; 2 * SYMBYTES];
-    kd_in[..SYMBYTES].copy_from_slice(&kbar);
-    kd_in[SYMBYTES..].copy_from_slice(&hc);
+    (kbar, ct_prime, z)
+}
 
+/// ss = KDF(Kbar || H(c))
+fn decaps_kdf(kbar: &[u8; SYMBYTES], ct: &[u8; CIPHERTEXTBYTES]) -> [u8; SYMBYTES] {
     let mut ss = [0u8; SYMBYTES];
-    shake256(&kd_in, &mut ss);
+    kdf_into(kbar, ct, &mut ss);
     ss
 }
 
+/// SHAKE256(Kbar || H(c)) squeezed to `out.len()` bytes; the first 32 are `ss`.
+fn kdf_into(kbar: &[u8; SYMBYTES], ct: &[u8; CIPHERTEXTBYTES], out: &mut [u8]) {
+    kdf_stream(kbar, &sha3_256(ct)).fill_bytes(out);
+}
+
+/// The Round 3 KDF SHAKE256(Kbar || H(c)) as an open stream, for callers
+/// that squeeze key material incrementally (key, then IV, ...). The first
+/// 32 bytes are `ss`.
+pub fn kdf_stream(kbar: &[u8; SYMBYTES], hct: &[u8; SYMBYTES]) -> ShakeStream {
+    let mut kd_in = [0u8; 2 * SYMBYTES];
+    kd_in[..SYMBYTES].copy_from_slice(kbar);
+    kd_in[SYMBYTES..].copy_from_slice(hct);
+    ShakeStream::shake256(&kd_in)
+}
+
+/// ML-KEM implicit-rejection key J(z || c).
+fn rejection_key(z: &[u8; SYMBYTES], ct: &[u8; CIPHERTEXTBYTES]) -> [u8; SYMBYTES] {
+    let mut j_in = [0u8; SYMBYTES + CIPHERTEXTBYTES];
+    j_in[..SYMBYTES].copy_from_slice(z);
+    j_in[SYMBYTES..].copy_from_slice(ct);
+
+    let mut k = [0u8; SYMBYTES];
+    shake256(&j_in, &mut k);
+    k
+}
+
+/// KEM decapsulation: returns shared secret ss.
+pub fn crypto_kem_dec(sk: &[u8; SECRETKEYBYTES], ct: &[u8; CIPHERTEXTBYTES]) -> [u8; SYMBYTES] {
+    crypto_kem_dec_with(sk, ct, KemKdf::Round3)
+}
+
+/// `crypto_kem_dec` with the final derivation selected by `kdf`.
+pub fn crypto_kem_dec_with(
+    sk: &[u8; SECRETKEYBYTES],
+    ct: &[u8; CIPHERTEXTBYTES],
+    kdf: KemKdf,
+) -> [u8; SYMBYTES] {
+    let (mut kbar, ct_prime, z) = decaps_reencrypt(sk, ct);
+
+    // If c != c' then Kbar = z or J(z || c) (constant-time)
+    let ok = ct_equal(ct, &ct_prime);
+    match kdf {
+        KemKdf::Round3 => {
+            cmov(&mut kbar, &z, !ok);
+            decaps_kdf(&kbar, ct)
+        }
+        KemKdf::MlKem => {
+            cmov(&mut kbar, &rejection_key(&z, ct), !ok);
+            kbar
+        }
+    }
+}
+
+/// Decapsulation with the c == c' check written as the RTL does it: OR-reduce
+/// the byte differences, turn that into an all-ones/all-zeros mask and blend
+/// Kbar'/z byte-wise. No bool, no branch.
+pub fn kem_decaps_masked(ct: &[u8; CIPHERTEXTBYTES], sk: &[u8; SECRETKEYBYTES]) -> [u8; SYMBYTES] {
+    let (kbar, ct_prime, z) = decaps_reencrypt(sk, ct);
+
+    let diff = ct
+        .iter()
+        .zip(ct_prime.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+
+    // diff == 0 -> 0xFF, diff in 1..=255 -> 0x00
+    let mask = ((diff as u16).wrapping_sub(1) >> 8) as u8;
+
+    let mut k = [0u8; SYMBYTES];
+    for (i, kb) in k.iter_mut().enumerate() {
+        *kb = (kbar[i] & mask) | (z[i] & !mask);
+    }
+
+    decaps_kdf(&k, ct)
+}
+
+/// Ciphertext part a byte offset falls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtRegion {
+    /// c1 = Compress_du(u), bytes [0, POLYVECCOMPRESSEDBYTES).
+    U,
+    /// c2 = Compress_dv(v), the remaining bytes.
+    V,
+}
+
+/// Where a ciphertext first differs from its re-encryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MismatchInfo {
+    /// Offset into the full ciphertext.
+    pub offset: usize,
+    pub region: CtRegion,
+}
+
+/// `kem_decaps` plus the first byte where c and the re-encryption c' differ
+/// (None when they match). Debug aid only: the scan stops at the first
+/// difference, so unlike the decapsulation itself it is not constant-time.
+pub fn kem_decaps_diagnose(
+    ct: &[u8; CIPHERTEXTBYTES],
+    sk: &[u8; SECRETKEYBYTES],
+) -> ([u8; SYMBYTES], Option<MismatchInfo>) {
+    let (mut kbar, ct_prime, z) = decaps_reencrypt(sk, ct);
+
+    let mismatch = ct.iter().zip(ct_prime.iter()).position(|(a, b)| a != b).map(|offset| MismatchInfo {
+        offset,
+        region: if offset < POLYVECCOMPRESSEDBYTES { CtRegion::U } else { CtRegion::V },
+    });
+
+    cmov(&mut kbar, &z, mismatch.is_some());
+    (decaps_kdf(&kbar, ct), mismatch)
+}
+
+// -----------------------------------------------------------------------------
+// Optional public-key sanity check (call before encaps on untrusted keys)
+// -----------------------------------------------------------------------------
+
+/// Why `validate_public_key` rejected a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PkError {
+    /// The matrix seed rho is all zero.
+    ZeroRho,
+    /// Every coefficient of t is zero.
+    ZeroT,
+    /// A 12-bit t coefficient is >= q (the FIPS 203 modulus check).
+    CoeffOutOfRange { poly: usize, index: usize, value: u16 },
+}
+
+impl fmt::Display for PkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PkError::ZeroRho => write!(f, "public key rho is all zero"),
+            PkError::ZeroT => write!(f, "public key t is all zero"),
+            PkError::CoeffOutOfRange { poly, index, value } => {
+                write!(f, "t[{}][{}] = {} is not reduced mod q", poly, index, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PkError {}
+
+/// Rejects public keys that are malformed (t not reduced mod q) or
+/// trivially degenerate (all-zero rho or t).
+pub fn validate_public_key(pk: &[u8; PUBLICKEYBYTES]) -> Result<(), PkError> {
+    let (t, rho) = pk_decode(pk);
+    if rho.iter().all(|&b| b == 0) {
+        return Err(PkError::ZeroRho);
+    }
+    for (poly, p) in t.iter().enumerate() {
+        for (index, c) in p.iter().enumerate() {
+            let value = c.raw() as u16;
+            if value >= KYBER_Q as u16 {
+                return Err(PkError::CoeffOutOfRange { poly, index, value });
+            }
+        }
+    }
+    if t.iter().flatten().all(|c| c.raw() == 0) {
+        return Err(PkError::ZeroT);
+    }
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // Simple wrappers used by your demo (kyber_demo.rs)
 // -----------------------------------------------------------------------------
 
 /// Demo-friendly deterministic keypair from a single 32-byte seed.
+///
+/// The seed is the only secret input; outside tests it must come from a
+/// CSPRNG. See `kem_keypair_checked` for a variant that refuses degenerate seeds.
 pub fn kem_keypair(seed: &[u8; SYMBYTES]) -> ([u8; PUBLICKEYBYTES], [u8; SECRETKEYBYTES]) {
     // Derive (d,z) from SHA3-512(seed)
     let g = sha3_512(seed);
@@ -153,12 +385,600 @@ pub fn kem_keypair(seed: &[u8; SYMBYTES]) -> ([u8; PUBLICKEYBYTES], [u8; SECRETK
     crypto_kem_keypair_deterministic(&d, &z)
 }
 
+/// Why `kem_keypair_checked` refused a seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedError {
+    /// Every seed byte is 0x00 (typically an uninitialised buffer).
+    AllZero,
+    /// Every seed byte is 0xFF (typically erased flash / a failed read).
+    AllOnes,
+}
+
+impl fmt::Display for SeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedError::AllZero => write!(f, "keygen seed is all 0x00"),
+            SeedError::AllOnes => write!(f, "keygen seed is all 0xFF"),
+        }
+    }
+}
+
+impl std::error::Error for SeedError {}
+
+/// `kem_keypair` with a defense-in-depth check for obviously degenerate seeds.
+///
+/// Passing the check says nothing about entropy: production keys still
+/// require a seed drawn from a CSPRNG. This only catches buffers that were
+/// never filled.
+pub fn kem_keypair_checked(
+    seed: &[u8; SYMBYTES],
+) -> Result<([u8; PUBLICKEYBYTES], [u8; SECRETKEYBYTES]), SeedError> {
+    if seed.iter().all(|&b| b == 0x00) {
+        return Err(SeedError::AllZero);
+    }
+    if seed.iter().all(|&b| b == 0xFF) {
+        return Err(SeedError::AllOnes);
+    }
+    Ok(kem_keypair(seed))
+}
+
 /// Demo-friendly deterministic encaps from a 32-byte seed.
 pub fn kem_encaps(seed_m: &[u8; SYMBYTES], pk: &[u8; PUBLICKEYBYTES]) -> ([u8; CIPHERTEXTBYTES], [u8; SYMBYTES]) {
-    crypto_kem_enc_deterministic(pk, seed_m)
+    let mut ss = [0u8; SYMBYTES];
+    let ct = kem_encaps_kdf(seed_m, pk, &mut ss);
+    (ct, ss)
+}
+
+/// Encaps with the Round 3 SHAKE256 KDF squeezed to `out.len()` bytes (e.g. 64
+/// for an AEAD key and nonce). The first 32 bytes equal `kem_encaps`'s ss.
+pub fn kem_encaps_kdf(seed_m: &[u8; SYMBYTES], pk: &[u8; PUBLICKEYBYTES], out: &mut [u8]) -> [u8; CIPHERTEXTBYTES] {
+    let (ct, kbar) = encaps_kbar(pk, seed_m, KemKdf::Round3);
+    kdf_into(&kbar, &ct, out);
+    ct
+}
+
+/// Encaps returning the KDF as a stream instead of a fixed-size `ss`.
+pub fn kem_encaps_stream(seed_m: &[u8; SYMBYTES], pk: &[u8; PUBLICKEYBYTES]) -> ([u8; CIPHERTEXTBYTES], ShakeStream) {
+    let (ct, kbar) = encaps_kbar(pk, seed_m, KemKdf::Round3);
+    let stream = kdf_stream(&kbar, &sha3_256(&ct));
+    (ct, stream)
+}
+
+/// Decaps counterpart of `kem_encaps_stream` (implicit rejection included).
+pub fn kem_decaps_stream(ct: &[u8; CIPHERTEXTBYTES], sk: &[u8; SECRETKEYBYTES]) -> ShakeStream {
+    let (mut kbar, ct_prime, z) = decaps_reencrypt(sk, ct);
+    cmov(&mut kbar, &z, !ct_equal(ct, &ct_prime));
+    kdf_stream(&kbar, &sha3_256(ct))
 }
 
 /// Demo-friendly decaps.
 pub fn kem_decaps(ct: &[u8; CIPHERTEXTBYTES], sk: &[u8; SECRETKEYBYTES]) -> [u8; SYMBYTES] {
     crypto_kem_dec(sk, ct)
 }
+
+/// Encapsulates to several recipients from one seed: one `(ct, ss)` per
+/// key, each decapsulating with that recipient's sk alone. Recipient i gets
+/// its own m_i = H(seed_m || i) (i as 4 little-endian bytes): decapsulation
+/// reveals m to its recipient, and a shared m would let any of them
+/// re-encapsulate to the other public keys and recompute their `ss`.
+pub fn kem_encaps_multi(
+    seed_m: &[u8; SYMBYTES],
+    pks: &[[u8; PUBLICKEYBYTES]],
+) -> Vec<([u8; CIPHERTEXTBYTES], [u8; SYMBYTES])> {
+    pks.iter()
+        .enumerate()
+        .map(|(i, pk)| {
+            let (ct, kbar) = encaps_kbar_from_m(pk, &multi_m(seed_m, i));
+            let mut ss = [0u8; SYMBYTES];
+            kdf_into(&kbar, &ct, &mut ss);
+            (ct, ss)
+        })
+        .collect()
+}
+
+/// m_i = H(seed_m || i) for `kem_encaps_multi`.
+fn multi_m(seed_m: &[u8; SYMBYTES], i: usize) -> [u8; SYMBYTES] {
+    let mut buf = [0u8; SYMBYTES + 4];
+    buf[..SYMBYTES].copy_from_slice(seed_m);
+    buf[SYMBYTES..].copy_from_slice(&(i as u32).to_le_bytes());
+    sha3_256(&buf)
+}
+
+/// Decaps counterpart of `kem_encaps_kdf`: same `out.len()` gives the same bytes.
+pub fn kem_decaps_kdf(ct: &[u8; CIPHERTEXTBYTES], sk: &[u8; SECRETKEYBYTES], out: &mut [u8]) {
+    let (mut kbar, ct_prime, z) = decaps_reencrypt(sk, ct);
+    cmov(&mut kbar, &z, !ct_equal(ct, &ct_prime));
+    kdf_into(&kbar, ct, out);
+}
+
+// -----------------------------------------------------------------------------
+// Compressed secret key (storage form)
+// -----------------------------------------------------------------------------
+//
+// The full secret key is sk_indcpa || pk || H(pk) || z. The public key cannot be
+// rebuilt from sk_indcpa alone (t = A*s + e also needs e), but everything is a
+// deterministic function of the keygen seeds, so the storage form is d || z.
+
+pub const COMPRESSED_SECRETKEYBYTES: usize = 2 * SYMBYTES; // 64
+
+/// Compress `sk` to `d || z`. `d` must be the seed `sk` was generated from;
+/// returns None if it does not regenerate the same key.
+pub fn kem_compress_sk(
+    sk: &[u8; SECRETKEYBYTES],
+    d: &[u8; SYMBYTES],
+) -> Option<[u8; COMPRESSED_SECRETKEYBYTES]> {
+    let (pk, sk_indcpa) = indcpa_keypair_deterministic(d);
+    let pk_off = INDCPA_SECRETKEYBYTES;
+    if sk[..pk_off] != sk_indcpa[..] || sk[pk_off..pk_off + PUBLICKEYBYTES] != pk[..] {
+        return None;
+    }
+
+    let mut out = [0u8; COMPRESSED_SECRETKEYBYTES];
+    out[..SYMBYTES].copy_from_slice(d);
+    out[SYMBYTES..].copy_from_slice(&sk[SECRETKEYBYTES - SYMBYTES..]);
+    Some(out)
+}
+
+/// Rebuild the full secret key (including pk and H(pk)) from `d || z`.
+pub fn kem_expand_sk(csk: &[u8; COMPRESSED_SECRETKEYBYTES]) -> [u8; SECRETKEYBYTES] {
+    let mut d = [0u8; SYMBYTES];
+    let mut z = [0u8; SYMBYTES];
+    d.copy_from_slice(&csk[..SYMBYTES]);
+    z.copy_from_slice(&csk[SYMBYTES..]);
+    crypto_kem_keypair_deterministic(&d, &z).1
+}
+
+// -----------------------------------------------------------------------------
+// Typed key forms (FIPS 203 / PQClean byte layouts)
+// -----------------------------------------------------------------------------
+
+/// Encapsulation key ek = t_hat (12-bit packed, NTT domain) || rho.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KyberEncapsKey {
+    pub t_hat: [u8; POLYVECBYTES],
+    pub rho: [u8; SYMBYTES],
+}
+
+impl KyberEncapsKey {
+    /// Splits an 800-byte ek. Only the length is checked; use
+    /// `validate_public_key` for the FIPS 203 modulus check.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != PUBLICKEYBYTES {
+            return None;
+        }
+        let (t, rho) = bytes.split_at(POLYVECBYTES);
+        Some(Self { t_hat: t.try_into().ok()?, rho: rho.try_into().ok()? })
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLICKEYBYTES] {
+        let mut out = [0u8; PUBLICKEYBYTES];
+        out[..POLYVECBYTES].copy_from_slice(&self.t_hat);
+        out[POLYVECBYTES..].copy_from_slice(&self.rho);
+        out
+    }
+
+    /// `to_bytes` behind a `util::key_header` header, for key storage.
+    pub fn to_bytes_versioned(&self) -> Vec<u8> {
+        key_header::wrap(Algorithm::Kyber, KYBER512_LEVEL, &self.to_bytes())
+    }
+
+    /// Inverse of `to_bytes_versioned`. None on any header mismatch.
+    pub fn from_bytes_versioned(bytes: &[u8]) -> Option<Self> {
+        Self::from_bytes(key_header::unwrap(bytes, Algorithm::Kyber, KYBER512_LEVEL)?)
+    }
+}
+
+/// Decapsulation key dk = s_hat || ek || H(ek) || z.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KyberDecapsKey {
+    pub s_hat: [u8; INDCPA_SECRETKEYBYTES],
+    pub ek: KyberEncapsKey,
+    pub h_ek: [u8; SYMBYTES],
+    pub z: [u8; SYMBYTES],
+}
+
+impl KyberDecapsKey {
+    /// Splits a 1632-byte dk. Returns None on a wrong length or when the
+    /// embedded H(ek) does not match ek (the FIPS 203 hash check).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != SECRETKEYBYTES {
+            return None;
+        }
+        let (s_hat, rest) = bytes.split_at(INDCPA_SECRETKEYBYTES);
+        let (ek, rest) = rest.split_at(PUBLICKEYBYTES);
+        let (h_ek, z) = rest.split_at(SYMBYTES);
+
+        let key = Self {
+            s_hat: s_hat.try_into().ok()?,
+            ek: KyberEncapsKey::from_bytes(ek)?,
+            h_ek: h_ek.try_into().ok()?,
+            z: z.try_into().ok()?,
+        };
+        if hash_pk(&key.ek.to_bytes()) != key.h_ek {
+            return None;
+        }
+        Some(key)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SECRETKEYBYTES] {
+        let mut out = [0u8; SECRETKEYBYTES];
+        let (s_hat, rest) = out.split_at_mut(INDCPA_SECRETKEYBYTES);
+        let (ek, rest) = rest.split_at_mut(PUBLICKEYBYTES);
+        let (h_ek, z) = rest.split_at_mut(SYMBYTES);
+        s_hat.copy_from_slice(&self.s_hat);
+        ek.copy_from_slice(&self.ek.to_bytes());
+        h_ek.copy_from_slice(&self.h_ek);
+        z.copy_from_slice(&self.z);
+        out
+    }
+
+    /// `to_bytes` behind a `util::key_header` header, for key storage.
+    pub fn to_bytes_versioned(&self) -> Vec<u8> {
+        key_header::wrap(Algorithm::Kyber, KYBER512_LEVEL, &self.to_bytes())
+    }
+
+    /// Inverse of `to_bytes_versioned`. None on any header mismatch or when
+    /// `from_bytes` rejects the key.
+    pub fn from_bytes_versioned(bytes: &[u8]) -> Option<Self> {
+        Self::from_bytes(key_header::unwrap(bytes, Algorithm::Kyber, KYBER512_LEVEL)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decaps_diagnose_reports_first_mismatch() {
+        let (pk, sk) = kem_keypair(&[0x6Du8; SYMBYTES]);
+        let (ct, ss) = kem_encaps(&[0x6Eu8; SYMBYTES], &pk);
+        assert_eq!(kem_decaps_diagnose(&ct, &sk), (ss, None));
+
+        // Flipping the low bit of one compressed coefficient leaves m' (and
+        // so c') unchanged, so the corrupted byte is the first difference.
+        for (offset, region) in [(100, CtRegion::U), (POLYVECCOMPRESSEDBYTES, CtRegion::V), (CIPHERTEXTBYTES - 1, CtRegion::V)] {
+            let mut bad = ct;
+            bad[offset] ^= 0x01;
+            let (ss_bad, info) = kem_decaps_diagnose(&bad, &sk);
+            assert_eq!(info, Some(MismatchInfo { offset, region }));
+            assert_eq!(ss_bad, kem_decaps(&bad, &sk));
+            assert_ne!(ss_bad, ss);
+        }
+    }
+
+    #[test]
+    fn encaps_multi_decapsulates_per_recipient() {
+        let keys: Vec<_> = (0..4u8).map(|i| kem_keypair(&[0x90 + i; SYMBYTES])).collect();
+        let pks: Vec<_> = keys.iter().map(|(pk, _)| *pk).collect();
+        let seed_m = [0x17u8; SYMBYTES];
+
+        let out = kem_encaps_multi(&seed_m, &pks);
+        assert_eq!(out.len(), pks.len());
+        for (i, ((ct, ss), (_, sk))) in out.iter().zip(keys.iter()).enumerate() {
+            assert_eq!(kem_decaps(ct, sk), *ss, "recipient {}", i);
+            // useless to the other recipients' keys
+            let (_, other_sk) = &keys[(i + 1) % keys.len()];
+            assert_ne!(kem_decaps(ct, other_sk), *ss);
+
+            // the m recipient i recovers does not rebuild anyone else's ct or ss
+            let m = indcpa_dec(sk[..INDCPA_SECRETKEYBYTES].try_into().unwrap(), ct);
+            for (j, (pk_j, _)) in keys.iter().enumerate().filter(|&(j, _)| j != i) {
+                let (ct_j, kbar) = encaps_kbar_from_m(pk_j, &m);
+                let mut ss_j = [0u8; SYMBYTES];
+                kdf_into(&kbar, &ct_j, &mut ss_j);
+                assert_ne!((ct_j, ss_j), out[j], "recipient {} from {}", j, i);
+            }
+        }
+        assert_ne!(out[0].0, out[1].0);
+        // a repeated key still gets independent encapsulations
+        let twice = kem_encaps_multi(&seed_m, &[pks[0], pks[0]]);
+        assert_ne!(twice[0], twice[1]);
+        assert!(kem_encaps_multi(&seed_m, &[]).is_empty());
+    }
+
+    #[test]
+    fn public_key_from_secret_extracts_and_checks_hash() {
+        let (pk, sk) = kem_keypair(&[0x4Bu8; SYMBYTES]);
+        assert_eq!(public_key_from_secret(&sk), pk);
+        assert!(secret_key_hash_matches(&sk));
+
+        // corrupted H(pk)
+        let mut bad = sk;
+        bad[INDCPA_SECRETKEYBYTES + PUBLICKEYBYTES + 5] ^= 0x10;
+        assert_eq!(public_key_from_secret(&bad), pk);
+        assert!(!secret_key_hash_matches(&bad));
+
+        // corrupted embedded pk, hash left alone
+        let mut bad = sk;
+        bad[INDCPA_SECRETKEYBYTES + 3] ^= 0x01;
+        assert_ne!(public_key_from_secret(&bad), pk);
+        assert!(!secret_key_hash_matches(&bad));
+    }
+
+    #[test]
+    fn typed_keys_roundtrip() {
+        let (pk, sk) = kem_keypair(&[0x3Cu8; SYMBYTES]);
+
+        let ek = KyberEncapsKey::from_bytes(&pk).unwrap();
+        assert_eq!(ek.to_bytes(), pk);
+        assert_eq!(ek.rho[..], pk[POLYVECBYTES..]);
+
+        let dk = KyberDecapsKey::from_bytes(&sk).unwrap();
+        assert_eq!(dk.to_bytes(), sk);
+        assert_eq!(dk.ek, ek);
+        assert_eq!(dk.h_ek, hash_pk(&pk));
+
+        // the typed forms drive the raw API unchanged
+        let (ct, ss) = kem_encaps(&[0x11; SYMBYTES], &ek.to_bytes());
+        assert_eq!(kem_decaps(&ct, &dk.to_bytes()), ss);
+
+        assert_eq!(KyberEncapsKey::from_bytes(&pk[1..]), None);
+        assert_eq!(KyberDecapsKey::from_bytes(&sk[..SECRETKEYBYTES - 1]), None);
+        let mut bad = sk;
+        bad[INDCPA_SECRETKEYBYTES + 3] ^= 1; // ek no longer matches H(ek)
+        assert_eq!(KyberDecapsKey::from_bytes(&bad), None);
+    }
+
+    #[test]
+    fn keypair_checked_flags_degenerate_seeds() {
+        assert_eq!(kem_keypair_checked(&[0u8; SYMBYTES]), Err(SeedError::AllZero));
+        assert_eq!(kem_keypair_checked(&[0xFFu8; SYMBYTES]), Err(SeedError::AllOnes));
+
+        let mut drbg = crate::nist_drbg::NistDrbg::new(&[0x5Au8; 48]);
+        for _ in 0..4 {
+            let mut seed = [0u8; SYMBYTES];
+            drbg.randombytes(&mut seed);
+            assert_eq!(kem_keypair_checked(&seed), Ok(kem_keypair(&seed)));
+        }
+
+        // a single non-zero byte is enough to pass; this is not an entropy test
+        let mut seed = [0u8; SYMBYTES];
+        seed[31] = 1;
+        assert!(kem_keypair_checked(&seed).is_ok());
+    }
+
+    #[test]
+    fn compressed_sk_roundtrip_decapsulates() {
+        let d = [0x31u8; SYMBYTES];
+        let z = [0x7Eu8; SYMBYTES];
+        let (pk, sk) = crypto_kem_keypair_deterministic(&d, &z);
+
+        let csk = kem_compress_sk(&sk, &d).expect("seed matches key");
+        let sk2 = kem_expand_sk(&csk);
+        assert_eq!(sk2, sk);
+
+        let (ct, ss) = kem_encaps(&[0x42u8; SYMBYTES], &pk);
+        assert_eq!(kem_decaps(&ct, &sk2), ss);
+        assert_eq!(kem_decaps(&ct, &sk2), kem_decaps(&ct, &sk));
+    }
+
+    #[test]
+    fn masked_decaps_matches_decaps() {
+        let (pk, sk) = kem_keypair(&[0x11u8; SYMBYTES]);
+        let (ct, ss) = kem_encaps(&[0x22u8; SYMBYTES], &pk);
+
+        assert_eq!(kem_decaps_masked(&ct, &sk), ss);
+
+        // forged ciphertexts: implicit rejection must pick z in both paths
+        for (pos, flip) in [(0usize, 0x01u8), (CIPHERTEXTBYTES / 2, 0x80), (CIPHERTEXTBYTES - 1, 0xFF)] {
+            let mut bad = ct;
+            bad[pos] ^= flip;
+            let ss_masked = kem_decaps_masked(&bad, &sk);
+            assert_eq!(ss_masked, kem_decaps(&bad, &sk), "pos {}", pos);
+            assert_ne!(ss_masked, ss);
+        }
+    }
+
+    #[test]
+    fn matches_official_kat_vectors() {
+        use crate::nist_drbg::NistDrbg;
+        use crate::util::hex;
+
+        let rsp = include_str!("kat/PQCkemKAT_1632.rsp");
+        let field = |block: &str, name: &str| -> Vec<u8> {
+            let prefix = format!("{} = ", name);
+            let line = block.lines().find(|l| l.starts_with(&prefix)).unwrap();
+            hex::decode(&line[prefix.len()..]).unwrap()
+        };
+
+        let mut checked = 0;
+        for block in rsp.split("\n\n").filter(|b| b.contains("seed = ")).take(3) {
+            let seed: [u8; 48] = field(block, "seed").try_into().unwrap();
+            let mut drbg = NistDrbg::new(&seed);
+
+            // keypair: d then z; encaps: seed_m
+            let mut d = [0u8; SYMBYTES];
+            let mut z = [0u8; SYMBYTES];
+            let mut seed_m = [0u8; SYMBYTES];
+            drbg.randombytes(&mut d);
+            drbg.randombytes(&mut z);
+            let (pk, sk) = crypto_kem_keypair_deterministic(&d, &z);
+            drbg.randombytes(&mut seed_m);
+            let (ct, ss) = crypto_kem_enc_deterministic(&pk, &seed_m);
+
+            assert_eq!(pk[..], field(block, "pk")[..]);
+            assert_eq!(sk[..], field(block, "sk")[..]);
+            assert_eq!(ct[..], field(block, "ct")[..]);
+            assert_eq!(ss[..], field(block, "ss")[..]);
+            assert_eq!(crypto_kem_dec(&sk, &ct), ss);
+            checked += 1;
+        }
+        assert_eq!(checked, 3);
+    }
+
+    #[test]
+    fn compress_rejects_wrong_seed() {
+        let (_, sk) = crypto_kem_keypair_deterministic(&[1u8; SYMBYTES], &[2u8; SYMBYTES]);
+        assert!(kem_compress_sk(&sk, &[3u8; SYMBYTES]).is_none());
+    }
+
+    #[test]
+    fn keygen_domains_match_their_vectors() {
+        use crate::nist_drbg::NistDrbg;
+        use crate::util::hex;
+
+        // KAT count 0: d and z are the first two DRBG outputs. Round 3 must give
+        // the file's pk/sk; ML-KEM hashes G(d || 2) instead, so rho and t change.
+        // ML-KEM-512 values from OpenSSL 3.5.6 on the same d || z:
+        //   openssl genpkey -algorithm ML-KEM-512 -pkeyopt hexseed:<d || z>
+        // (its encoded pk/sk equal this crate's byte for byte).
+        let rsp = include_str!("kat/PQCkemKAT_1632.rsp");
+        let block = rsp.split("\n\n").find(|b| b.contains("count = 0")).unwrap();
+        let field = |name: &str| -> Vec<u8> {
+            let prefix = format!("{} = ", name);
+            let line = block.lines().find(|l| l.starts_with(&prefix)).unwrap();
+            hex::decode(&line[prefix.len()..]).unwrap()
+        };
+
+        let mut drbg = NistDrbg::new(&field("seed").try_into().unwrap());
+        let (mut d, mut z) = ([0u8; SYMBYTES], [0u8; SYMBYTES]);
+        drbg.randombytes(&mut d);
+        drbg.randombytes(&mut z);
+
+        let (pk, sk) = crypto_kem_keypair_deterministic_with(&d, &z, KeygenDomain::Round3);
+        assert_eq!(pk[..], field("pk")[..]);
+        assert_eq!(sk[..], field("sk")[..]);
+        assert_eq!((pk, sk), crypto_kem_keypair_deterministic(&d, &z));
+
+        let (pk_ml, sk_ml) = crypto_kem_keypair_deterministic_with(&d, &z, KeygenDomain::MlKem);
+        assert_eq!(
+            hex::encode(&pk_ml[PUBLICKEYBYTES - SYMBYTES..]),
+            "7efb9e40c3bf0ff0432986ae4bc1a242ce9921aa9e22448819585dea308eb039"
+        );
+        assert_eq!(
+            hex::encode(&sha3_256(&pk_ml)),
+            "50c8dd152a4531aab560d2fc7ca9a40ad8af25ad1dd08c6d79afe4dd4d1eee5a"
+        );
+        assert_eq!(
+            hex::encode(&sha3_256(&sk_ml[..INDCPA_SECRETKEYBYTES])),
+            "91bbfcd1bbc644cd82eba99b6e51724c48b96f6ad18c1da0361872dc4f596b64"
+        );
+        assert_eq!(sk_ml[SECRETKEYBYTES - SYMBYTES..], z);
+
+        // and the ML-KEM key works end to end
+        let (ct, ss) = crypto_kem_enc_deterministic_with(&pk_ml, &[0x42; SYMBYTES], KemKdf::MlKem);
+        assert_eq!(crypto_kem_dec_with(&sk_ml, &ct, KemKdf::MlKem), ss);
+    }
+
+    #[test]
+    fn kdf_variants_match_their_vectors() {
+        use crate::nist_drbg::NistDrbg;
+        use crate::util::hex;
+
+        // KAT count 0: the Round 3 file gives pk/sk/ct/ss directly. ML-KEM shares
+        // Enc, so with m = H(seed_m) it must reproduce the same ct; its ss is
+        // Kbar = G(m || H(pk))[..32], and a rejected ct yields SHAKE256(z || c).
+        // ML-KEM-512 values from OpenSSL 3.5.6 with the KAT pk/sk loaded as
+        // ML-KEM keys: `pkeyutl -encap -pkeyopt hexikme:<m>` gives this ct and
+        // ss, `pkeyutl -decap` of ct with byte 0 flipped the rejection key.
+        let rsp = include_str!("kat/PQCkemKAT_1632.rsp");
+        let block = rsp.split("\n\n").find(|b| b.contains("count = 0")).unwrap();
+        let field = |name: &str| -> Vec<u8> {
+            let prefix = format!("{} = ", name);
+            let line = block.lines().find(|l| l.starts_with(&prefix)).unwrap();
+            hex::decode(&line[prefix.len()..]).unwrap()
+        };
+        let pk: [u8; PUBLICKEYBYTES] = field("pk").try_into().unwrap();
+        let sk: [u8; SECRETKEYBYTES] = field("sk").try_into().unwrap();
+
+        let mut drbg = NistDrbg::new(&field("seed").try_into().unwrap());
+        let mut seed_m = [0u8; SYMBYTES];
+        for _ in 0..3 {
+            drbg.randombytes(&mut seed_m);
+        }
+
+        let (ct, ss) = crypto_kem_enc_deterministic_with(&pk, &seed_m, KemKdf::Round3);
+        assert_eq!(ct[..], field("ct")[..]);
+        assert_eq!(ss[..], field("ss")[..]);
+        assert_eq!(crypto_kem_dec_with(&sk, &ct, KemKdf::Round3), ss);
+
+        let m = sha3_256(&seed_m);
+        let (ct_ml, ss_ml) = crypto_kem_enc_deterministic_with(&pk, &m, KemKdf::MlKem);
+        assert_eq!(ct_ml, ct);
+        assert_eq!(
+            hex::encode(&ss_ml),
+            "c6677d7111eafa8a2570633ca5dd40aef40c3789d94e4b85241ac810f6812b77"
+        );
+        assert_eq!(crypto_kem_dec_with(&sk, &ct, KemKdf::MlKem), ss_ml);
+
+        let mut bad = ct;
+        bad[0] ^= 1;
+        assert_eq!(
+            hex::encode(&crypto_kem_dec_with(&sk, &bad, KemKdf::MlKem)),
+            "048a8d0cb0c1b92f1137eb68f521106dac323c2f81f85505cc15bd1728d912d1"
+        );
+    }
+
+    #[test]
+    fn validate_public_key_flags_degenerate_keys() {
+        let (pk, _) = kem_keypair(&[5u8; SYMBYTES]);
+        assert_eq!(validate_public_key(&pk), Ok(()));
+
+        assert_eq!(validate_public_key(&[0u8; PUBLICKEYBYTES]), Err(PkError::ZeroRho));
+
+        let mut zero_t = [0u8; PUBLICKEYBYTES];
+        zero_t[PUBLICKEYBYTES - 1] = 1;
+        assert_eq!(validate_public_key(&zero_t), Err(PkError::ZeroT));
+
+        // t[1][1] = 0xFFF: second coefficient of a triple, in the second poly
+        let mut bad = pk;
+        bad[384 + 1] |= 0xF0;
+        bad[384 + 2] = 0xFF;
+        assert_eq!(
+            validate_public_key(&bad),
+            Err(PkError::CoeffOutOfRange { poly: 1, index: 1, value: 0xFFF })
+        );
+    }
+
+    #[test]
+    fn long_kdf_extends_standard_ss() {
+        let (pk, sk) = kem_keypair(&[6u8; SYMBYTES]);
+        let seed_m = [7u8; SYMBYTES];
+
+        let (ct, ss) = crypto_kem_enc_deterministic(&pk, &seed_m);
+        let mut okm = [0u8; 64];
+        let ct64 = kem_encaps_kdf(&seed_m, &pk, &mut okm);
+        assert_eq!(ct64, ct);
+        assert_eq!(okm[..SYMBYTES], ss);
+        assert_eq!(kem_encaps(&seed_m, &pk), (ct, ss));
+
+        let mut okm_dec = [0u8; 64];
+        kem_decaps_kdf(&ct, &sk, &mut okm_dec);
+        assert_eq!(okm_dec, okm);
+
+        // implicit rejection also extends the 32-byte rejection secret
+        let mut bad = ct;
+        bad[5] ^= 0x10;
+        kem_decaps_kdf(&bad, &sk, &mut okm_dec);
+        assert_eq!(okm_dec[..SYMBYTES], kem_decaps(&bad, &sk));
+        assert_ne!(okm_dec, okm);
+    }
+
+    #[test]
+    fn kdf_stream_starts_with_ss_and_is_deterministic() {
+        let (pk, sk) = kem_keypair(&[8u8; SYMBYTES]);
+        let seed_m = [9u8; SYMBYTES];
+        let (ct, ss) = kem_encaps(&seed_m, &pk);
+
+        let (ct_s, mut enc) = kem_encaps_stream(&seed_m, &pk);
+        assert_eq!(ct_s, ct);
+        let mut key = [0u8; SYMBYTES];
+        enc.fill_bytes(&mut key);
+        assert_eq!(key, ss);
+
+        // 1000 bytes, squeezed in uneven pieces, equal a one-shot squeeze
+        let mut a = vec![0u8; 1000];
+        let mut dec = kem_decaps_stream(&ct, &sk);
+        for chunk in a.chunks_mut(137) {
+            dec.fill_bytes(chunk);
+        }
+        let mut b = vec![0u8; 1000];
+        kem_decaps_stream(&ct, &sk).fill_bytes(&mut b);
+        assert_eq!(a, b);
+        assert_eq!(a[..SYMBYTES], ss);
+
+        let mut long = vec![0u8; 1000];
+        kem_decaps_kdf(&ct, &sk, &mut long);
+        assert_eq!(long, a);
+    }
+}
@@ -4,6 +4,7 @@
 use rhdl::prelude::*;
 use crate::kyber_arith::{barrett_reduce, fqmul};
 use crate::kyber_params::ZETAS;
+use crate::ntt_control::{ControlOut, NttControl};
 
 /// f = inv128 * R^2 mod q = 1441 (Kyber reference invntt final factor)
 pub const INVNTT_F: i32 = 1441;
@@ -45,6 +46,11 @@ pub struct MemReq {
 }
 
 /// NTT command inputs
+///
+/// `rdata_a`/`rdata_b` are the BRAM outputs for the addresses driven in the
+/// previous cycle. The FSM only samples them in `Write` and `FinalWrite`,
+/// which always follow a read cycle, and ignores them otherwise; a driver
+/// may latch addresses every cycle or only on read cycles.
 #[derive(Copy, Clone, Default)]
 pub struct NttIn {
     pub start: bool,
@@ -63,7 +69,7 @@ pub struct NttOut {
 }
 
 /// FSM phases
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Phase {
     Idle,
     Read,
@@ -261,3 +267,188 @@ pub fn ntt_step(st: NttState, inp: NttIn) -> (NttState, NttOut) {
 
     (ns, out)
 }
+
+// -------------------------------------------------------
+// Fused final scaling (area/latency option for invntt)
+// -------------------------------------------------------
+
+pub use crate::ntt_control::FinalScaling;
+
+/// `ntt_step` with the invntt scaling fused into the last butterfly stage:
+/// a' = (a+b)*f and b' = ((b-a)*zeta)*f, the same reductions and Montgomery
+/// multiplies the FinalWrite pass would apply, so the output is bit-identical
+/// to the per-coefficient variant. Forward transforms are unchanged.
+pub fn ntt_step_fused(st: NttState, inp: NttIn) -> (NttState, NttOut) {
+    let (mut ns, mut out) = ntt_step(st, inp);
+
+    if st.phase == Phase::Write && st.inverse && st.len == u9(128) {
+        let f = s16(INVNTT_F);
+        let t = inp.rdata_a;
+        let b = inp.rdata_b;
+
+        let z = zeta_from_k(st.k);
+
+        out.porta.wdata = fqmul(barrett_reduce(t + b), f);
+        out.portb.wdata = fqmul(fqmul(z, b - t), f);
+
+        if ns.phase == Phase::FinalRead {
+            ns.phase = Phase::Done;
+        }
+    }
+
+    (ns, out)
+}
+
+// -------------------------------------------------------
+// Dataflow view (butterfly graph, for HLS flows)
+// -------------------------------------------------------
+
+/// One butterfly: coefficients `a_idx` and `b_idx` (= a_idx + len) combined
+/// with `ZETAS[zeta_idx]`, Cooley-Tukey forward and Gentleman-Sande inverse,
+/// exactly as the FSM's Write phase does it. `stage` counts from 0 in
+/// execution order. Two butterflies depend on each other iff they share an
+/// index and sit in different stages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Butterfly {
+    pub a_idx: usize,
+    pub b_idx: usize,
+    pub zeta_idx: usize,
+    pub stage: usize,
+}
+
+/// The butterflies of one n-point transform in the FSM's schedule order.
+/// The inverse's final multiply by `INVNTT_F` is per coefficient, not a
+/// butterfly, and is left out. `n` must be a power of two, 4..=256.
+pub fn ntt_dataflow(n: usize, inverse: bool) -> Vec<Butterfly> {
+    assert!(n.is_power_of_two() && (4..=256).contains(&n), "n must be a power of two in 4..=256");
+
+    let lens: Vec<usize> = if inverse {
+        (1..n.trailing_zeros()).map(|s| 1 << s).collect()
+    } else {
+        (1..n.trailing_zeros()).rev().map(|s| 1 << s).collect()
+    };
+
+    let mut out = Vec::with_capacity(lens.len() * n / 2);
+    let mut k = if inverse { n / 2 - 1 } else { 1 };
+    for (stage, &len) in lens.iter().enumerate() {
+        for start in (0..n).step_by(2 * len) {
+            for j in start..start + len {
+                out.push(Butterfly { a_idx: j, b_idx: j + len, zeta_idx: k, stage });
+            }
+            if inverse { k -= 1 } else { k += 1 }
+        }
+    }
+    out
+}
+
+// -------------------------------------------------------
+// Control transition relation (for model checking / state diagrams)
+// -------------------------------------------------------
+
+// `ntt_control::enumerate_transitions::<NttState>()` lists the relation.
+impl NttControl for NttState {
+    type Phase = Phase;
+    const MIN_LEN: usize = 2;
+    type Key = (u8, bool, u128, u128, u128, u128, u128);
+
+    fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    fn key(&self) -> Self::Key {
+        (self.phase as u8, self.inverse, self.len.raw(), self.start.raw(), self.j.raw(), self.k.raw(), self.idx.raw())
+    }
+
+    fn control_step(self, start: bool, inverse: bool) -> (Self, ControlOut) {
+        let (ns, o) = ntt_step(self, NttIn { start, inverse, ..Default::default() });
+        (ns, ControlOut { busy: o.busy, done: o.done, we_a: o.porta.we, we_b: o.portb.we })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt_control::expected_ntt_cycles;
+
+    fn measured_cycles(inverse: bool, scaling: FinalScaling) -> u64 {
+        let step = match scaling {
+            FinalScaling::PerCoeff => ntt_step,
+            FinalScaling::Fused => ntt_step_fused,
+        };
+        let mut st = NttState::default();
+        for cycle in 1..=20_000u64 {
+            let (ns, out) = step(st, NttIn { start: cycle == 1, inverse, ..Default::default() });
+            st = ns;
+            if out.done {
+                return cycle;
+            }
+        }
+        panic!("NTT FSM did not finish");
+    }
+
+    #[test]
+    fn expected_cycles_match_measured() {
+        for inverse in [false, true] {
+            for scaling in [FinalScaling::PerCoeff, FinalScaling::Fused] {
+                assert_eq!(
+                    expected_ntt_cycles::<NttState>(256, inverse, scaling),
+                    measured_cycles(inverse, scaling),
+                    "inverse={} {:?}", inverse, scaling
+                );
+            }
+        }
+        // the figures used in calcul_latenta
+        assert_eq!(expected_ntt_cycles::<NttState>(256, false, FinalScaling::PerCoeff), 1794);
+        assert_eq!(expected_ntt_cycles::<NttState>(256, true, FinalScaling::PerCoeff), 2306);
+    }
+
+    #[test]
+    fn dataflow_replay_matches_run_ntt() {
+        use crate::kyber_poly::run_ntt;
+
+        let a: [Coeff; 256] = core::array::from_fn(|i| s16(((i * 2_011 + 7) % 3329) as i32));
+        for inverse in [false, true] {
+            let graph = ntt_dataflow(256, inverse);
+            assert_eq!(graph.len(), 7 * 128);
+
+            let mut replay = a;
+            for bf in &graph {
+                let (x, y) = (replay[bf.a_idx], replay[bf.b_idx]);
+                let z = zeta_from_k(u8(bf.zeta_idx as u8));
+                let (nx, ny) = if inverse {
+                    (barrett_reduce(x + y), fqmul(z, y - x))
+                } else {
+                    let t = fqmul(z, y);
+                    (x + t, x - t)
+                };
+                replay[bf.a_idx] = nx;
+                replay[bf.b_idx] = ny;
+            }
+            if inverse {
+                for c in replay.iter_mut() {
+                    *c = fqmul(*c, s16(INVNTT_F));
+                }
+            }
+
+            let mut fsm = a;
+            run_ntt(&mut fsm, inverse);
+            assert_eq!(replay, fsm, "inverse={}", inverse);
+
+            // and the FSM issues its butterflies in the same order
+            let mut st = NttState::default();
+            let mut issued = Vec::new();
+            for cycle in 0..20_000 {
+                if st.phase == Phase::Write {
+                    issued.push((st.j.raw() as usize, (st.j + st.len).raw() as usize, st.k.raw() as usize));
+                }
+                let (ns, out) = ntt_step(st, NttIn { start: cycle == 0, inverse, ..Default::default() });
+                st = ns;
+                if out.done {
+                    break;
+                }
+            }
+            let listed: Vec<_> = graph.iter().map(|b| (b.a_idx, b.b_idx, b.zeta_idx)).collect();
+            assert_eq!(issued, listed, "inverse={}", inverse);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+// Single-port BRAM variant of the Kyber NTT FSM in `kyber_ntt.rs`.
+//
+// The dual-port core reads r[j], r[j+len] in one cycle and writes both back in
+// the next. With a single port only one access fits per cycle, so a butterfly
+// becomes ReadA -> ReadB -> WriteA -> WriteB (4 cycles instead of 2). The
+// invntt final scaling already used one port and is unchanged.
+//
+// Arithmetic and loop order are identical to `kyber_ntt::ntt_step`, so the
+// transform output is bit-for-bit the same.
+
+#![allow(dead_code)]
+
+use rhdl::prelude::*;
+use crate::kyber_arith::{barrett_reduce, fqmul};
+use crate::kyber_ntt::{Coeff, MemReq, INVNTT_F};
+use crate::kyber_params::{N, ZETAS};
+
+#[inline(always)]
+fn u9(x: u16) -> Bits<U9> {
+    bits(x as u128)
+}
+#[inline(always)]
+fn u8(x: u8) -> Bits<U8> {
+    bits(x as u128)
+}
+#[inline(always)]
+fn s16(x: i32) -> Coeff {
+    signed::<U16>(x as i128)
+}
+
+#[inline(always)]
+fn zeta_from_k(k: Bits<U8>) -> Coeff {
+    s16(ZETAS[k.raw() as usize] as i32)
+}
+
+/// NTT command inputs (one read data bus)
+#[derive(Copy, Clone, Default)]
+pub struct NttSpIn {
+    pub start: bool,
+    pub inverse: bool,
+    pub rdata: Coeff,
+}
+
+/// NTT outputs (one memory port)
+#[derive(Copy, Clone, Default)]
+pub struct NttSpOut {
+    pub busy: bool,
+    pub done: bool,
+    pub port: MemReq,
+}
+
+/// FSM phases
+#[derive(Copy, Clone, PartialEq, Default)]
+pub enum SpPhase {
+    #[default]
+    Idle,
+    ReadA,
+    ReadB,
+    WriteA,
+    WriteB,
+    FinalRead,
+    FinalWrite,
+    Done,
+}
+
+/// State for the single-port NTT engine
+#[derive(Copy, Clone, Default)]
+pub struct NttSpState {
+    pub phase: SpPhase,
+
+    // transform parameters
+    pub inverse: bool,
+
+    // loop counters (same meaning as in kyber_ntt::NttState)
+    pub len: Bits<U9>,
+    pub start: Bits<U9>,
+    pub j: Bits<U9>,
+    pub k: Bits<U8>,
+
+    // final scaling index (invntt)
+    pub idx: Bits<U9>,
+
+    // r[j] latched in ReadB, new r[j+len] held from WriteA to WriteB
+    pub a: Coeff,
+    pub b_new: Coeff,
+}
+
+/// One-cycle step of the single-port FSM.
+pub fn ntt_step_single_port(st: NttSpState, inp: NttSpIn) -> (NttSpState, NttSpOut) {
+    let mut ns = st;
+    let mut out = NttSpOut {
+        busy: st.phase != SpPhase::Idle && st.phase != SpPhase::Done,
+        ..Default::default()
+    };
+
+    match st.phase {
+        SpPhase::Idle => {
+            if inp.start {
+                ns.inverse = inp.inverse;
+
+                if inp.inverse {
+                    ns.len = u9(2);
+                    ns.k = u8(127);
+                } else {
+                    ns.len = u9(128);
+                    ns.k = u8(1);
+                }
+
+                ns.start = u9(0);
+                ns.j = u9(0);
+                ns.idx = u9(0);
+                ns.phase = SpPhase::ReadA;
+            }
+        }
+
+        SpPhase::ReadA => {
+            out.port.addr = st.j.resize::<U8>();
+            ns.phase = SpPhase::ReadB;
+        }
+
+        SpPhase::ReadB => {
+            // r[j] arrives while r[j+len] is requested.
+            ns.a = inp.rdata;
+            out.port.addr = (st.j + st.len).resize::<U8>();
+            ns.phase = SpPhase::WriteA;
+        }
+
+        SpPhase::WriteA => {
+            let a = st.a;
+            let b = inp.rdata;
+            let z = zeta_from_k(st.k);
+
+            let (new_a, new_b) = if st.inverse {
+                let t = a;
+                (barrett_reduce(t + b), fqmul(z, b - t))
+            } else {
+                let t = fqmul(z, b);
+                (a + t, a - t)
+            };
+
+            out.port.addr = st.j.resize::<U8>();
+            out.port.we = true;
+            out.port.wdata = new_a;
+
+            ns.b_new = new_b;
+            ns.phase = SpPhase::WriteB;
+        }
+
+        SpPhase::WriteB => {
+            out.port.addr = (st.j + st.len).resize::<U8>();
+            out.port.we = true;
+            out.port.wdata = st.b_new;
+
+            // Advance loop counters (twiddle index changes once per block).
+            let endj = st.start + st.len;
+            let next_j = st.j + u9(1);
+
+            if next_j == endj {
+                let next_k = if st.inverse { st.k - u8(1) } else { st.k + u8(1) };
+                let next_start = st.start + (st.len << 1);
+
+                if next_start >= u9(256) {
+                    let next_len = if st.inverse { st.len << 1 } else { st.len >> 1 };
+                    if st.inverse && next_len > u9(128) {
+                        ns.phase = SpPhase::FinalRead;
+                        ns.idx = u9(0);
+                    } else if !st.inverse && next_len < u9(2) {
+                        ns.phase = SpPhase::Done;
+                    } else {
+                        ns.len = next_len;
+                        ns.start = u9(0);
+                        ns.j = u9(0);
+                        ns.k = next_k;
+                        ns.phase = SpPhase::ReadA;
+                    }
+                } else {
+                    ns.start = next_start;
+                    ns.j = next_start;
+                    ns.k = next_k;
+                    ns.phase = SpPhase::ReadA;
+                }
+            } else {
+                ns.j = next_j;
+                ns.phase = SpPhase::ReadA;
+            }
+        }
+
+        SpPhase::FinalRead => {
+            out.port.addr = st.idx.resize::<U8>();
+            ns.phase = SpPhase::FinalWrite;
+        }
+
+        SpPhase::FinalWrite => {
+            out.port.addr = st.idx.resize::<U8>();
+            out.port.we = true;
+            out.port.wdata = fqmul(inp.rdata, s16(INVNTT_F));
+
+            let next = st.idx + u9(1);
+            if next == u9(256) {
+                ns.phase = SpPhase::Done;
+            } else {
+                ns.idx = next;
+                ns.phase = SpPhase::FinalRead;
+            }
+        }
+
+        SpPhase::Done => {
+            out.done = true;
+            out.busy = false;
+            if !inp.start {
+                ns.phase = SpPhase::Idle;
+            }
+        }
+    }
+
+    (ns, out)
+}
+
+/// Runs the single-port FSM to completion on `mem` with 1-cycle synchronous
+/// read latency. Returns the cycle count (start pulse to done, inclusive).
+pub fn run_ntt_single_port(mem: &mut [Coeff; N], inverse: bool) -> u64 {
+    let mut st = NttSpState::default();
+    let mut start = true;
+    let mut raddr: usize = 0;
+    let mut cycles: u64 = 0;
+
+    loop {
+        let inp = NttSpIn {
+            start,
+            inverse,
+            rdata: mem[raddr],
+        };
+
+        let (ns, out) = ntt_step_single_port(st, inp);
+        st = ns;
+
+        let addr = out.port.addr.raw() as usize;
+        if out.port.we {
+            mem[addr] = out.port.wdata;
+        } else {
+            raddr = addr;
+        }
+
+        cycles += 1;
+        start = false;
+
+        if out.done {
+            return cycles;
+        }
+        if cycles > 20_000 {
+            panic!("single-port NTT FSM did not finish within the cycle bound");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kyber_poly::run_ntt;
+
+    fn sample_poly(seed: u32) -> [Coeff; N] {
+        let mut x = seed;
+        core::array::from_fn(|_| {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            s16(((x >> 16) % 3329) as i32)
+        })
+    }
+
+    #[test]
+    fn single_port_matches_dual_port() {
+        for (seed, inverse) in [(1, false), (2, false), (3, true), (4, true)] {
+            let mut dual = sample_poly(seed);
+            let mut single = dual;
+            run_ntt(&mut dual, inverse);
+            run_ntt_single_port(&mut single, inverse);
+            assert!(dual == single, "seed {} inverse {}", seed, inverse);
+        }
+    }
+
+    #[test]
+    fn single_port_cycle_counts() {
+        // 7 layers * 128 butterflies * 4 cycles, + start and done
+        let mut a = sample_poly(5);
+        assert_eq!(run_ntt_single_port(&mut a, false), 3586);
+        // + 256 * 2 cycles final scaling
+        assert_eq!(run_ntt_single_port(&mut a, true), 4098);
+    }
+}
@@ -16,6 +16,9 @@ pub const DU: usize = 10;
 pub const DV: usize = 4;
 
 pub const SYMBYTES: usize = 32;
+// Kyber message: one bit per coefficient, always exactly 32 bytes
+pub const INDCPA_MSGBYTES: usize = SYMBYTES;
+const _: () = assert!(INDCPA_MSGBYTES * 8 == N);
 
 pub const POLYBYTES: usize = 384; // 256*12/8
 pub const POLYCOMPRESSEDBYTES_DU10: usize = 320; // 256*10/8
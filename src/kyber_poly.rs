@@ -8,10 +8,12 @@
 
 use rhdl::prelude::*;
 
-use crate::kyber_arith::{barrett_reduce, csubq, fqmul, freeze, montgomery_reduce, Coeff};
-use crate::kyber_ntt::{ntt_step, MemReq, NttIn, NttState};
+use crate::kyber_arith::{barrett_reduce, csubq, fqmul, freeze, frommont, montgomery_reduce, tomont, Coeff};
+use crate::kyber_ntt::{ntt_step, ntt_step_fused, FinalScaling, MemReq, INVNTT_F, NttIn, NttState, Phase};
 use crate::kyber_params::*;
 use crate::shake::shake256;
+use crate::util::bytes::load_le;
+use crate::xof_stream::ShakeStream;
 
 /// Polynomial type used across Kyber modules.
 pub type Poly = [Coeff; N];
@@ -53,29 +55,40 @@ fn mem_write(mem: &mut Poly, req: MemReq) {
 /// Runs the reference NTT FSM (from `kyber_ntt.rs`) to completion on a local array.
 ///
 /// The FSM assumes 1-cycle synchronous read latency (Read -> Write), which we emulate.
-fn run_ntt(mem: &mut Poly, inverse: bool) {
+pub(crate) fn run_ntt(mem: &mut Poly, inverse: bool) {
+    run_ntt_with(mem, inverse, FinalScaling::PerCoeff);
+}
+
+fn run_ntt_with(mem: &mut Poly, inverse: bool, scaling: FinalScaling) {
+    run_ntt_traced(mem, inverse, scaling, &mut |_| {});
+}
+
+/// `run_ntt_with`, calling `on_cycle(mem)` after each cycle's writes land.
+///
+/// Reads follow the `NttIn` contract: addresses driven in a cycle are served
+/// in the next one, and only cycles the FSM spends in `Write`/`FinalWrite`
+/// sample `rdata`. Other cycles get zero, so a stray use of rdata outside
+/// those phases shows up as a wrong result rather than a lucky stale value.
+fn run_ntt_traced(mem: &mut Poly, inverse: bool, scaling: FinalScaling, on_cycle: &mut impl FnMut(&Poly)) {
+    let step = match scaling {
+        FinalScaling::PerCoeff => ntt_step,
+        FinalScaling::Fused => ntt_step_fused,
+    };
     let mut st = NttState::default();
 
     let mut start = true;
 
-    // Pending read addresses (issued in the previous cycle).
+    // Read addresses issued in the previous cycle.
     let mut pending_read_a: Bits<U8> = bits(0u128);
     let mut pending_read_b: Bits<U8> = bits(0u128);
-    let mut pending_valid = false;
-
-    // Data to present as synchronous BRAM outputs.
-    let mut rdata_a = c16(0);
-    let mut rdata_b = c16(0);
 
     // Safety bound: 256-pt NTT with 2-cycle butterflies + final pass fits well under this.
     for _cycle in 0..20_000 {
-        if pending_valid {
-            rdata_a = mem_read(mem, pending_read_a);
-            rdata_b = mem_read(mem, pending_read_b);
+        let (rdata_a, rdata_b) = if matches!(st.phase, Phase::Write | Phase::FinalWrite) {
+            (mem_read(mem, pending_read_a), mem_read(mem, pending_read_b))
         } else {
-            rdata_a = c16(0);
-            rdata_b = c16(0);
-        }
+            (c16(0), c16(0))
+        };
 
         let inp = NttIn {
             start,
@@ -84,19 +97,18 @@ fn run_ntt(mem: &mut Poly, inverse: bool) {
             rdata_b,
         };
 
-        let (ns, out) = ntt_step(st, inp);
+        let (ns, out) = step(st, inp);
         st = ns;
 
         // Apply writes (same-cycle).
         mem_write(mem, out.porta);
         mem_write(mem, out.portb);
+        on_cycle(mem);
 
-        // Capture next cycle's reads (only when ports are in read mode).
-        pending_valid = !out.porta.we; // Read phases set porta.we=false.
-        if pending_valid {
-            pending_read_a = out.porta.addr;
-            pending_read_b = out.portb.addr;
-        }
+        // Latch this cycle's addresses; they are only used if the FSM is now
+        // in a data phase, i.e. this was a read cycle.
+        pending_read_a = out.porta.addr;
+        pending_read_b = out.portb.addr;
 
         start = false;
 
@@ -108,6 +120,48 @@ fn run_ntt(mem: &mut Poly, inverse: bool) {
     panic!("NTT FSM did not finish within the cycle bound");
 }
 
+/// Loop-form NTT with the twiddle table passed in, for testing table variants.
+///
+/// Same butterflies, twiddle order and final `INVNTT_F` pass as the FSM, so
+/// `ntt_with_zetas(mem, inv, &ZETAS)` matches `run_ntt(mem, inv)` exactly.
+pub fn ntt_with_zetas(mem: &mut Poly, inverse: bool, zetas: &[i16; 128]) {
+    if inverse {
+        let mut k = 127usize;
+        let mut len = 2usize;
+        while len <= 128 {
+            for start in (0..N).step_by(2 * len) {
+                let z = c16(zetas[k]);
+                k -= 1;
+                for j in start..start + len {
+                    let t = mem[j];
+                    mem[j] = barrett_reduce(t + mem[j + len]);
+                    mem[j + len] = fqmul(z, mem[j + len] - t);
+                }
+            }
+            len <<= 1;
+        }
+        let f = c16(INVNTT_F as i16);
+        for c in mem.iter_mut() {
+            *c = fqmul(*c, f);
+        }
+    } else {
+        let mut k = 1usize;
+        let mut len = 128usize;
+        while len >= 2 {
+            for start in (0..N).step_by(2 * len) {
+                let z = c16(zetas[k]);
+                k += 1;
+                for j in start..start + len {
+                    let t = fqmul(z, mem[j + len]);
+                    mem[j + len] = mem[j] - t;
+                    mem[j] += t;
+                }
+            }
+            len >>= 1;
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Basic polynomial ops
 // -----------------------------------------------------------------------------
@@ -118,13 +172,38 @@ pub fn poly_reduce(a: &mut Poly) {
     }
 }
 
+/// Barrett reduction and freeze in one pass: any i16 coefficients end up
+/// as the canonical representative in [0, q). Packing and compression
+/// run this on their input and then read coefficients as plain u16.
+pub fn poly_canonicalize(a: &mut Poly) {
+    for c in a.iter_mut() {
+        *c = freeze(*c);
+    }
+}
+
 pub fn poly_csubq(a: &mut Poly) {
     for i in 0..N {
         a[i] = csubq(a[i]);
     }
 }
 
-/// Forward NTT.
+/// a <- a*R. Lifts a reference-domain NTT poly into the R-scaled domain that
+/// `poly_ntt` outputs and `poly_basemul_montgomery` expects.
+pub fn poly_tomont(a: &mut Poly) {
+    for c in a.iter_mut() {
+        *c = tomont(*c);
+    }
+}
+
+/// a <- a*R^-1. Inverse of `poly_tomont`, used before packing NTT-domain keys.
+pub fn poly_frommont(a: &mut Poly) {
+    for c in a.iter_mut() {
+        *c = frommont(*c);
+    }
+}
+
+/// Forward NTT. Runs the loop form (`ntt_with_zetas`), which matches the
+/// FSM bit for bit; `run_ntt` is kept for cycle-level checks.
 pub fn poly_ntt(a: &mut Poly) {
     const R2: i16 = 1353;
     let r2 = c16(R2);
@@ -132,13 +211,22 @@ pub fn poly_ntt(a: &mut Poly) {
         a[i] = fqmul(a[i], r2);
     }
 
-    run_ntt(a, false);
+    ntt_with_zetas(a, false, &ZETAS);
     poly_reduce(a);
 }
 
 /// Inverse NTT.
 pub fn poly_invntt(a: &mut Poly) {
-    run_ntt(a, true);
+    poly_invntt_with(a, FinalScaling::PerCoeff);
+}
+
+/// Inverse NTT with a choice of how the FSM applies the final factor f.
+/// `PerCoeff` runs the loop form (same result as the FSM), `Fused` the FSM.
+pub fn poly_invntt_with(a: &mut Poly, scaling: FinalScaling) {
+    match scaling {
+        FinalScaling::PerCoeff => ntt_with_zetas(a, true, &ZETAS),
+        FinalScaling::Fused => run_ntt_with(a, true, scaling),
+    }
 
     for i in 0..N {
         a[i] = montgomery_reduce(a[i].resize::<U32>());
@@ -192,18 +280,32 @@ pub fn poly_basemul_montgomery(r: &mut Poly, a: &Poly, b: &Poly) {
     }
 }
 
+/// r = a + b with no reduction, as in the reference.
+///
+/// Precondition: every exact sum fits in i16. In the CPAPKE flow the inputs
+/// are Barrett/Montgomery outputs (|x| <= q) plus noise (|e| <= 3) or a
+/// message coefficient (<= (q+1)/2), so |a + b| stays far below 2^15; callers
+/// reduce afterwards. Debug builds check the precondition.
 pub fn poly_add(r: &mut Poly, a: &Poly, b: &Poly) {
     for i in 0..N {
+        debug_assert!(fits_i16(a[i].raw() + b[i].raw()), "poly_add overflow at {}", i);
         r[i] = a[i] + b[i];
     }
 }
 
+/// r = a - b with no reduction; same precondition as `poly_add`.
 pub fn poly_sub(r: &mut Poly, a: &Poly, b: &Poly) {
     for i in 0..N {
+        debug_assert!(fits_i16(a[i].raw() - b[i].raw()), "poly_sub overflow at {}", i);
         r[i] = a[i] - b[i];
     }
 }
 
+#[inline(always)]
+fn fits_i16(x: i128) -> bool {
+    (i16::MIN as i128..=i16::MAX as i128).contains(&x)
+}
+
 pub fn polyvec_ntt(v: &mut PolyVec) {
     for i in 0..K {
         poly_ntt(&mut v[i]);
@@ -231,48 +333,18 @@ pub fn polyvec_pointwise_acc(r: &mut Poly, a: &PolyVec, b: &PolyVec) {
     poly_reduce(r);
 }
 
-// -----------------------------------------------------------------------------
-// Packing/unpacking (12-bit)
-// -----------------------------------------------------------------------------
-
-pub fn poly_tobytes(out: &mut [u8; POLYBYTES], a: &Poly) {
-    let mut t = [0u16; N];
-    for i in 0..N {
-        t[i] = coef_to_u16(freeze(a[i]));
-    }
-    for i in 0..128 {
-        let t0 = t[2 * i];
-        let t1 = t[2 * i + 1];
-        out[3 * i] = (t0 & 0xFF) as u8;
-        out[3 * i + 1] = ((t0 >> 8) as u8) | ((t1 << 4) as u8);
-        out[3 * i + 2] = (t1 >> 4) as u8;
-    }
-}
-
-pub fn poly_frombytes(a: &mut Poly, inp: &[u8; POLYBYTES]) {
-    for i in 0..128 {
-        let b0 = inp[3 * i] as u16;
-        let b1 = inp[3 * i + 1] as u16;
-        let b2 = inp[3 * i + 2] as u16;
-
-        let v0 = (b0 | ((b1 & 0x0F) << 8)) as i16;
-        let v1 = (((b1 >> 4) | (b2 << 4)) & 0x0FFF) as i16;
-
-        a[2 * i] = c16(v0);
-        a[2 * i + 1] = c16(v1);
-    }
-}
-
 // -----------------------------------------------------------------------------
 // Compression/decompression
 // -----------------------------------------------------------------------------
 
 pub fn poly_compress_du10(out: &mut [u8; POLYCOMPRESSEDBYTES_DU10], a: &Poly) {
+    let mut a = *a;
+    poly_canonicalize(&mut a);
     let mut k = 0usize;
     for i in 0..(N / 4) {
         let mut t = [0u16; 4];
         for j in 0..4 {
-            let v = coef_to_u16(freeze(a[4 * i + j])) as u32;
+            let v = coef_to_u16(a[4 * i + j]) as u32;
             let num = (v << 10).wrapping_add((Q as u32) / 2);
             t[j] = (num / (Q as u32)) as u16 & 0x03FF;
         }
@@ -302,9 +374,11 @@ pub fn poly_decompress_du10(a: &mut Poly, inp: &[u8; POLYCOMPRESSEDBYTES_DU10])
 }
 
 pub fn poly_compress_dv4(out: &mut [u8; POLYCOMPRESSEDBYTES_DV4], a: &Poly) {
+    let mut a = *a;
+    poly_canonicalize(&mut a);
     for i in 0..(N / 2) {
-        let a0 = coef_to_u16(freeze(a[2 * i])) as u32;
-        let a1 = coef_to_u16(freeze(a[2 * i + 1])) as u32;
+        let a0 = coef_to_u16(a[2 * i]) as u32;
+        let a1 = coef_to_u16(a[2 * i + 1]) as u32;
 
         let num0 = (a0 << 4).wrapping_add((Q as u32) / 2);
         let num1 = (a1 << 4).wrapping_add((Q as u32) / 2);
@@ -357,27 +431,29 @@ pub fn poly_tomsg(msg: &mut [u8; 32], a: &Poly) {
 // Noise: PRF + CBD (eta1=3, eta2=2)
 // -----------------------------------------------------------------------------
 
-fn load24_le(x: &[u8]) -> u32 {
-    (x[0] as u32) | ((x[1] as u32) << 8) | ((x[2] as u32) << 16)
+/// PRF input block `key || nonce`, shared by `prf_variable` and the streaming FSM samplers.
+#[inline(always)]
+pub fn prf_input(key: &[u8; SYMBYTES], nonce: u8) -> [u8; SYMBYTES + 1] {
+    let mut inbuf = [0u8; SYMBYTES + 1];
+    inbuf[..SYMBYTES].copy_from_slice(key);
+    inbuf[SYMBYTES] = nonce;
+    inbuf
 }
 
-fn load32_le(x: &[u8]) -> u32 {
-    (x[0] as u32)
-        | ((x[1] as u32) << 8)
-        | ((x[2] as u32) << 16)
-        | ((x[3] as u32) << 24)
+/// PRF(key, nonce) as an incremental SHAKE256 stream (same bytes as `prf_variable`).
+pub fn prf_stream(key: &[u8; SYMBYTES], nonce: u8) -> ShakeStream {
+    ShakeStream::shake256(&prf_input(key, nonce))
 }
 
-fn prf(out: &mut [u8], key: &[u8; SYMBYTES], nonce: u8) {
-    let mut inbuf = [0u8; 33];
-    inbuf[..32].copy_from_slice(key);
-    inbuf[32] = nonce;
-    shake256(&inbuf, out);
+/// PRF(key, nonce) = SHAKE256(key || nonce), squeezed to fill `out` of any
+/// length (eta * N / 4 bytes for CBD_eta).
+pub fn prf_variable(key: &[u8; SYMBYTES], nonce: u8, out: &mut [u8]) {
+    shake256(&prf_input(key, nonce), out);
 }
 
 fn cbd_eta1(r: &mut Poly, buf: &[u8]) {
     for i in 0..(N / 4) {
-        let t = load24_le(&buf[3 * i..3 * i + 3]);
+        let t = load_le::<3>(&buf[3 * i..]);
 
         let mut d = t & 0x0024_9249;
         d = d.wrapping_add((t >> 1) & 0x0024_9249);
@@ -393,7 +469,7 @@ fn cbd_eta1(r: &mut Poly, buf: &[u8]) {
 
 fn cbd_eta2(r: &mut Poly, buf: &[u8]) {
     for i in 0..(N / 8) {
-        let t = load32_le(&buf[4 * i..4 * i + 4]);
+        let t = load_le::<4>(&buf[4 * i..]);
 
         let mut d = t & 0x5555_5555;
         d = d.wrapping_add((t >> 1) & 0x5555_5555);
@@ -409,7 +485,7 @@ fn cbd_eta2(r: &mut Poly, buf: &[u8]) {
 pub fn cbd_eta(out: &mut Poly, eta: usize, key: &[u8; SYMBYTES], nonce: u8) {
     let buflen = eta * N / 4;
     let mut buf = vec![0u8; buflen];
-    prf(&mut buf, key, nonce);
+    prf_variable(key, nonce, &mut buf);
 
     match eta {
         3 => cbd_eta1(out, &buf),
@@ -417,3 +493,374 @@ pub fn cbd_eta(out: &mut Poly, eta: usize, key: &[u8; SYMBYTES], nonce: u8) {
         _ => panic!("Kyber: eta must be 2 or 3"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::kyber_ntt::NttOut;
+
+    // The ntt_cycles runner: latch both port addresses every cycle and always
+    // present mem at those addresses, whatever the FSM is doing.
+    fn run_ntt_latch_always(
+        mem: &mut Poly,
+        inverse: bool,
+        step: fn(NttState, NttIn) -> (NttState, NttOut),
+        on_cycle: &mut impl FnMut(&Poly),
+    ) {
+        let mut st = NttState::default();
+        let (mut ra, mut rb) = (0usize, 0usize);
+        for cycle in 0..20_000 {
+            let inp = NttIn { start: cycle == 0, inverse, rdata_a: mem[ra], rdata_b: mem[rb] };
+            let (ns, out) = step(st, inp);
+            st = ns;
+            mem_write(mem, out.porta);
+            mem_write(mem, out.portb);
+            on_cycle(mem);
+            ra = out.porta.addr.raw() as usize;
+            rb = out.portb.addr.raw() as usize;
+            if out.done {
+                return;
+            }
+        }
+        panic!("NTT FSM did not finish within the cycle bound");
+    }
+
+    #[test]
+    fn ntt_drivers_agree_cycle_by_cycle() {
+        let a: Poly = core::array::from_fn(|i| c16(((i * 1_237 + 5) % KYBER_Q as usize) as i16));
+        for inverse in [false, true] {
+            for (scaling, step) in [
+                (FinalScaling::PerCoeff, ntt_step as fn(_, _) -> _),
+                (FinalScaling::Fused, ntt_step_fused),
+            ] {
+                let mut trace_pending = Vec::new();
+                let mut pending = a;
+                run_ntt_traced(&mut pending, inverse, scaling, &mut |m| trace_pending.push(*m));
+
+                let mut trace_always = Vec::new();
+                let mut always = a;
+                run_ntt_latch_always(&mut always, inverse, step, &mut |m| trace_always.push(*m));
+
+                assert_eq!(trace_pending.len(), trace_always.len(), "inverse={} {:?}", inverse, scaling);
+                for (cycle, (x, y)) in trace_pending.iter().zip(trace_always.iter()).enumerate() {
+                    assert_eq!(x, y, "memory differs after cycle {} (inverse={} {:?})", cycle, inverse, scaling);
+                }
+                assert_eq!(pending, always);
+            }
+        }
+    }
+
+    #[test]
+    fn prf_stream_matches_oneshot_prf() {
+        let key: [u8; SYMBYTES] = core::array::from_fn(|i| (i as u8) ^ 0xC3);
+        for nonce in [0u8, 1, 4, 255] {
+            // eta=3 buffer length, long enough to cross a SHAKE256 block boundary
+            let mut a = [0u8; 3 * N / 4];
+            prf_variable(&key, nonce, &mut a);
+
+            let mut b = [0u8; 3 * N / 4];
+            prf_stream(&key, nonce).fill_bytes(&mut b);
+            assert_eq!(a, b, "nonce={}", nonce);
+        }
+    }
+
+    #[test]
+    fn cbd_eta_reads_eta_n_over_4_prf_bytes() {
+        let key: [u8; SYMBYTES] = core::array::from_fn(|i| i as u8);
+        for (eta, nonce) in [(2usize, 0u8), (2, 7), (3, 0), (3, 200)] {
+            let len = eta * N / 4;
+            let mut buf = vec![0u8; len];
+            prf_variable(&key, nonce, &mut buf);
+
+            // a shorter or longer squeeze is a prefix/extension of the same stream
+            let mut longer = vec![0u8; len + 50];
+            prf_variable(&key, nonce, &mut longer);
+            assert_eq!(buf[..], longer[..len]);
+
+            // coefficient i is popcount of its first eta bits minus the next eta
+            let bit = |k: usize| ((buf[k / 8] >> (k % 8)) & 1) as i16;
+            let expect: Vec<i16> = (0..N)
+                .map(|i| {
+                    let base = 2 * eta * i;
+                    (0..eta).map(|j| bit(base + j)).sum::<i16>() - (0..eta).map(|j| bit(base + eta + j)).sum::<i16>()
+                })
+                .collect();
+
+            let mut r: Poly = [c16(0); N];
+            cbd_eta(&mut r, eta, &key, nonce);
+            let got: Vec<i16> = r.iter().map(|&c| coef_to_i16(c)).collect();
+            assert_eq!(got, expect, "eta={} nonce={}", eta, nonce);
+            assert!(got.iter().all(|c| c.unsigned_abs() as usize <= eta));
+        }
+    }
+
+    fn modq(x: Coeff) -> i32 {
+        (coef_to_i16(x) as i32).rem_euclid(KYBER_Q)
+    }
+
+    #[test]
+    fn ntt_of_zero_is_zero() {
+        let mut a: Poly = [c16(0); N];
+        poly_ntt(&mut a);
+        assert!(a.iter().all(|&c| modq(c) == 0));
+        poly_invntt(&mut a);
+        assert!(a.iter().all(|&c| modq(c) == 0));
+    }
+
+    #[test]
+    fn ntt_of_impulse_matches_analytic() {
+        // NTT output is R*a_hat, R = 2^16 mod q
+        let r = (1i32 << 16) % KYBER_Q;
+
+        // delta at X^0: every residue mod (X^2 - zeta_i) is 1
+        let mut a: Poly = [c16(0); N];
+        a[0] = c16(1);
+        poly_ntt(&mut a);
+        for (i, &c) in a.iter().enumerate() {
+            assert_eq!(modq(c), if i % 2 == 0 { r } else { 0 }, "delta0 idx {}", i);
+        }
+
+        // delta at X^1: every residue is X
+        let mut a: Poly = [c16(0); N];
+        a[1] = c16(1);
+        poly_ntt(&mut a);
+        for (i, &c) in a.iter().enumerate() {
+            assert_eq!(modq(c), if i % 2 == 1 { r } else { 0 }, "delta1 idx {}", i);
+        }
+    }
+
+    #[test]
+    fn fused_invntt_scaling_matches_per_coeff() {
+        let mut x = 0x1234_5678u32;
+        for _ in 0..4 {
+            let a: Poly = core::array::from_fn(|_| {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                c16(((x >> 16) % 3329) as i16)
+            });
+
+            let mut per_coeff = a;
+            let mut fused = a;
+            run_ntt_with(&mut per_coeff, true, FinalScaling::PerCoeff);
+            run_ntt_with(&mut fused, true, FinalScaling::Fused);
+            for i in 0..N {
+                assert_eq!(fused[i], per_coeff[i], "idx {}", i);
+            }
+
+            let mut b = a;
+            poly_ntt(&mut b);
+            poly_invntt_with(&mut b, FinalScaling::Fused);
+            for i in 0..N {
+                assert_eq!(modq(b[i]), modq(a[i]), "roundtrip idx {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn invntt_inverts_impulses() {
+        for pos in [0usize, 1, 2, 127, 128, 255] {
+            let mut a: Poly = [c16(0); N];
+            a[pos] = c16(1);
+            poly_ntt(&mut a);
+            poly_invntt(&mut a);
+            for (i, &c) in a.iter().enumerate() {
+                assert_eq!(modq(c), (i == pos) as i32, "pos {} idx {}", pos, i);
+            }
+        }
+    }
+
+    #[test]
+    fn ntt_with_zetas_matches_fsm_and_catches_bad_table() {
+        let a: Poly = core::array::from_fn(|i| c16(((i * 1103 + 17) % 3329) as i16 - 1664));
+        for inverse in [false, true] {
+            let mut fsm = a;
+            run_ntt(&mut fsm, inverse);
+            let mut sw = a;
+            ntt_with_zetas(&mut sw, inverse, &ZETAS);
+            assert_eq!(sw, fsm, "inverse = {}", inverse);
+
+            // swapping two twiddles of the same stage must be visible in the output
+            let mut bad = ZETAS;
+            bad.swap(if inverse { 126 } else { 2 }, if inverse { 127 } else { 3 });
+            let mut wrong = a;
+            ntt_with_zetas(&mut wrong, inverse, &bad);
+            assert!(
+                wrong.iter().zip(fsm.iter()).any(|(&x, &y)| modq(x) != modq(y)),
+                "shuffled table not detected (inverse = {})", inverse
+            );
+        }
+    }
+
+    #[test]
+    fn r2_prescale_cancels_invntt_scaling() {
+        let a: Poly = core::array::from_fn(|i| c16(((i as i32 * 2131 + 7) % KYBER_Q - KYBER_Q / 2) as i16));
+
+        // The FSM sees a*R2/R = a*R. NTT then unscaled INTT gives 128*a*R,
+        // fqmul by f = R^2/128 gives a*R^2, the two Montgomery reductions give a.
+        let mut b = a;
+        poly_ntt(&mut b);
+        poly_invntt(&mut b);
+        for i in 0..N {
+            assert_eq!(modq(b[i]), modq(a[i]), "coeff {}", i);
+        }
+
+        // without the R2 pre-scale the roundtrip is off by exactly R^-1
+        let mut c = a;
+        run_ntt(&mut c, false);
+        poly_invntt(&mut c);
+        for i in 0..N {
+            assert_eq!(modq(c[i]) * (1 << 16) % KYBER_Q, modq(a[i]), "coeff {}", i);
+        }
+    }
+
+    // Textbook Kyber invntt in plain i64 arithmetic: twiddles 17^brv7(k) mod q,
+    // no Montgomery factors anywhere, final 1/128.
+    fn ref_invntt_plain(x: &[i64; N]) -> [i64; N] {
+        let q = KYBER_Q as i64;
+        let pow = |mut b: i64, mut e: u32| {
+            let mut r = 1i64;
+            while e > 0 {
+                if e & 1 == 1 { r = r * b % q }
+                b = b * b % q;
+                e >>= 1;
+            }
+            r
+        };
+        let zeta = |k: usize| pow(17, (k as u8).reverse_bits() as u32 >> 1);
+
+        let mut r = *x;
+        let mut k = 127usize;
+        let mut len = 2usize;
+        while len <= 128 {
+            for start in (0..N).step_by(2 * len) {
+                let z = zeta(k);
+                k -= 1;
+                for j in start..start + len {
+                    let t = r[j];
+                    r[j] = (t + r[j + len]) % q;
+                    r[j + len] = z * (r[j + len] - t) % q;
+                }
+            }
+            len <<= 1;
+        }
+        let inv128 = pow(128, KYBER_Q as u32 - 2);
+        r.map(|c| (c * inv128).rem_euclid(q))
+    }
+
+    #[test]
+    fn invntt_double_reduce_gives_r_inverse_domain() {
+        let x: Poly = core::array::from_fn(|i| c16(((i as i32 * 733 + 91) % KYBER_Q) as i16));
+        let expect = ref_invntt_plain(&x.map(|c| coef_to_i16(c) as i64));
+
+        // poly_invntt(x) = INTT(x) * R^-1, matching poly_ntt's R*NTT(a) output
+        let mut got = x;
+        poly_invntt(&mut got);
+        for i in 0..N {
+            assert_eq!(modq(got[i]) as i64 * (1 << 16) % KYBER_Q as i64, expect[i], "coeff {}", i);
+        }
+
+        // one reduction instead of two lands in a different domain (INTT(x) itself)
+        let mut single = x;
+        run_ntt(&mut single, true);
+        for c in single.iter_mut() {
+            *c = montgomery_reduce(c.resize::<U32>());
+        }
+        for i in 0..N {
+            assert_eq!(modq(single[i]) as i64, expect[i], "coeff {}", i);
+        }
+        assert!((0..N).any(|i| modq(single[i]) != modq(got[i])));
+    }
+
+    #[test]
+    fn poly_add_sub_worst_case_flow_inputs() {
+        // Largest magnitudes the CPAPKE flow feeds in: reduced/INTT outputs
+        // (|x| <= q) against noise (eta1 = 3) and message coefficients.
+        let q = KYBER_Q as i16;
+        let msg = ((KYBER_Q + 1) / 2) as i16;
+        for (x, y) in [(q, 3), (-q, -3), (q, msg), (-q, -q), (q, q), (q + 3, msg)] {
+            let a = [c16(x); N];
+            let b = [c16(y); N];
+            let mut r = [c16(0); N];
+            poly_add(&mut r, &a, &b);
+            assert_eq!(r[0].raw(), x as i128 + y as i128);
+            poly_sub(&mut r, &a, &b);
+            assert_eq!(r[N - 1].raw(), x as i128 - y as i128);
+        }
+
+        // The real flow, with the debug precondition checks armed.
+        for seed in [0u8, 0x7F, 0xFF] {
+            let (pk, sk) = crate::kyber_indcpa::indcpa_keypair_deterministic(&[seed; SYMBYTES]);
+            let m = [seed ^ 0xA5; INDCPA_MSGBYTES];
+            let ct = crate::kyber_indcpa::indcpa_enc(&pk, &m, &[seed.wrapping_add(1); SYMBYTES]);
+            assert_eq!(crate::kyber_indcpa::indcpa_dec(&sk, &ct), m);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "poly_add overflow")]
+    fn poly_add_flags_i16_overflow() {
+        let a = [c16(i16::MAX); N];
+        let b = [c16(1); N];
+        let mut r = [c16(0); N];
+        poly_add(&mut r, &a, &b);
+    }
+
+    #[test]
+    fn direct_poly_ntt_matches_fsm_path() {
+        let mut x = 0x1234_5678u32;
+        for _ in 0..8 {
+            let a: Poly = core::array::from_fn(|_| {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                c16((x >> 16) as i16 % KYBER_Q as i16)
+            });
+
+            let mut direct = a;
+            poly_ntt(&mut direct);
+            let mut fsm = a;
+            for c in fsm.iter_mut() {
+                *c = fqmul(*c, c16(1353));
+            }
+            run_ntt(&mut fsm, false);
+            poly_reduce(&mut fsm);
+            assert_eq!(direct, fsm);
+
+            let mut direct = a;
+            poly_invntt(&mut direct);
+            let mut fsm = a;
+            run_ntt(&mut fsm, true);
+            for c in fsm.iter_mut() {
+                *c = montgomery_reduce(montgomery_reduce(c.resize::<U32>()).resize::<U32>());
+            }
+            assert_eq!(direct, fsm);
+        }
+    }
+
+    #[test]
+    fn canonicalize_lands_in_zero_to_q() {
+        let q = KYBER_Q as i16;
+        let mut edge: Vec<i16> = vec![i16::MIN, i16::MIN + 1, -q - 1, -q, -q + 1, -1, 0, 1, q - 1, q, q + 1];
+        edge.extend([2 * q, 3 * q + 7, 9 * q, i16::MAX - 1, i16::MAX]);
+
+        let mut x = 0xC0FF_EE11u32;
+        let mut inputs = edge.clone();
+        inputs.extend((0..4000).map(|_| {
+            x = x.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+            (x >> 16) as u16 as i16
+        }));
+
+        for chunk in inputs.chunks(N) {
+            let mut a = [c16(0); N];
+            for (c, &v) in a.iter_mut().zip(chunk) {
+                *c = c16(v);
+            }
+            let orig = a;
+            poly_canonicalize(&mut a);
+            for (i, (c, o)) in a.iter().zip(orig.iter()).enumerate() {
+                let (c, o) = (c.raw() as i32, o.raw() as i32);
+                assert!((0..KYBER_Q).contains(&c), "{} -> {}", o, c);
+                assert_eq!(c, o.rem_euclid(KYBER_Q), "coeff {}", i);
+            }
+        }
+    }
+}
@@ -6,12 +6,13 @@
 
 use rhdl::prelude::*;
 
-use crate::kyber_params::{K, N, SYMBYTES};
-use crate::kyber_poly::{poly_ntt, Poly, PolyVec};
+use crate::kyber_params::{K, KYBER_Q, N, SYMBYTES};
+use crate::kyber_poly::{poly_tomont, polyvec_pointwise_acc, prf_stream, Poly, PolyVec};
 use crate::kyber_sampling_rhdl::{
     cbd2_step, cbd3_step, parse_uniform_step, ByteStreamIn, Cbd2Phase, Cbd2State, Cbd3Phase,
     Cbd3State, ParsePhase, ParseUniformState,
 };
+use crate::util::bytes::load_le;
 use crate::xof_stream::ShakeStream;
 
 pub type PolyMat = [[Poly; K]; K];
@@ -32,16 +33,38 @@ fn zero_mat() -> PolyMat {
     [[zero_poly(); K]; K]
 }
 
+/// One 12-bit candidate rejected by ParseUniform (value >= q), reported to
+/// the observer of `gen_matrix_with_observer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UniformRejection {
+    /// matrix position (row, column) of the polynomial being sampled
+    pub row: usize,
+    pub col: usize,
+    /// rejected value, in q..4096
+    pub value: u16,
+    /// coefficients already accepted for this polynomial
+    pub accepted: usize,
+    /// XOF bytes consumed so far, including the triple holding `value`
+    pub bytes: usize,
+}
+
 /// Run ParseUniform FSM to completion, consuming bytes from a SHAKE128 stream.
-fn run_parse_uniform(mut stream: ShakeStream) -> Poly {
+/// `on_reject(value, accepted, bytes)` is called per rejected candidate.
+fn run_parse_uniform(mut stream: ShakeStream, on_reject: &mut impl FnMut(u16, usize, usize)) -> Poly {
     let mut mem = zero_poly();
     let mut st = ParseUniformState::reset();
+    let mut triple = [0u8; 3];
+    let mut bytes = 0usize;
+    let mut accepted = 0usize;
 
     for _ in 0..250_000 {
         let want_byte = (!st.pend_valid) && (st.phase != ParsePhase::Done);
 
         let inp = if want_byte {
-            ByteStreamIn { valid: true, data: b8(stream.next_u8()) }
+            let byte = stream.next_u8();
+            triple[bytes % 3] = byte;
+            bytes += 1;
+            ByteStreamIn { valid: true, data: b8(byte) }
         } else {
             ByteStreamIn { valid: false, data: b8(0) }
         };
@@ -49,9 +72,25 @@ fn run_parse_uniform(mut stream: ShakeStream) -> Poly {
         let (ns, out) = parse_uniform_step(st, inp);
         st = ns;
 
+        // The FSM gives no rejection strobe; decode the completed triple the
+        // same way it does. d2 is only looked at if d1 did not fill the poly.
+        if want_byte && bytes.is_multiple_of(3) {
+            let t = load_le::<3>(&triple);
+            let d1 = (t & 0x0FFF) as u16;
+            let d2 = (t >> 12) as u16;
+            if d1 >= KYBER_Q as u16 {
+                on_reject(d1, accepted, bytes);
+            }
+            let d1_filled = d1 < KYBER_Q as u16 && accepted + 1 == N;
+            if !d1_filled && d2 >= KYBER_Q as u16 {
+                on_reject(d2, accepted + (d1 < KYBER_Q as u16) as usize, bytes);
+            }
+        }
+
         if out.wr.we {
             let addr = out.wr.addr.raw() as usize;
             mem[addr] = out.wr.data;
+            accepted += 1;
         }
         if out.done {
             return mem;
@@ -118,22 +157,53 @@ fn run_cbd3(mut stream: ShakeStream) -> Poly {
 }
 
 pub fn gen_matrix(rho: &[u8; SYMBYTES], transposed: bool) -> PolyMat {
+    gen_matrix_with_observer(rho, transposed, &mut |_| {})
+}
+
+/// `gen_matrix` with `label` absorbed after `rho || x || y` in every entry's
+/// SHAKE128 input, giving an independent matrix per label. Experimental
+/// domain separation, not part of Kyber/ML-KEM: only the empty label (which
+/// is exactly `gen_matrix`) interoperates.
+pub fn gen_matrix_with_label(rho: &[u8; SYMBYTES], transposed: bool, label: &[u8]) -> PolyMat {
+    gen_matrix_inner(rho, transposed, label, &mut |_| {})
+}
+
+/// `gen_matrix`, calling `observer` for every candidate the rejection
+/// sampler throws away.
+pub fn gen_matrix_with_observer(
+    rho: &[u8; SYMBYTES],
+    transposed: bool,
+    observer: &mut impl FnMut(UniformRejection),
+) -> PolyMat {
+    gen_matrix_inner(rho, transposed, &[], observer)
+}
+
+fn gen_matrix_inner(
+    rho: &[u8; SYMBYTES],
+    transposed: bool,
+    label: &[u8],
+    observer: &mut impl FnMut(UniformRejection),
+) -> PolyMat {
     let mut A = zero_mat();
 
     for i in 0..K {
         for j in 0..K {
-            let mut inbuf = [0u8; SYMBYTES + 2];
-            inbuf[..SYMBYTES].copy_from_slice(rho);
-
             let x = if transposed { i as u8 } else { j as u8 };
             let y = if transposed { j as u8 } else { i as u8 };
-            inbuf[SYMBYTES] = x;
-            inbuf[SYMBYTES + 1] = y;
+
+            let mut inbuf = Vec::with_capacity(SYMBYTES + 2 + label.len());
+            inbuf.extend_from_slice(rho);
+            inbuf.extend_from_slice(&[x, y]);
+            inbuf.extend_from_slice(label);
 
             let stream = ShakeStream::shake128(&inbuf);
-            let mut p = run_parse_uniform(stream);
+            let mut p = run_parse_uniform(stream, &mut |value, accepted, bytes| {
+                observer(UniformRejection { row: i, col: j, value, accepted, bytes })
+            });
 
-            poly_ntt(&mut p);
+            // The XOF output is A_hat itself (already NTT domain, as in the
+            // reference); lift it to the R-scaled domain of poly_ntt.
+            poly_tomont(&mut p);
             A[i][j] = p;
         }
     }
@@ -141,12 +211,43 @@ pub fn gen_matrix(rho: &[u8; SYMBYTES], transposed: bool) -> PolyMat {
     A
 }
 
-pub fn poly_getnoise(seed: &[u8; SYMBYTES], nonce: u8, eta: usize) -> Poly {
-    let mut inbuf = [0u8; SYMBYTES + 1];
-    inbuf[..SYMBYTES].copy_from_slice(seed);
-    inbuf[SYMBYTES] = nonce;
+/// A_hat sampled once (NTT domain, times R, non-transposed layout).
+///
+/// A^T holds the same entries, so keygen (A * s) and encryption (A^T * r)
+/// both read this one matrix instead of running the rejection sampler twice.
+#[derive(Clone, Copy)]
+pub struct MatrixA {
+    a: PolyMat,
+}
+
+impl MatrixA {
+    pub fn from_rho(rho: &[u8; SYMBYTES]) -> Self {
+        Self { a: gen_matrix(rho, false) }
+    }
+
+    /// Entry A_hat[i][j].
+    pub fn entry(&self, i: usize, j: usize) -> &Poly {
+        &self.a[i][j]
+    }
 
-    let stream = ShakeStream::shake256(&inbuf);
+    /// Row i of A_hat times v (pointwise, accumulated and reduced).
+    pub fn row_times_vec(&self, i: usize, v: &PolyVec) -> Poly {
+        let mut acc = zero_poly();
+        polyvec_pointwise_acc(&mut acc, &self.a[i], v);
+        acc
+    }
+
+    /// Row i of A_hat^T (column i of A_hat) times v.
+    pub fn transposed_row_times_vec(&self, i: usize, v: &PolyVec) -> Poly {
+        let col: PolyVec = core::array::from_fn(|j| self.a[j][i]);
+        let mut acc = zero_poly();
+        polyvec_pointwise_acc(&mut acc, &col, v);
+        acc
+    }
+}
+
+pub fn poly_getnoise(seed: &[u8; SYMBYTES], nonce: u8, eta: usize) -> Poly {
+    let stream = prf_stream(seed, nonce);
     match eta {
         2 => run_cbd2(stream),
         3 => run_cbd3(stream),
@@ -162,3 +263,136 @@ pub fn polyvec_getnoise(seed: &[u8; SYMBYTES], mut nonce: u8, eta: usize) -> (Po
     }
     (v, nonce)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kyber_indcpa;
+    use crate::kyber_poly::cbd_eta;
+
+    #[test]
+    fn fsm_noise_matches_reference_cbd() {
+        let seed: [u8; SYMBYTES] = core::array::from_fn(|i| (i as u8).wrapping_mul(29) ^ 0x5A);
+        for eta in [2usize, 3] {
+            for nonce in [0u8, 1, 7, 255] {
+                let fsm = poly_getnoise(&seed, nonce, eta);
+                let mut reference = zero_poly();
+                cbd_eta(&mut reference, eta, &seed, nonce);
+                assert_eq!(fsm, reference, "eta={} nonce={}", eta, nonce);
+            }
+        }
+    }
+
+    #[test]
+    fn fsm_matrix_matches_reference_gen_matrix() {
+        let rho: [u8; SYMBYTES] = core::array::from_fn(|i| (i as u8).wrapping_mul(7).wrapping_add(3));
+        for transposed in [false, true] {
+            let fsm = gen_matrix(&rho, transposed);
+            let reference = kyber_indcpa::gen_matrix(&rho, transposed);
+            assert!(fsm == reference, "transposed={}", transposed);
+        }
+    }
+
+    #[test]
+    fn matrix_label_domain_separates() {
+        let rho: [u8; SYMBYTES] = core::array::from_fn(|i| (i as u8).wrapping_mul(7));
+        for transposed in [false, true] {
+            let standard = gen_matrix(&rho, transposed);
+            assert!(gen_matrix_with_label(&rho, transposed, b"") == standard);
+
+            let a = gen_matrix_with_label(&rho, transposed, b"instance-a");
+            let b = gen_matrix_with_label(&rho, transposed, b"instance-b");
+            for i in 0..K {
+                for j in 0..K {
+                    assert!(a[i][j] != standard[i][j], "[{}][{}]", i, j);
+                    assert!(a[i][j] != b[i][j], "[{}][{}]", i, j);
+                }
+            }
+            // deterministic per label
+            assert!(gen_matrix_with_label(&rho, transposed, b"instance-a") == a);
+        }
+    }
+
+    #[test]
+    fn matrix_ordering_follows_reference_nonce_convention() {
+        // Kyber ref gen_matrix: A[i][j] = Parse(XOF(rho, j, i)),
+        // A^T[i][j] = Parse(XOF(rho, i, j)). Both implementations must agree
+        // with that, not just with each other.
+        let rho: [u8; SYMBYTES] = core::array::from_fn(|i| (i as u8).wrapping_mul(13) ^ 0x3C);
+        let xof = |x: u8, y: u8| {
+            let mut p = zero_poly();
+            kyber_indcpa::rej_uniform(&mut p, &rho, x, y);
+            poly_tomont(&mut p);
+            p
+        };
+
+        for gen in [gen_matrix, kyber_indcpa::gen_matrix] {
+            let a = gen(&rho, false);
+            let a_t = gen(&rho, true);
+            for i in 0..K {
+                for j in 0..K {
+                    assert!(a[i][j] == xof(j as u8, i as u8), "A[{}][{}]", i, j);
+                    assert!(a_t[i][j] == xof(i as u8, j as u8), "A^T[{}][{}]", i, j);
+                    assert!(a_t[i][j] == a[j][i]);
+                }
+            }
+            // off-diagonal entries differ, so a swapped (x, y) would be caught
+            assert!(a[0][1] != a[1][0]);
+        }
+    }
+
+    #[test]
+    fn matrix_a_transposed_product_matches_regenerated() {
+        let rho: [u8; SYMBYTES] = core::array::from_fn(|i| (i as u8) ^ 0xC3);
+        let m = MatrixA::from_rho(&rho);
+        let a = gen_matrix(&rho, false);
+        let a_t = gen_matrix(&rho, true);
+
+        let seed = [0x42u8; SYMBYTES];
+        let (mut v, _) = polyvec_getnoise(&seed, 0, 3);
+        crate::kyber_poly::polyvec_ntt(&mut v);
+
+        for i in 0..K {
+            let mut expected = zero_poly();
+            polyvec_pointwise_acc(&mut expected, &a_t[i], &v);
+            assert!(m.transposed_row_times_vec(i, &v) == expected, "A^T row {}", i);
+
+            polyvec_pointwise_acc(&mut expected, &a[i], &v);
+            assert!(m.row_times_vec(i, &v) == expected, "A row {}", i);
+        }
+    }
+
+    #[test]
+    fn matrix_observer_sees_every_rejection() {
+        let rho = [0xA5u8; SYMBYTES];
+        let mut seen = Vec::new();
+        let a = gen_matrix_with_observer(&rho, false, &mut |r| seen.push(r));
+        assert!(a == gen_matrix(&rho, false));
+
+        for i in 0..K {
+            for j in 0..K {
+                // count rejections straight from the XOF
+                let mut inbuf = [0u8; SYMBYTES + 2];
+                inbuf[..SYMBYTES].copy_from_slice(&rho);
+                inbuf[SYMBYTES] = j as u8;
+                inbuf[SYMBYTES + 1] = i as u8;
+                let mut stream = ShakeStream::shake128(&inbuf);
+                let (mut ctr, mut expected) = (0usize, 0usize);
+                while ctr < N {
+                    let b: [u8; 3] = core::array::from_fn(|_| stream.next_u8());
+                    let t = load_le::<3>(&b);
+                    for d in [t & 0xFFF, t >> 12] {
+                        if ctr == N {
+                            break;
+                        }
+                        if (d as i32) < KYBER_Q { ctr += 1 } else { expected += 1 }
+                    }
+                }
+
+                let got: Vec<_> = seen.iter().filter(|r| r.row == i && r.col == j).collect();
+                assert_eq!(got.len(), expected, "A[{}][{}]", i, j);
+                assert!(got.iter().all(|r| r.value as i32 >= KYBER_Q && r.accepted < N && r.bytes.is_multiple_of(3)));
+            }
+        }
+    }
+}
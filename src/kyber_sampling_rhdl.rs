@@ -8,6 +8,7 @@ use rhdl::prelude::*;
 
 use crate::kyber_arith::Coeff;
 use crate::kyber_params::{KYBER_N, KYBER_Q};
+use crate::util::bytes::load_le;
 
 // ------------------------------
 // Streaming interface (generic)
@@ -152,12 +153,10 @@ pub fn parse_uniform_step(mut st: ParseUniformState, inp: ByteStreamIn) -> (Pars
                 st.buf_cnt = bits(0u128);
 
                 // parse 3 bytes -> two 12-bit values
-                let b0 = u8_from_bits(st.buf0) as u32;
-                let b1 = u8_from_bits(st.buf1) as u32;
-                let b2 = u8_from_bits(st.buf2) as u32;
+                let t = load_le::<3>(&[u8_from_bits(st.buf0), u8_from_bits(st.buf1), u8_from_bits(st.buf2)]);
 
-                let d1 = (b0 | (b1 << 8)) & 0x0FFF;
-                let d2 = ((b1 >> 4) | (b2 << 4)) & 0x0FFF;
+                let d1 = t & 0x0FFF;
+                let d2 = t >> 12;
 
                 let q = KYBER_Q as u32;
                 let accepted1 = d1 < q;
@@ -269,15 +268,7 @@ pub fn cbd2_step(mut st: Cbd2State, inp: ByteStreamIn) -> (Cbd2State, SampleOut)
                 st.b3 = inp.data;
                 st.buf_cnt = bits(0u128);
 
-                let b0: u8 = st.b0.raw() as u8;
-                let b1: u8 = st.b1.raw() as u8;
-                let b2: u8 = st.b2.raw() as u8;
-                let b3: u8 = st.b3.raw() as u8;
-
-                let t: u32 = (b0 as u32)
-                    | ((b1 as u32) << 8)
-                    | ((b2 as u32) << 16)
-                    | ((b3 as u32) << 24);
+                let t = load_le::<4>(&[st.b0.raw() as u8, st.b1.raw() as u8, st.b2.raw() as u8, st.b3.raw() as u8]);
 
                 let mut d = t & 0x5555_5555;
                 d = d.wrapping_add((t >> 1) & 0x5555_5555);
@@ -375,10 +366,7 @@ pub fn cbd3_step(mut st: Cbd3State, inp: ByteStreamIn) -> (Cbd3State, SampleOut)
                 st.b2 = inp.data;
                 st.buf_cnt = bits(0u128);
 
-                let t: u32 =
-                    (u8_from_bits(st.b0) as u32) |
-                    ((u8_from_bits(st.b1) as u32) << 8) |
-                    ((u8_from_bits(st.b2) as u32) << 16);
+                let t = load_le::<3>(&[u8_from_bits(st.b0), u8_from_bits(st.b1), u8_from_bits(st.b2)]);
 
                 let mut d = t & 0x0024_9249;
                 d = d.wrapping_add((t >> 1) & 0x0024_9249);
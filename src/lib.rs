@@ -1,9 +1,14 @@
+pub mod api;
+
 pub mod kyber_params;
 pub mod kyber_arith;
 pub mod kyber_keccak;
 pub mod shake;
 pub mod xof_stream;
 pub mod keccak;
+pub mod montgomery;
+pub mod ntt_control;
+pub mod poly_ops;
 pub mod kyber_sampling;
 pub mod kyber_sampling_rhdl;
 
@@ -11,6 +16,7 @@ pub mod kyber_poly;
 pub mod kyber_indcpa;
 pub mod kyber_kem;
 pub mod kyber_ntt;
+pub mod kyber_ntt_single_port;
 pub mod kyber_codec;
 
 pub mod dilithium_params;
@@ -21,9 +27,15 @@ pub mod dilithium_poly;
 pub mod dilithium_rounding;
 pub mod dilithium_sample;
 pub mod dilithium_pack;
+#[cfg(feature = "ml-dsa")]
 pub mod dilithium_mldsa;
 pub mod dilithium;
+pub mod dilithium_kat;
 pub mod dilithium_test;
 pub mod nist_drbg;
+pub mod shake_drbg;
+pub mod hybrid;
+pub mod kem_aead;
+pub mod util;
 
 
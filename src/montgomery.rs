@@ -0,0 +1,37 @@
+// Montgomery reduction shared by the Kyber and Dilithium datapaths.
+//
+// Both reference implementations compute, for R = 2^WIDTH,
+//   t = (low WIDTH bits of a) * QINV   mod 2^WIDTH   (signed)
+//   r = (a - t*Q) >> WIDTH
+// which is a * R^{-1} mod Q. They only differ in the widths and constants,
+// so the bit-accurate version lives here once and each scheme supplies them.
+
+use rhdl::prelude::*;
+
+pub trait Montgomery {
+    /// Coefficient width (U16 for Kyber, U32 for Dilithium).
+    type N: BitWidth;
+    /// Product width, 2 * N.
+    type W: BitWidth;
+    const Q: i128;
+    /// Q^{-1} mod 2^WIDTH, as the signed value the reference uses.
+    const QINV: i128;
+    /// Bits of N; R = 2^WIDTH.
+    const WIDTH: i32;
+
+    /// a * R^{-1} mod Q, with |result| < Q for |a| < Q * 2^(WIDTH-1).
+    #[inline(always)]
+    fn montgomery_reduce(a: SignedBits<Self::W>) -> SignedBits<Self::N> {
+        // t = (intN)a * QINV, truncated to N bits like the C cast
+        let t: SignedBits<Self::N> =
+            (a.resize::<Self::N>().resize::<Self::W>() * signed::<Self::W>(Self::QINV)).resize::<Self::N>();
+        let r: SignedBits<Self::W> = (a - t.resize::<Self::W>() * signed::<Self::W>(Self::Q)) >> Self::WIDTH;
+        r.resize::<Self::N>()
+    }
+
+    /// fqmul(a, b) = montgomery_reduce(a * b)
+    #[inline(always)]
+    fn fqmul(a: SignedBits<Self::N>, b: SignedBits<Self::N>) -> SignedBits<Self::N> {
+        Self::montgomery_reduce(a.resize::<Self::W>() * b.resize::<Self::W>())
+    }
+}
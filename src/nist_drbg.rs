@@ -5,22 +5,37 @@ use aes::Aes256;
 use cipher::{BlockEncrypt, KeyInit};
 use cipher::generic_array::GenericArray;
 
+use crate::util::rand::RandomBytes;
+
 pub struct NistDrbg {
     key: [u8; 32],
     v:   [u8; 16],
+    // AES-256 key schedule for `key`, rebuilt only when `update` changes the key
+    cipher: Aes256,
 }
 
 impl NistDrbg {
     pub fn new(seed48: &[u8; 48]) -> Self {
-        let mut drbg = Self { key: [0u8; 32], v: [0u8; 16] };
+        let key = [0u8; 32];
+        let mut drbg = Self { key, v: [0u8; 16], cipher: Aes256::new(GenericArray::from_slice(&key)) };
         drbg.update(Some(seed48));
         drbg
     }
 
-    fn aes256_encrypt_block(key: &[u8; 32], block16: &[u8; 16]) -> [u8; 16] {
-        let cipher = Aes256::new(GenericArray::from_slice(key));
+    /// Current (Key, V), taken between `randombytes` calls to checkpoint a run.
+    pub fn export_state(&self) -> ([u8; 32], [u8; 16]) {
+        (self.key, self.v)
+    }
+
+    /// Resumes a DRBG from `export_state`; the byte stream continues exactly
+    /// where the exported instance would have.
+    pub fn from_state(key: [u8; 32], v: [u8; 16]) -> Self {
+        Self { key, v, cipher: Aes256::new(GenericArray::from_slice(&key)) }
+    }
+
+    fn encrypt_block(&self, block16: &[u8; 16]) -> [u8; 16] {
         let mut block = GenericArray::clone_from_slice(block16);
-        cipher.encrypt_block(&mut block);
+        self.cipher.encrypt_block(&mut block);
         let mut out = [0u8; 16];
         out.copy_from_slice(&block);
         out
@@ -39,7 +54,7 @@ impl NistDrbg {
 
         for i in 0..3 {
             Self::inc_v(&mut self.v);
-            let block = Self::aes256_encrypt_block(&self.key, &self.v);
+            let block = self.encrypt_block(&self.v);
             temp[i * 16..(i + 1) * 16].copy_from_slice(&block);
         }
 
@@ -51,13 +66,14 @@ impl NistDrbg {
 
         self.key.copy_from_slice(&temp[0..32]);
         self.v.copy_from_slice(&temp[32..48]);
+        self.cipher = Aes256::new(GenericArray::from_slice(&self.key));
     }
 
     pub fn randombytes(&mut self, out: &mut [u8]) {
         let mut pos = 0usize;
         while pos < out.len() {
             Self::inc_v(&mut self.v);
-            let block = Self::aes256_encrypt_block(&self.key, &self.v);
+            let block = self.encrypt_block(&self.v);
             let take = core::cmp::min(16, out.len() - pos);
             out[pos..pos + take].copy_from_slice(&block[..take]);
             pos += take;
@@ -65,3 +81,102 @@ impl NistDrbg {
         self.update(None);
     }
 }
+
+impl RandomBytes for NistDrbg {
+    fn randombytes(&mut self, out: &mut [u8]) {
+        NistDrbg::randombytes(self, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The previous implementation: a fresh Aes256 for every block.
+    struct UncachedDrbg {
+        key: [u8; 32],
+        v: [u8; 16],
+    }
+
+    impl UncachedDrbg {
+        fn block(&self) -> [u8; 16] {
+            let cipher = Aes256::new(GenericArray::from_slice(&self.key));
+            let mut block = GenericArray::clone_from_slice(&self.v);
+            cipher.encrypt_block(&mut block);
+            let mut out = [0u8; 16];
+            out.copy_from_slice(&block);
+            out
+        }
+
+        fn update(&mut self, provided: Option<&[u8; 48]>) {
+            let mut temp = [0u8; 48];
+            for i in 0..3 {
+                NistDrbg::inc_v(&mut self.v);
+                temp[i * 16..(i + 1) * 16].copy_from_slice(&self.block());
+            }
+            if let Some(pd) = provided {
+                temp.iter_mut().zip(pd.iter()).for_each(|(t, p)| *t ^= p);
+            }
+            self.key.copy_from_slice(&temp[0..32]);
+            self.v.copy_from_slice(&temp[32..48]);
+        }
+
+        fn randombytes(&mut self, out: &mut [u8]) {
+            for chunk in out.chunks_mut(16) {
+                NistDrbg::inc_v(&mut self.v);
+                chunk.copy_from_slice(&self.block()[..chunk.len()]);
+            }
+            self.update(None);
+        }
+    }
+
+    #[test]
+    fn cached_cipher_output_unchanged() {
+        let seed: [u8; 48] = core::array::from_fn(|i| i as u8);
+        let mut fast = NistDrbg::new(&seed);
+        let mut slow = UncachedDrbg { key: [0u8; 32], v: [0u8; 16] };
+        slow.update(Some(&seed));
+
+        for len in [0usize, 1, 15, 16, 17, 32, 48, 100, 1000] {
+            let (mut a, mut b) = (vec![0u8; len], vec![0u8; len]);
+            fast.randombytes(&mut a);
+            slow.randombytes(&mut b);
+            assert_eq!(a, b, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn resume_from_exported_state() {
+        let seed = [0x5Au8; 48];
+        let lens = [32usize, 32, 32, 7, 64, 48];
+
+        let mut straight = NistDrbg::new(&seed);
+        let expected: Vec<Vec<u8>> = lens
+            .iter()
+            .map(|&n| {
+                let mut b = vec![0u8; n];
+                straight.randombytes(&mut b);
+                b
+            })
+            .collect();
+
+        // stop after the third request, resume in a fresh instance
+        let mut first = NistDrbg::new(&seed);
+        let mut got = Vec::new();
+        for &n in &lens[..3] {
+            let mut b = vec![0u8; n];
+            first.randombytes(&mut b);
+            got.push(b);
+        }
+        let (key, v) = first.export_state();
+
+        let mut resumed = NistDrbg::from_state(key, v);
+        for &n in &lens[3..] {
+            let mut b = vec![0u8; n];
+            resumed.randombytes(&mut b);
+            got.push(b);
+        }
+        assert_eq!(got, expected);
+        assert_eq!(resumed.export_state(), straight.export_state());
+    }
+}
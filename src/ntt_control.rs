@@ -0,0 +1,145 @@
+// Control-level view of the NTT FSMs, shared by the Kyber and Dilithium engines.
+//
+// Both engines step through the same phases (Idle, Read/Write per butterfly,
+// FinalRead/FinalWrite for the inverse's scaling pass, Done) and only differ
+// in their datapaths, so the control analysis lives here once and each
+// scheme implements `NttControl` for its `NttState`.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Control outputs of one FSM step; the datapath outputs are left out.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ControlOut {
+    pub busy: bool,
+    pub done: bool,
+    pub we_a: bool,
+    pub we_b: bool,
+}
+
+pub trait NttControl: Copy + Default {
+    type Phase: Copy + Debug + Eq + Hash;
+    /// Butterfly distance of the last forward stage (Kyber 2, Dilithium 1).
+    const MIN_LEN: usize;
+    /// Every register of the state, so two states with equal keys behave the same.
+    type Key: Eq + Hash;
+
+    fn phase(&self) -> Self::Phase;
+    fn key(&self) -> Self::Key;
+    /// One `ntt_step` with only the control inputs driven (read data zero).
+    fn control_step(self, start: bool, inverse: bool) -> (Self, ControlOut);
+}
+
+/// How the invntt applies the final factor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FinalScaling {
+    /// Separate FinalRead/FinalWrite pass over all n coefficients (reference).
+    #[default]
+    PerCoeff,
+    /// Factor folded into the last GS stage (len = 128), see each scheme's
+    /// `ntt_step_fused`. Saves the 2n-cycle scaling pass.
+    Fused,
+}
+
+/// Cycle count of one transform on `S`'s FSM, derived from its structure:
+/// 1 start cycle, one stage of n/2 two-cycle butterflies per len from n/2
+/// down to `S::MIN_LEN`, the 2n-cycle FinalRead/FinalWrite pass (inverse
+/// with `PerCoeff` only) and the done cycle. A measured count that
+/// disagrees means the FSM schedule changed.
+pub fn expected_ntt_cycles<S: NttControl>(n: usize, inverse: bool, final_scale: FinalScaling) -> u64 {
+    let stages = (n.trailing_zeros() - S::MIN_LEN.trailing_zeros()) as u64;
+    let butterflies = stages * (n as u64 / 2) * 2;
+    let scaling = if inverse && final_scale == FinalScaling::PerCoeff { 2 * n as u64 } else { 0 };
+    1 + butterflies + scaling + 1
+}
+
+/// One control-level step of `ntt_step`: phase and control inputs in, next
+/// phase and control outputs out. Read data only feeds the datapath, never
+/// the next-state logic, so it is not part of the relation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Transition<P> {
+    pub from: P,
+    pub start: bool,
+    pub inverse: bool,
+    pub to: P,
+    pub busy: bool,
+    pub done: bool,
+    pub we_a: bool,
+    pub we_b: bool,
+}
+
+/// Every distinct `Transition` reachable from reset, found by exploring the
+/// concrete FSM state space (counters included) under all control inputs.
+pub fn enumerate_transitions<S: NttControl>() -> Vec<Transition<S::Phase>> {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut found = HashSet::new();
+    let mut out = Vec::new();
+
+    let reset = S::default();
+    seen.insert(reset.key());
+    queue.push_back(reset);
+
+    while let Some(st) = queue.pop_front() {
+        for (start, inverse) in [(false, false), (false, true), (true, false), (true, true)] {
+            let (ns, o) = st.control_step(start, inverse);
+            let t = Transition {
+                from: st.phase(),
+                start,
+                inverse,
+                to: ns.phase(),
+                busy: o.busy,
+                done: o.done,
+                we_a: o.we_a,
+                we_b: o.we_b,
+            };
+            if found.insert(t) {
+                out.push(t);
+            }
+            if seen.insert(ns.key()) {
+                queue.push_back(ns);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // phases in declaration order: Idle, Read, Write, FinalRead, FinalWrite, Done
+    fn check_cover<S: NttControl>(phases: [S::Phase; 6]) {
+        let [idle, _, write, _, final_write, done] = phases;
+        let ts = enumerate_transitions::<S>();
+        for p in phases {
+            assert!(ts.iter().any(|t| t.from == p), "{:?} never left", p);
+            assert!(ts.iter().any(|t| t.to == p), "{:?} never entered", p);
+        }
+
+        // Done is reached by both transforms (forward straight from Write)
+        assert!(ts.iter().any(|t| t.from == write && t.to == done));
+        assert!(ts.iter().any(|t| t.from == final_write && t.to == done));
+        assert!(ts.iter().all(|t| t.done == (t.from == done)));
+        assert!(ts.contains(&Transition {
+            from: done, start: false, inverse: false, to: idle,
+            busy: false, done: true, we_a: false, we_b: false,
+        }));
+        // writes only happen in the write phases
+        assert!(ts.iter().all(|t| t.we_a == (t.from == write || t.from == final_write)));
+    }
+
+    #[test]
+    fn transitions_cover_every_phase() {
+        use crate::dilithium_ntt as d;
+        use crate::kyber_ntt as k;
+
+        check_cover::<k::NttState>([
+            k::Phase::Idle, k::Phase::Read, k::Phase::Write, k::Phase::FinalRead, k::Phase::FinalWrite, k::Phase::Done,
+        ]);
+        check_cover::<d::NttState>([
+            d::Phase::Idle, d::Phase::Read, d::Phase::Write, d::Phase::FinalRead, d::Phase::FinalWrite, d::Phase::Done,
+        ]);
+    }
+}
@@ -0,0 +1,182 @@
+// Standalone polynomial arithmetic for Z_q[X]/(X^256 + 1), one submodule per
+// modulus, with the same names in both:
+//
+//   ntt, intt, reduce      in place
+//   basemul, add, sub      return a new polynomial
+//   pointwise_acc          sum of basemuls over a vector, returns a polynomial
+//
+// These are thin wrappers over kyber_poly / dilithium_poly and keep their
+// domains: `ntt` output is R-scaled the way `basemul` expects, and `intt`
+// removes the R^-1 that `basemul` leaves, so
+//   ntt(a); ntt(b); c = basemul(a, b); intt(c)
+// is the plain negacyclic product a*b for either scheme.
+
+/// q = 3329, 16-bit coefficients (`SignedBits<U16>`), R = 2^16.
+pub mod kyber {
+    use crate::kyber_poly;
+    pub use crate::kyber_poly::{Poly, PolyVec};
+
+    /// Forward NTT (output in bit-reversed order, coefficients |x| < q).
+    pub fn ntt(a: &mut Poly) {
+        kyber_poly::poly_ntt(a);
+    }
+
+    /// Inverse NTT including the final scaling (`invntt_tomont`).
+    pub fn intt(a: &mut Poly) {
+        kyber_poly::poly_invntt(a);
+    }
+
+    /// Barrett reduction of every coefficient to a small representative.
+    pub fn reduce(a: &mut Poly) {
+        kyber_poly::poly_reduce(a);
+    }
+
+    /// NTT-domain product a*b*R^-1 (degree-1 basemuls over the 128 pairs).
+    pub fn basemul(a: &Poly, b: &Poly) -> Poly {
+        let mut r = *a;
+        kyber_poly::poly_basemul_montgomery(&mut r, a, b);
+        r
+    }
+
+    /// a + b with no reduction; see `poly_add` for the i16 precondition.
+    pub fn add(a: &Poly, b: &Poly) -> Poly {
+        let mut r = *a;
+        kyber_poly::poly_add(&mut r, a, b);
+        r
+    }
+
+    /// a - b with no reduction; same precondition as `add`.
+    pub fn sub(a: &Poly, b: &Poly) -> Poly {
+        let mut r = *a;
+        kyber_poly::poly_sub(&mut r, a, b);
+        r
+    }
+
+    /// sum_i basemul(a[i], b[i]), Barrett-reduced.
+    pub fn pointwise_acc(a: &PolyVec, b: &PolyVec) -> Poly {
+        let mut r = a[0];
+        kyber_poly::polyvec_pointwise_acc(&mut r, a, b);
+        r
+    }
+}
+
+/// q = 8380417, 32-bit coefficients (`i32`), R = 2^32.
+pub mod dilithium {
+    pub use crate::dilithium_poly::{Poly, PolyVec};
+
+    /// Forward NTT.
+    pub fn ntt(a: &mut Poly) {
+        a.ntt();
+    }
+
+    /// Inverse NTT including the final scaling by 2^32/256 (`invntt_tomont`).
+    pub fn intt(a: &mut Poly) {
+        a.intt();
+    }
+
+    /// Reduce every coefficient into [0, q).
+    pub fn reduce(a: &mut Poly) {
+        a.reduce();
+    }
+
+    /// NTT-domain product a*b*R^-1 (coefficient-wise Montgomery multiply).
+    pub fn basemul(a: &Poly, b: &Poly) -> Poly {
+        Poly::pointwise_mul(a, b)
+    }
+
+    /// a + b mod q.
+    pub fn add(a: &Poly, b: &Poly) -> Poly {
+        let mut r = *a;
+        r.add_assign(b);
+        r
+    }
+
+    /// a - b mod q.
+    pub fn sub(a: &Poly, b: &Poly) -> Poly {
+        let mut r = *a;
+        r.sub_assign(b);
+        r
+    }
+
+    /// sum_i basemul(a[i], b[i]) mod q.
+    pub fn pointwise_acc<const DIM: usize>(a: &PolyVec<DIM>, b: &PolyVec<DIM>) -> Poly {
+        PolyVec::pointwise_acc(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rhdl::prelude::*;
+
+    // Schoolbook product in Z_q[X]/(X^256 + 1), coefficients in [0, q).
+    fn negacyclic(a: &[i64], b: &[i64], q: i64) -> Vec<i64> {
+        let n = a.len();
+        let mut r = vec![0i64; n];
+        for i in 0..n {
+            for j in 0..n {
+                let p = a[i] * b[j] % q;
+                if i + j < n { r[i + j] += p } else { r[i + j - n] -= p }
+            }
+        }
+        r.iter().map(|x| x.rem_euclid(q)).collect()
+    }
+
+    #[test]
+    fn manual_multiplication_through_the_public_api() {
+        use super::kyber;
+        use crate::kyber_params::{K, KYBER_Q};
+
+        let q = KYBER_Q as i64;
+        let a_i: Vec<i64> = (0..256).map(|i| (i * 37 + 11) % q).collect();
+        let b_i: Vec<i64> = (0..256).map(|i| (i * i * 5 + 3) % q).collect();
+        let expect = negacyclic(&a_i, &b_i, q);
+
+        let to_poly = |v: &[i64]| -> kyber::Poly { core::array::from_fn(|i| signed::<U16>(v[i] as i128)) };
+        let (mut a, mut b) = (to_poly(&a_i), to_poly(&b_i));
+        kyber::ntt(&mut a);
+        kyber::ntt(&mut b);
+        let mut c = kyber::basemul(&a, &b);
+        kyber::intt(&mut c);
+        kyber::reduce(&mut c);
+        let got: Vec<i64> = c.iter().map(|x| (x.raw() as i64).rem_euclid(q)).collect();
+        assert_eq!(got, expect);
+
+        // K copies accumulate to K*a*b; take one back off with sub
+        let mut single = kyber::basemul(&a, &b);
+        kyber::intt(&mut single);
+        let mut acc = kyber::pointwise_acc(&[a; K], &[b; K]);
+        kyber::intt(&mut acc);
+        let mut rest = kyber::sub(&acc, &single);
+        kyber::reduce(&mut rest);
+        let got: Vec<i64> = rest.iter().map(|x| (x.raw() as i64).rem_euclid(q)).collect();
+        assert_eq!(got, expect.iter().map(|x| x * (K as i64 - 1) % q).collect::<Vec<_>>());
+        let twice = kyber::add(&single, &single);
+        assert!(twice.iter().zip(single.iter()).all(|(t, x)| t.raw() == 2 * x.raw()));
+
+        use super::dilithium;
+        use crate::dilithium_params::Q;
+
+        let q = Q as i64;
+        let a_i: Vec<i64> = (0..256).map(|i| (i * 1_234_567 + 89) % q).collect();
+        let b_i: Vec<i64> = (0..256).map(|i| (i * i * 7_654_321 + 5) % q).collect();
+        let expect = negacyclic(&a_i, &b_i, q);
+
+        let to_poly = |v: &[i64]| dilithium::Poly { coeffs: core::array::from_fn(|i| v[i] as i32) };
+        let (mut a, mut b) = (to_poly(&a_i), to_poly(&b_i));
+        dilithium::ntt(&mut a);
+        dilithium::ntt(&mut b);
+        let mut c = dilithium::basemul(&a, &b);
+        dilithium::intt(&mut c);
+        dilithium::reduce(&mut c);
+        assert!(c.coeffs.iter().zip(expect.iter()).all(|(&x, &e)| x as i64 == e));
+
+        let va = dilithium::PolyVec::<3> { v: [a; 3] };
+        let vb = dilithium::PolyVec::<3> { v: [b; 3] };
+        let acc = dilithium::pointwise_acc(&va, &vb);
+        let single = dilithium::basemul(&a, &b);
+        let mut zero = dilithium::sub(&acc, &dilithium::add(&single, &single));
+        zero = dilithium::sub(&zero, &single);
+        dilithium::reduce(&mut zero);
+        assert!(zero.coeffs.iter().all(|&x| x == 0));
+    }
+}
@@ -1,6 +1,9 @@
-use crate::keccak::keccak_f1600;
+use crate::keccak::keccak_p1600;
 
-fn absorb(rate: usize, input: &[u8], domain: u8) -> [u64; 25] {
+const KECCAK_ROUNDS: usize = 24;
+const TURBOSHAKE_ROUNDS: usize = 12;
+
+fn absorb(rate: usize, input: &[u8], domain: u8, rounds: usize) -> [u64; 25] {
     let mut st = [0u64; 25];
     let mut off = 0usize;
 
@@ -12,12 +15,12 @@ fn absorb(rate: usize, input: &[u8], domain: u8) -> [u64; 25] {
             }
             st[i] ^= lane;
         }
-        keccak_f1600(&mut st);
+        keccak_p1600(&mut st, rounds);
         off += rate;
     }
 
     // last block
-    let mut block = vec![0u8; rate];
+    let mut block = [0u8; 168]; // max rate
     block[..(input.len() - off)].copy_from_slice(&input[off..]);
     block[input.len() - off] ^= domain;
     block[rate - 1] ^= 0x80;
@@ -29,11 +32,11 @@ fn absorb(rate: usize, input: &[u8], domain: u8) -> [u64; 25] {
         }
         st[i] ^= lane;
     }
-    keccak_f1600(&mut st);
+    keccak_p1600(&mut st, rounds);
     st
 }
 
-fn squeeze(rate: usize, mut st: [u64; 25], out: &mut [u8]) {
+fn squeeze(rate: usize, mut st: [u64; 25], out: &mut [u8], rounds: usize) {
     let mut produced = 0usize;
     while produced < out.len() {
         let take = core::cmp::min(rate, out.len() - produced);
@@ -43,31 +46,149 @@ fn squeeze(rate: usize, mut st: [u64; 25], out: &mut [u8]) {
         }
         produced += take;
         if produced < out.len() {
-            keccak_f1600(&mut st);
+            keccak_p1600(&mut st, rounds);
         }
     }
 }
 
 pub fn sha3_256(input: &[u8]) -> [u8; 32] {
-    let st = absorb(136, input, 0x06);
+    let st = absorb(136, input, 0x06, KECCAK_ROUNDS);
     let mut out = [0u8; 32];
-    squeeze(136, st, &mut out);
+    squeeze(136, st, &mut out, KECCAK_ROUNDS);
     out
 }
 
 pub fn sha3_512(input: &[u8]) -> [u8; 64] {
-    let st = absorb(72, input, 0x06);
+    let st = absorb(72, input, 0x06, KECCAK_ROUNDS);
     let mut out = [0u8; 64];
-    squeeze(72, st, &mut out);
+    squeeze(72, st, &mut out, KECCAK_ROUNDS);
     out
 }
 
 pub fn shake128(input: &[u8], out: &mut [u8]) {
-    let st = absorb(168, input, 0x1F);
-    squeeze(168, st, out);
+    let st = absorb(168, input, 0x1F, KECCAK_ROUNDS);
+    squeeze(168, st, out, KECCAK_ROUNDS);
 }
 
 pub fn shake256(input: &[u8], out: &mut [u8]) {
-    let st = absorb(136, input, 0x1F);
-    squeeze(136, st, out);
+    let st = absorb(136, input, 0x1F, KECCAK_ROUNDS);
+    squeeze(136, st, out, KECCAK_ROUNDS);
+}
+
+/// TurboSHAKE128 (RFC 9861): SHAKE128 sponge on Keccak-p[1600, 12] with a
+/// caller-chosen domain byte in 0x01..=0x7F (0x1F is the default).
+pub fn turboshake128(input: &[u8], domain: u8, out: &mut [u8]) {
+    assert!((0x01..=0x7F).contains(&domain), "TurboSHAKE domain byte must be in 0x01..=0x7F");
+    let st = absorb(168, input, domain, TURBOSHAKE_ROUNDS);
+    squeeze(168, st, out, TURBOSHAKE_ROUNDS);
+}
+
+/// TurboSHAKE256 (RFC 9861), rate 136.
+pub fn turboshake256(input: &[u8], domain: u8, out: &mut [u8]) {
+    assert!((0x01..=0x7F).contains(&domain), "TurboSHAKE domain byte must be in 0x01..=0x7F");
+    let st = absorb(136, input, domain, TURBOSHAKE_ROUNDS);
+    squeeze(136, st, out, TURBOSHAKE_ROUNDS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::hex;
+
+    // RFC 9861 section 5, ptn(n) = 00 01 .. FA 00 01 .. (repeating mod 251)
+    fn ptn(n: usize) -> Vec<u8> {
+        (0..n).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn turboshake128_rfc9861_vectors() {
+        let mut out = [0u8; 32];
+        turboshake128(&[], 0x1F, &mut out);
+        assert_eq!(hex::encode(&out), "1e415f1c5983aff2169217277d17bb538cd945a397ddec541f1ce41af2c1b74c");
+
+        turboshake128(&ptn(17), 0x1F, &mut out);
+        assert_eq!(hex::encode(&out), "9c97d036a3bac819db70ede0ca554ec6e4c2a1a4ffbfd9ec269ca6a111161233");
+    }
+
+    #[test]
+    fn turboshake256_rfc9861_vector() {
+        let mut out = [0u8; 64];
+        turboshake256(&[], 0x1F, &mut out);
+        assert_eq!(
+            hex::encode(&out),
+            "367a329dafea871c7802ec67f905ae13c57695dc2c6663c61035f59a18f8e7db\
+             11edc0e12e91ea60eb6b32df06dd7f002fbafabb6e13ec1cc20d995547600db0"
+        );
+    }
+
+    #[test]
+    fn domain_byte_separates_outputs() {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        turboshake128(b"x", 0x07, &mut a);
+        turboshake128(b"x", 0x1F, &mut b);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn absorb_length_classes_unchanged() {
+        use crate::xof_stream::ShakeStream;
+
+        // empty, sub-block, exact block and multi-block for rates 72, 136 and 168
+        let lens = [0usize, 1, 71, 72, 73, 135, 136, 137, 167, 168, 169, 400];
+        let mut acc = Vec::new();
+        for &n in &lens {
+            let x: Vec<u8> = (0..n).map(|i| (i * 7 + 3) as u8).collect();
+            acc.extend_from_slice(&sha3_256(&x));
+            acc.extend_from_slice(&sha3_512(&x));
+
+            let (mut a, mut b) = ([0u8; 200], [0u8; 200]);
+            shake128(&x, &mut a);
+            ShakeStream::shake128(&x).fill_bytes(&mut b);
+            assert_eq!(a, b, "shake128 len {}", n);
+            shake256(&x, &mut a);
+            ShakeStream::shake256(&x).fill_bytes(&mut b);
+            assert_eq!(a, b, "shake256 len {}", n);
+        }
+        // SHA3-256 over all the digests above, from hashlib
+        assert_eq!(
+            hex::encode(&sha3_256(&acc)),
+            "b7112b76bce62f28157182a15f1b9172a48566a66fddcb9926de915d5338848f"
+        );
+    }
+
+    #[test]
+    fn exact_rate_inputs_pad_an_empty_final_block() {
+        use crate::xof_stream::ShakeStream;
+
+        // len = rate and 2*rate: every input byte goes through the full-block
+        // loop and the padding lands alone in a fresh block. hashlib vectors.
+        type Case = (usize, &'static str, fn(&[u8], &mut [u8]), fn(&[u8]) -> ShakeStream);
+        let xof: [Case; 4] = [
+            (168, "f15277eb61c4908d44a2853f3cde071ae2ed7a23461fbe162a1a98cf6875059c", shake128, ShakeStream::shake128),
+            (336, "278918d9abddd4a3c154affd8fe4f85e8e890eb2bbf659f393ca09ec8f9254bb", shake128, ShakeStream::shake128),
+            (136, "b7ff4073b3f5a8eabd6e17705ca7f6761a31058f9df781a6a47e3a3063b9d67a", shake256, ShakeStream::shake256),
+            (272, "e3299fa992163e7ffc875aff708dac93d2157e9b4ccaa2a13ba1ca4ef0b40f29", shake256, ShakeStream::shake256),
+        ];
+        for (n, expect, oneshot, stream) in xof {
+            let (mut a, mut b) = ([0u8; 32], [0u8; 32]);
+            oneshot(&ptn(n), &mut a);
+            stream(&ptn(n)).fill_bytes(&mut b);
+            assert_eq!(hex::encode(&a), expect, "len {}", n);
+            assert_eq!(a, b, "stream len {}", n);
+        }
+
+        assert_eq!(hex::encode(&sha3_256(&ptn(136))), "cf3ccff92480a29160c2d38317c430e14749bfee1788106957dfe73f8c4930e5");
+        assert_eq!(hex::encode(&sha3_256(&ptn(272))), "b7ccd55b6c2c3fa144c9e0624059294975a348b02f321abe289701d3012f7794");
+        assert_eq!(
+            hex::encode(&sha3_512(&ptn(72))),
+            "5d63f2bbe971a983ac6847480106e4e1264ee3a0befd79954914e1d86e795b2e\
+             18238f12fc5e46cb9cc78efdec610a93647cc04e1c23d8caaa6a58c21dd26c07"
+        );
+        assert_eq!(
+            hex::encode(&sha3_512(&ptn(144))),
+            "e1951b8bcb58ca75a34af80a7a2b765cad4257fe383a79b55bf21f180b75f6e5\
+             b08f09598851eeea7d13486387618d6c6bf88cf23c0088a3f783f59a06d60493"
+        );
+    }
 }
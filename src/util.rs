@@ -0,0 +1,5 @@
+pub mod bytes;
+pub mod ct;
+pub mod hex;
+pub mod key_header;
+pub mod rand;
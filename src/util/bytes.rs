@@ -0,0 +1,60 @@
+// Little-endian loads shared by the samplers (CBD, rejection parsing).
+
+/// Loads `N` bytes (`N <= 4`) from the start of `x` as a little-endian u32.
+#[inline(always)]
+pub fn load_le<const N: usize>(x: &[u8]) -> u32 {
+    const { assert!(N >= 1 && N <= 4) };
+    let mut v = 0u32;
+    for (i, &b) in x[..N].iter().enumerate() {
+        v |= (b as u32) << (8 * i);
+    }
+    v
+}
+
+/// Loads `N` bytes (`N <= 8`) from the start of `x` as a little-endian u64.
+#[inline(always)]
+pub fn load_le64<const N: usize>(x: &[u8]) -> u64 {
+    const { assert!(N >= 1 && N <= 8) };
+    let mut v = 0u64;
+    for (i, &b) in x[..N].iter().enumerate() {
+        v |= (b as u64) << (8 * i);
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUF: [u8; 10] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xff, 0x00];
+
+    #[test]
+    fn loads_at_offsets() {
+        assert_eq!(load_le::<2>(&BUF), 0x2301);
+        assert_eq!(load_le::<2>(&BUF[7..]), 0xffef);
+        assert_eq!(load_le::<3>(&BUF), 0x45_2301);
+        assert_eq!(load_le::<3>(&BUF[1..]), 0x67_4523);
+        assert_eq!(load_le::<3>(&BUF[5..]), 0xef_cdab);
+        assert_eq!(load_le::<4>(&BUF), 0x6745_2301);
+        assert_eq!(load_le::<4>(&BUF[3..]), 0xcdab_8967);
+        assert_eq!(load_le::<4>(&BUF[6..]), 0x00ff_efcd);
+        assert_eq!(load_le64::<8>(&BUF[2..]), 0x00ff_efcd_ab89_6745);
+        assert_eq!(load_le64::<5>(&BUF), 0x89_6745_2301);
+    }
+
+    #[test]
+    fn matches_from_le_bytes() {
+        for off in 0..=6 {
+            let w: [u8; 4] = BUF[off..off + 4].try_into().unwrap();
+            assert_eq!(load_le::<4>(&BUF[off..]), u32::from_le_bytes(w));
+            assert_eq!(load_le::<3>(&BUF[off..]), u32::from_le_bytes(w) & 0xff_ffff);
+            assert_eq!(load_le::<2>(&BUF[off..]), u32::from_le_bytes(w) & 0xffff);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn short_slice_panics() {
+        load_le::<3>(&BUF[8..]);
+    }
+}
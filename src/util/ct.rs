@@ -0,0 +1,41 @@
+// Constant-time helpers for software models where an index or condition may
+// one day be secret (e.g. masked implementations).
+
+/// All-ones if `a == b`, else 0, without a branch on the values.
+#[inline(always)]
+fn eq_mask(a: usize, b: usize) -> u16 {
+    let d = (a ^ b) as u64;
+    // top bit of (d | -d) is set iff d != 0
+    let ne = (d | d.wrapping_neg()) >> 63;
+    (ne as u16).wrapping_sub(1)
+}
+
+/// `table[idx]`, read by scanning every entry and keeping the matching one
+/// with a mask, so the memory access pattern does not depend on `idx`.
+/// Returns 0 if `idx` is out of range.
+pub fn ct_table_lookup(table: &[i16], idx: usize) -> i16 {
+    let mut acc: u16 = 0;
+    for (i, &v) in table.iter().enumerate() {
+        acc |= (v as u16) & eq_mask(i, idx);
+    }
+    acc as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kyber_params::ZETAS;
+
+    #[test]
+    fn ct_table_lookup_matches_indexing() {
+        for (idx, &z) in ZETAS.iter().enumerate() {
+            assert_eq!(ct_table_lookup(&ZETAS, idx), z, "idx={}", idx);
+        }
+        let signed = [-1i16, i16::MIN, i16::MAX, 0, -3329];
+        for (idx, &v) in signed.iter().enumerate() {
+            assert_eq!(ct_table_lookup(&signed, idx), v);
+        }
+        assert_eq!(ct_table_lookup(&ZETAS, ZETAS.len()), 0);
+        assert_eq!(ct_table_lookup(&ZETAS, usize::MAX), 0);
+    }
+}
@@ -0,0 +1,89 @@
+// Hex encode/decode shared by the KAT binaries.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    OddLength(usize),
+    InvalidChar { index: usize, ch: char },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::OddLength(n) => write!(f, "odd hex length {}", n),
+            HexError::InvalidChar { index, ch } => {
+                write!(f, "invalid hex character {:?} at index {}", ch, index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HexError {}
+
+/// Lowercase hex, two characters per byte.
+pub fn encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+fn nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decodes upper- or lowercase hex. Surrounding whitespace is ignored
+/// (as in .rsp lines); anything else that is not a hex digit is rejected.
+pub fn decode(s: &str) -> Result<Vec<u8>, HexError> {
+    let s = s.trim();
+    let b = s.as_bytes();
+    if !b.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(b.len()));
+    }
+
+    let digit = |i: usize| {
+        nibble(b[i]).ok_or(HexError::InvalidChar {
+            index: i,
+            ch: s[i..].chars().next().unwrap_or('?'),
+        })
+    };
+
+    let mut out = Vec::with_capacity(b.len() / 2);
+    for i in (0..b.len()).step_by(2) {
+        out.push((digit(i)? << 4) | digit(i + 1)?);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let s = encode(&data);
+        assert_eq!(s.len(), 512);
+        assert_eq!(decode(&s).unwrap(), data);
+        assert_eq!(decode(&s.to_uppercase()).unwrap(), data);
+        assert_eq!(decode("  0aFf\n").unwrap(), vec![0x0a, 0xff]);
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert_eq!(decode("abc"), Err(HexError::OddLength(3)));
+        assert_eq!(decode("0g"), Err(HexError::InvalidChar { index: 1, ch: 'g' }));
+        assert_eq!(decode("+1"), Err(HexError::InvalidChar { index: 0, ch: '+' }));
+        assert!(decode("ab cd").is_err());
+        // multi-byte chars must not panic on slicing
+        assert!(decode("é0").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+// Small self-describing header for keys stored on disk:
+//   magic (4) || version (1) || algorithm id (1) || parameter level (1) || key
+// The key part is the raw FIPS-format `to_bytes` encoding, unchanged.
+
+pub const MAGIC: [u8; 4] = *b"PQCK";
+pub const VERSION: u8 = 1;
+pub const HEADER_BYTES: usize = MAGIC.len() + 3;
+
+// Parameter level = NIST security category of the parameter set.
+pub const KYBER512_LEVEL: u8 = 1;
+pub const DILITHIUM2_LEVEL: u8 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Algorithm {
+    Kyber = 1,
+    Dilithium = 2,
+}
+
+/// Header followed by `body`.
+pub fn wrap(alg: Algorithm, level: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_BYTES + body.len());
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&[VERSION, alg as u8, level]);
+    out.extend_from_slice(body);
+    out
+}
+
+/// The body after a header matching (`VERSION`, `alg`, `level`). None on a
+/// bad magic, an unknown version or a different algorithm/parameter set.
+pub fn unwrap(bytes: &[u8], alg: Algorithm, level: u8) -> Option<&[u8]> {
+    if bytes.len() < HEADER_BYTES {
+        return None;
+    }
+    let (header, body) = bytes.split_at(HEADER_BYTES);
+    if header[..4] != MAGIC || header[4] != VERSION || header[5] != alg as u8 || header[6] != level {
+        return None;
+    }
+    Some(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dilithium::{keygen, PublicKey, SecretKey};
+    use crate::kyber_kem::{kem_keypair, KyberDecapsKey, KyberEncapsKey};
+
+    #[test]
+    fn versioned_keys_roundtrip_and_reject_mismatch() {
+        let (pk, sk) = kem_keypair(&[0x21u8; 32]);
+        let ek = KyberEncapsKey::from_bytes(&pk).unwrap();
+        let dk = KyberDecapsKey::from_bytes(&sk).unwrap();
+        let ek_v = ek.to_bytes_versioned();
+        let dk_v = dk.to_bytes_versioned();
+        assert_eq!(&ek_v[HEADER_BYTES..], &pk[..]);
+        assert_eq!(KyberEncapsKey::from_bytes_versioned(&ek_v), Some(ek));
+        assert_eq!(KyberDecapsKey::from_bytes_versioned(&dk_v), Some(dk));
+
+        let (dpk, dsk) = keygen([0x22u8; 32]);
+        let dpk_v = dpk.to_bytes_versioned();
+        let dsk_v = dsk.to_bytes_versioned();
+        assert_eq!(PublicKey::from_bytes_versioned(&dpk_v).unwrap().to_bytes(), dpk.to_bytes());
+        assert_eq!(SecretKey::from_bytes_versioned(&dsk_v).unwrap().to_bytes(), dsk.to_bytes());
+
+        // unknown version
+        let mut bad = ek_v.clone();
+        bad[4] = VERSION + 1;
+        assert_eq!(KyberEncapsKey::from_bytes_versioned(&bad), None);
+        let mut bad = dpk_v.clone();
+        bad[4] = 0;
+        assert!(PublicKey::from_bytes_versioned(&bad).is_none());
+
+        // wrong magic, wrong algorithm, wrong level, raw key without header
+        let mut bad = dsk_v.clone();
+        bad[0] ^= 1;
+        assert!(SecretKey::from_bytes_versioned(&bad).is_none());
+        let mut bad = dk_v.clone();
+        bad[5] = Algorithm::Dilithium as u8;
+        assert!(KyberDecapsKey::from_bytes_versioned(&bad).is_none());
+        let mut bad = dpk_v;
+        bad[6] = 3;
+        assert!(PublicKey::from_bytes_versioned(&bad).is_none());
+        assert!(KyberEncapsKey::from_bytes_versioned(&pk).is_none());
+    }
+}
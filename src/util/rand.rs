@@ -0,0 +1,39 @@
+// Randomness sources for the randomized (hedged) signing path.
+
+/// Anything that can fill a buffer with random bytes.
+pub trait RandomBytes {
+    fn randombytes(&mut self, out: &mut [u8]);
+}
+
+impl<R: RandomBytes + ?Sized> RandomBytes for &mut R {
+    fn randombytes(&mut self, out: &mut [u8]) {
+        (**self).randombytes(out)
+    }
+}
+
+/// Replays a fixed byte sequence, so tests can pin the randomness a
+/// `RandomBytes` consumer sees. Panics if more bytes are drawn than recorded.
+#[derive(Clone, Debug)]
+pub struct RandTape<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RandTape<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Bytes not yet replayed.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+}
+
+impl RandomBytes for RandTape<'_> {
+    fn randombytes(&mut self, out: &mut [u8]) {
+        assert!(out.len() <= self.remaining(), "RandTape exhausted");
+        out.copy_from_slice(&self.bytes[self.pos..self.pos + out.len()]);
+        self.pos += out.len();
+    }
+}
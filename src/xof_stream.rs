@@ -5,11 +5,15 @@
 //   but sampling_rhdl FSM becomes MUCH easier to test if it consumes a stream.
 //
 // This file provides ShakeStream::shake128(...) and ShakeStream::shake256(...)
-// with next_u8() / fill_bytes().
+// (or ShakeAbsorber for input that arrives in pieces)
+// with next_u8() / fill_bytes() and little-endian next_u16_le() / next_u24_le().
 
 #![allow(dead_code)]
 
+use core::fmt;
+
 use crate::keccak::keccak_f1600;
+use crate::util::bytes::load_le;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ShakeKind {
@@ -62,44 +66,32 @@ impl ShakeStream {
         }
     }
 
+    /// Next two bytes as a little-endian value.
+    #[inline(always)]
+    pub fn next_u16_le(&mut self) -> u16 {
+        self.next_le::<2>() as u16
+    }
+
+    /// Next three bytes as a little-endian value (the uniform samplers' triple).
+    #[inline(always)]
+    pub fn next_u24_le(&mut self) -> u32 {
+        self.next_le::<3>()
+    }
+
+    #[inline(always)]
+    fn next_le<const B: usize>(&mut self) -> u32 {
+        let b: [u8; B] = core::array::from_fn(|_| self.next_u8());
+        load_le::<B>(&b)
+    }
+
     // ------------------------------------------------------------------------
     // Internal sponge logic
     // ------------------------------------------------------------------------
 
     fn new(kind: ShakeKind, rate: usize, domain: u8, input: &[u8]) -> Self {
-        debug_assert!(rate == 168 || rate == 136);
-        let mut st = [0u64; 25];
-        let mut off = 0usize;
-
-        // Absorb full blocks
-        while input.len().saturating_sub(off) >= rate {
-            Self::xor_bytes_into_state(&mut st, &input[off..off + rate], rate);
-            keccak_f1600(&mut st);
-            off += rate;
-        }
-
-        // Final block with padding (no heap)
-        let rem = input.len() - off;
-        let mut block = [0u8; 168]; // max rate
-        if rem > 0 {
-            block[..rem].copy_from_slice(&input[off..]);
-        }
-        block[rem] ^= domain;
-        block[rate - 1] ^= 0x80;
-
-        Self::xor_bytes_into_state(&mut st, &block[..rate], rate);
-        keccak_f1600(&mut st);
-
-        // Initialize output buffer with first squeezed block
-        let mut me = Self {
-            kind,
-            rate,
-            state: st,
-            buf: [0u8; 168],
-            pos: 0,
-        };
-        me.squeeze_block_into_buf();
-        me
+        let mut absorber = ShakeAbsorber::new(kind, rate, domain);
+        absorber.absorb(input);
+        absorber.finish()
     }
 
     #[inline(always)]
@@ -123,6 +115,144 @@ impl ShakeStream {
     }
 }
 
+/// Absorbing half of a `ShakeStream`: takes the input in any number of
+/// pieces, so a caller can hash data it produces piecewise (packed polys)
+/// without concatenating it first. `finish` pads and returns the stream.
+#[derive(Clone, Debug)]
+pub struct ShakeAbsorber {
+    kind: ShakeKind,
+    rate: usize,
+    domain: u8,
+    state: [u64; 25],
+    block: [u8; 168], // max(rate)
+    pos: usize,       // bytes of `block` filled
+}
+
+impl ShakeAbsorber {
+    pub fn shake128() -> Self {
+        Self::new(ShakeKind::Shake128, 168, 0x1F)
+    }
+
+    pub fn shake256() -> Self {
+        Self::new(ShakeKind::Shake256, 136, 0x1F)
+    }
+
+    fn new(kind: ShakeKind, rate: usize, domain: u8) -> Self {
+        debug_assert!(rate == 168 || rate == 136);
+        Self { kind, rate, domain, state: [0u64; 25], block: [0u8; 168], pos: 0 }
+    }
+
+    /// Append `data` to the input; permutes once per full rate block.
+    pub fn absorb(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = (self.rate - self.pos).min(data.len());
+            self.block[self.pos..self.pos + take].copy_from_slice(&data[..take]);
+            self.pos += take;
+            data = &data[take..];
+            if self.pos == self.rate {
+                ShakeStream::xor_bytes_into_state(&mut self.state, &self.block[..self.rate], self.rate);
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Pad the final (possibly empty) block and switch to squeezing.
+    pub fn finish(mut self) -> ShakeStream {
+        let rate = self.rate;
+        self.block[self.pos..rate].fill(0);
+        self.block[self.pos] ^= self.domain;
+        self.block[rate - 1] ^= 0x80;
+        ShakeStream::xor_bytes_into_state(&mut self.state, &self.block[..rate], rate);
+        keccak_f1600(&mut self.state);
+
+        let mut me = ShakeStream {
+            kind: self.kind,
+            rate,
+            state: self.state,
+            buf: [0u8; 168],
+            pos: 0,
+        };
+        me.squeeze_block_into_buf();
+        me
+    }
+}
+
+/// A read past the byte budget of a `BoundedShakeStream`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamExhausted {
+    pub bound: usize,
+}
+
+impl fmt::Display for StreamExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SHAKE stream read past its {}-byte bound", self.bound)
+    }
+}
+
+impl std::error::Error for StreamExhausted {}
+
+/// `ShakeStream` with a fixed byte budget, for samplers specified to consume
+/// a known amount of XOF output. Reads past the budget fail instead of
+/// squeezing more.
+#[derive(Clone, Debug)]
+pub struct BoundedShakeStream {
+    inner: ShakeStream,
+    bound: usize,
+    remaining: usize,
+}
+
+impl BoundedShakeStream {
+    pub fn new(inner: ShakeStream, bound: usize) -> Self {
+        Self { inner, bound, remaining: bound }
+    }
+
+    /// Bytes that can still be read.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    #[inline(always)]
+    pub fn next_u8(&mut self) -> Result<u8, StreamExhausted> {
+        if self.remaining == 0 {
+            return Err(StreamExhausted { bound: self.bound });
+        }
+        self.remaining -= 1;
+        Ok(self.inner.next_u8())
+    }
+
+    /// Fills `out` entirely or, if that would exceed the bound, reads nothing.
+    pub fn fill_bytes(&mut self, out: &mut [u8]) -> Result<(), StreamExhausted> {
+        if out.len() > self.remaining {
+            return Err(StreamExhausted { bound: self.bound });
+        }
+        self.remaining -= out.len();
+        self.inner.fill_bytes(out);
+        Ok(())
+    }
+}
+
+/// Uniform rejection sampling shared by Kyber (12-bit, q = 3329) and
+/// Dilithium (23-bit, q = 8380417): each 3-byte little-endian group yields
+/// `24 / BITS` candidates of `BITS` bits (the top bit is dropped for 23),
+/// kept when < `Q`. Fills all of `out`.
+pub fn rej_uniform_generic<const BITS: u32, const Q: u32>(stream: &mut ShakeStream, out: &mut [u32]) {
+    const { assert!(BITS >= 8 && BITS <= 24 && Q <= (1 << BITS)) };
+    let mask = (1u32 << BITS) - 1;
+
+    let mut ctr = 0usize;
+    while ctr < out.len() {
+        let t = stream.next_u24_le();
+        for i in 0..24 / BITS {
+            let c = (t >> (i * BITS)) & mask;
+            if ctr < out.len() && c < Q {
+                out[ctr] = c;
+                ctr += 1;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,4 +282,148 @@ mod tests {
 
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn piecewise_absorb_matches_oneshot() {
+        let input: Vec<u8> = (0..700u32).map(|i| (i * 31 + 7) as u8).collect();
+        // piece sizes that land exactly on, just before and across rate boundaries
+        for pieces in [&[0usize, 136, 0, 136][..], &[1, 135, 1, 167], &[168, 168], &[5; 40], &[699, 1]] {
+            let mut cut = Vec::new();
+            let mut at = 0;
+            for &p in pieces {
+                cut.push(&input[at..at + p]);
+                at += p;
+            }
+            let total = &input[..at];
+
+            let mut a = ShakeAbsorber::shake256();
+            let mut b = ShakeAbsorber::shake128();
+            for piece in &cut {
+                a.absorb(piece);
+                b.absorb(piece);
+            }
+            let mut got = [0u8; 200];
+            let mut want = [0u8; 200];
+            a.finish().fill_bytes(&mut got);
+            ShakeStream::shake256(total).fill_bytes(&mut want);
+            assert_eq!(got, want, "shake256 pieces {:?}", pieces);
+            b.finish().fill_bytes(&mut got);
+            ShakeStream::shake128(total).fill_bytes(&mut want);
+            assert_eq!(got, want, "shake128 pieces {:?}", pieces);
+        }
+    }
+
+    #[test]
+    fn bounded_stream_errors_past_bound() {
+        let mut free = ShakeStream::shake128(b"bounded");
+        let mut bounded = BoundedShakeStream::new(ShakeStream::shake128(b"bounded"), 200);
+
+        // crosses the 168-byte block boundary inside the budget
+        let mut a = [0u8; 170];
+        bounded.fill_bytes(&mut a).unwrap();
+        assert_eq!(bounded.remaining(), 30);
+
+        // a request larger than what is left consumes nothing
+        assert_eq!(bounded.fill_bytes(&mut [0u8; 31]), Err(StreamExhausted { bound: 200 }));
+        assert_eq!(bounded.remaining(), 30);
+
+        let mut b = [0u8; 29];
+        bounded.fill_bytes(&mut b).unwrap();
+        let last = bounded.next_u8().unwrap();
+        assert_eq!(bounded.remaining(), 0);
+        assert_eq!(bounded.next_u8(), Err(StreamExhausted { bound: 200 }));
+        assert!(bounded.fill_bytes(&mut [0u8; 1]).is_err());
+        assert!(bounded.fill_bytes(&mut []).is_ok());
+
+        let mut expect = [0u8; 200];
+        free.fill_bytes(&mut expect);
+        assert_eq!(a[..], expect[..170]);
+        assert_eq!(b[..], expect[170..199]);
+        assert_eq!(last, expect[199]);
+    }
+
+    #[test]
+    fn multi_byte_reads_match_manual_assembly() {
+        let mut s = ShakeStream::shake128(b"le reads");
+        let mut reference = ShakeStream::shake128(b"le reads");
+
+        // enough reads of mixed widths to cross the 168-byte block boundary mid-value
+        for round in 0..40 {
+            let b: [u8; 5] = core::array::from_fn(|_| reference.next_u8());
+            assert_eq!(s.next_u16_le(), b[0] as u16 | (b[1] as u16) << 8, "round {}", round);
+            assert_eq!(s.next_u24_le(), b[2] as u32 | (b[3] as u32) << 8 | (b[4] as u32) << 16, "round {}", round);
+        }
+        assert_eq!(s.next_u8(), reference.next_u8());
+    }
+
+    // The samplers as they were open-coded in kyber_indcpa and dilithium_sample.
+    fn kyber_open_coded(stream: &mut ShakeStream) -> [u32; 256] {
+        let mut r = [0u32; 256];
+        let mut ctr = 0;
+        while ctr < 256 {
+            let b: [u8; 3] = core::array::from_fn(|_| stream.next_u8());
+            let t = load_le::<3>(&b);
+            let (d1, d2) = (t & 0x0FFF, t >> 12);
+            if d1 < 3329 {
+                r[ctr] = d1;
+                ctr += 1;
+            }
+            if ctr < 256 && d2 < 3329 {
+                r[ctr] = d2;
+                ctr += 1;
+            }
+        }
+        r
+    }
+
+    fn dilithium_open_coded(stream: &mut ShakeStream) -> [u32; 256] {
+        let mut r = [0u32; 256];
+        let mut ctr = 0;
+        while ctr < 256 {
+            let b: [u8; 3] = core::array::from_fn(|_| stream.next_u8());
+            let a = load_le::<3>(&b) & 0x7F_FFFF;
+            if a < 8_380_417 {
+                r[ctr] = a;
+                ctr += 1;
+            }
+        }
+        r
+    }
+
+    #[test]
+    fn rej_uniform_generic_reproduces_both_samplers() {
+        for seed in 0u8..4 {
+            let mut input = [seed.wrapping_mul(0x3B); 34];
+            input[32] = seed;
+            input[33] = 1;
+
+            let mut out = [0u32; 256];
+            let mut s = ShakeStream::shake128(&input);
+            rej_uniform_generic::<12, 3329>(&mut s, &mut out);
+            let mut reference = ShakeStream::shake128(&input);
+            assert_eq!(out, kyber_open_coded(&mut reference));
+            // same number of bytes consumed
+            assert_eq!(s.next_u8(), reference.next_u8());
+
+            let mut s = ShakeStream::shake128(&input);
+            rej_uniform_generic::<23, 8_380_417>(&mut s, &mut out);
+            let mut reference = ShakeStream::shake128(&input);
+            assert_eq!(out, dilithium_open_coded(&mut reference));
+            assert_eq!(s.next_u8(), reference.next_u8());
+        }
+
+        // and through the public wrappers
+        let rho = [0x17u8; 32];
+        let mut kyber = [rhdl::prelude::signed::<rhdl::prelude::U16>(0); 256];
+        crate::kyber_indcpa::rej_uniform(&mut kyber, &rho, 1, 0);
+        let mut input = [0x17u8; 34];
+        input[32] = 1;
+        input[33] = 0;
+        let expect = kyber_open_coded(&mut ShakeStream::shake128(&input));
+        assert!(kyber.iter().zip(expect.iter()).all(|(c, &e)| c.raw() as u32 == e));
+
+        let dil = crate::dilithium_sample::poly_uniform(&rho, 0x0001);
+        let expect = dilithium_open_coded(&mut ShakeStream::shake128(&input));
+        assert!(dil.coeffs.iter().zip(expect.iter()).all(|(&c, &e)| c as u32 == e));
+    }
 }